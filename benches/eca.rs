@@ -0,0 +1,58 @@
+// Elementary automata : the per cell stepping against the bit-packed one, over all 256 rules.
+//
+//      cargo bench --bench eca
+
+use gol_rs::{BoundaryType, ECA, ECARule};
+use gol_rs::eca;
+use gol_rs::eca_packed::PackedECA;
+
+use std::time::{Duration, Instant};
+
+const WIDTH : usize = 1000;
+const HEIGHT : usize = 500;
+
+fn report(name : &str, elapsed : Duration) {
+    let cells : f64 = (256 * WIDTH * (HEIGHT - 1)) as f64;
+
+    println!("{:<24} {:>10.2} ms {:>8.3} ns/cell", name, elapsed.as_secs_f64() * 1e3,
+        elapsed.as_secs_f64() * 1e9 / cells);
+}
+
+fn main() {
+    let initial_configuration : Vec<usize> = eca::random_configuration(WIDTH, 0.5, 1).unwrap();
+    let mut automata = ECA::new(0, WIDTH, HEIGHT, initial_configuration.clone(), BoundaryType::Torus).unwrap();
+
+    println!("256 rules, width {}, {} generations", WIDTH, HEIGHT - 1);
+
+    let start = Instant::now();
+    for rule in 0..=255 {
+        automata.set_rule(ECARule::elementary(rule)).unwrap();
+        automata.generate_per_cell();
+    }
+    report("ECA::generate_per_cell", start.elapsed());
+
+    let start = Instant::now();
+    for rule in 0..=255 {
+        automata.set_rule(ECARule::elementary(rule)).unwrap();
+        automata.generate();
+    }
+    report("ECA::generate", start.elapsed());
+
+    // without writing the rows into a grid
+    let mut packed = PackedECA::new(0, &initial_configuration, BoundaryType::Torus).unwrap();
+    let mut population : usize = 0;
+
+    let start = Instant::now();
+    for rule in 0..=255 {
+        packed.set_rule(rule);
+        packed.set_row(&initial_configuration).unwrap();
+        for _ in 1..HEIGHT {
+            packed.step();
+        }
+        population += packed.get_population();
+    }
+    report("PackedECA::step", start.elapsed());
+
+    // keeps the packed loop from being optimised away
+    println!("total final population : {}", population);
+}
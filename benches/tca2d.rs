@@ -0,0 +1,52 @@
+// Two dimensional automata : the reference backend against the bit-sliced one, and the
+// reference backend on several threads, on an 800 x 400 and a 3600 x 5400 Life soup.
+//
+//      cargo bench --bench tca2d
+
+use gol_rs::{BoundaryType, Grid, TCA2D};
+use gol_rs::rule::OuterTotalistic;
+use gol_rs::tca2d::Backend;
+
+use std::thread;
+use std::time::Instant;
+
+// (width, height, generations)
+const CASES : [(usize, usize, usize); 2] = [(800, 400, 100), (3600, 5400, 3)];
+
+// returns the population after the run, and the generations per second.
+fn run(backend : Backend, threads : usize, soup : &Grid, generations : usize) -> (usize, f64) {
+    let (width, height) : (usize, usize) = (soup.get_width(), soup.get_height());
+    let mut gol = TCA2D::with_backend(OuterTotalistic::new(224), width, height, 1, true, BoundaryType::Torus,
+        soup.clone(), backend).unwrap();
+
+    let start = Instant::now();
+    gol.step_parallel(generations, threads);
+    let elapsed = start.elapsed();
+    let rate : f64 = generations as f64 / elapsed.as_secs_f64();
+
+    println!("{:<12} {:>2} threads {:>10.2} ms {:>8.1} generations/s", format!("{:?}", backend), threads,
+        elapsed.as_secs_f64() * 1e3, rate);
+
+    return (gol.get_page(0).get_population(), rate);
+}
+
+fn main() {
+    let cores : usize = thread::available_parallelism().map_or(1, |threads| threads.get());
+    println!("{} cores", cores);
+
+    for (width, height, generations) in CASES.iter().copied() {
+        let soup : Grid = Grid::random(width, height, 0.5, 1).unwrap();
+
+        println!("B3/S23, {} x {}, {} generations", width, height, generations);
+
+        let (reference, serial_rate) : (usize, f64) = run(Backend::Reference, 1, &soup, generations);
+        let (bit_sliced, _) : (usize, f64) = run(Backend::BitSliced, 1, &soup, generations);
+        assert_eq!(reference, bit_sliced);
+
+        for threads in [2, 4, 8, cores].iter().copied().filter(|threads| *threads > 1 && *threads <= cores.max(4)) {
+            let (population, rate) : (usize, f64) = run(Backend::Reference, threads, &soup, generations);
+            assert_eq!(population, reference);
+            println!("{:>26} speedup {:.2}x", "", rate / serial_rate);
+        }
+    }
+}
@@ -1,14 +1,56 @@
-
 use crate::compass_direction::CompassDirection;
+use crate::error::Error;
+
+use std::str::FromStr;
 
+/// How the edges of a grid are glued together.
+///
+/// For a one dimensional automaton (a single row), `Cylinder` and `Torus` are both periodic.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BoundaryType {
+    /// cells beyond the edge are 0.
     Null,
+    /// columns wrap around; cells above and below the grid are 0.
     Cylinder,
+    /// columns wrap around with the rows flipped; cells above and below the grid are 0.
     Moebius,
+    /// rows and columns wrap around.
     Torus,
+    /// rows wrap around; columns wrap around with the rows flipped.
     Klein,
+    /// cells beyond the edge have the given value.
+    Constant(usize),
+    /// cells beyond the edge mirror the cells inside, the edge cell included.
+    Reflective,
 }
 
+impl FromStr for BoundaryType {
+    type Err = Error;
+
+    /// Parses `null`, `cylinder`, `moebius`, `torus` (or `periodic`), `klein`, `reflective`
+    /// or `constant:{value}`.
+    fn from_str(boundary_type : &str) -> Result<BoundaryType, Error> {
+        let lowercase : String = boundary_type.to_lowercase();
+
+        if let Some(value) = lowercase.strip_prefix("constant:") {
+            return value.parse::<usize>()
+                .map(BoundaryType::Constant)
+                .map_err(|e| Error::Parse(format!("invalid boundary type {} : {}", boundary_type, e)));
+        }
+
+        return match lowercase.as_str() {
+            "null" => Ok(BoundaryType::Null),
+            "cylinder" => Ok(BoundaryType::Cylinder),
+            "moebius" => Ok(BoundaryType::Moebius),
+            "torus" | "periodic" => Ok(BoundaryType::Torus),
+            "klein" => Ok(BoundaryType::Klein),
+            "reflective" => Ok(BoundaryType::Reflective),
+            _ => Err(Error::Parse(format!("unknown boundary type {}", boundary_type)))
+        };
+    }
+}
+
+/// Resolves the neighbours of a cell according to a `BoundaryType`.
 pub struct Boundary { // maybe rename to grid boundary
     width : usize,
     height : usize,
@@ -27,87 +69,94 @@ impl Boundary {
         };
     }
 
-    pub fn get_new_row_index(&self,
+    /// Returns the coordinates of the neighbour of (`row_index`, `column_index`) in direction
+    /// `offset`, or `None` if it lies outside the grid; its value is then `get_outside_value`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{Boundary, BoundaryType, CompassDirection};
+    ///
+    /// let west = CompassDirection {x : -1, y : 0, cardinal : true, active : true};
+    ///
+    /// let null = Boundary::new(4, 3, BoundaryType::Null);
+    /// assert_eq!(null.get_neighbour(1, 0, &west), None);
+    ///
+    /// let torus = Boundary::new(4, 3, BoundaryType::Torus);
+    /// assert_eq!(torus.get_neighbour(1, 0, &west), Some((1, 3)));
+    ///
+    /// let moebius = Boundary::new(4, 3, BoundaryType::Moebius);
+    /// assert_eq!(moebius.get_neighbour(0, 0, &west), Some((2, 3)));
+    /// ```
+    pub fn get_neighbour(&self,
         row_index : usize,
-        offset : &CompassDirection) -> isize {
-        // returns the new coordinates, shifted from the given cell, following the
-
-        // rules of the given BoundaryType
-        let mut new_row_index : isize;
-
-        new_row_index = match self.boundary_type {
-            BoundaryType::Null => self.get_null_row_index(row_index, offset),
-            _ => -1
-        };
-
-        return new_row_index;
-    }
-
-    pub fn get_new_column_index(&self,
         column_index : usize,
-        offset : &CompassDirection) -> isize {
-        // returns new column index based on the given directional shift.
-
-        let mut new_column_index : isize;
-
-        new_column_index = match self.boundary_type {
-            BoundaryType::Null => self.get_null_column_index(column_index, &offset),
-            _ => -1
-        };
+        offset : &CompassDirection) -> Option<(usize, usize)> {
 
-        return new_column_index;
+        return self.resolve(row_index as isize + offset.y, column_index as isize + offset.x);
     }
 
-    fn get_null_row_index(&self,
-        row_index : usize,
-        offset : &CompassDirection) -> isize { // should cast as usize
-        //
-
-        let mut new_row_index : isize = -1;
+    /// Returns the cell of the grid that (`row_index`, `column_index`) stands for once the
+    /// edges are glued together, or `None` if it lies outside the grid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{Boundary, BoundaryType};
+    ///
+    /// let cylinder = Boundary::new(4, 3, BoundaryType::Cylinder);
+    /// assert_eq!(cylinder.resolve(1, -1), Some((1, 3)));
+    /// assert_eq!(cylinder.resolve(-1, 1), None);
+    /// ```
+    pub fn resolve(&self, row_index : isize, column_index : isize) -> Option<(usize, usize)> {
+        let height : isize = self.height as isize;
+        let width : isize = self.width as isize;
+
+        // number of times the cell crosses the left / right edge
+        let column_crossings : isize = column_index.div_euclid(width);
+
+        let (new_row_index, new_column_index) : (isize, isize) = match self.boundary_type {
+            BoundaryType::Null | BoundaryType::Constant(_) => (row_index, column_index),
+            BoundaryType::Cylinder => (row_index, column_index.rem_euclid(width)),
+            BoundaryType::Torus =>
+                (row_index.rem_euclid(height), column_index.rem_euclid(width)),
+            BoundaryType::Moebius => {
+                let row : isize = if column_crossings % 2 != 0 { height - 1 - row_index } else { row_index };
+                (row, column_index.rem_euclid(width))
+            },
+            BoundaryType::Klein => {
+                let row : isize = row_index.rem_euclid(height);
+                let row : isize = if column_crossings % 2 != 0 { height - 1 - row } else { row };
+                (row, column_index.rem_euclid(width))
+            },
+            BoundaryType::Reflective =>
+                (Boundary::reflect(row_index, height), Boundary::reflect(column_index, width)),
+        };
 
-        if (row_index == 0 && offset.y < 0) || (row_index == self.height - 1 && offset.y > 0) {
-            // row == 0 and I'm trying to go to row == -1;
-            // null rule : use zero.
-        } else {
-            new_row_index = (row_index as isize) + offset.y;
+        if new_row_index < 0 || new_row_index >= height || new_column_index < 0 || new_column_index >= width {
+            return None;
         }
 
-        // if row_index == self.height - 1 && offset.y > 0 {
-        //     // row == last row and trying to go one more;
-        //     // null rule : use zero.
-        // } else {
-        //     new_row_index = (row_index as isize) + 1;
-        // }
-
-        return new_row_index;
+        return Some((new_row_index as usize, new_column_index as usize));
     }
 
+    /// Returns the value of cells outside the grid.
+    pub fn get_outside_value(&self) -> usize {
+        return match self.boundary_type {
+            BoundaryType::Constant(value) => value,
+            _ => 0
+        };
+    }
 
-    fn get_null_column_index(&self,
-        column_index : usize,
-        offset : &CompassDirection) -> isize {
-        // todo : add comments
-
-        // println!("column_index:{}, offset.x:{}", column_index, offset.x);
-
-        let mut new_column_index : isize = -1;
+    // mirrors `index` into 0..length, repeating the edge : -1 -> 0, -2 -> 1, length -> length - 1.
+    fn reflect(index : isize, length : isize) -> isize {
+        let index : isize = index.rem_euclid(2 * length);
 
-        if (column_index == 0 && offset.x < 0) ||
-            (column_index == self.width - 1 && offset.x > 0)  {
-            // column == 0 and trying to go to column == -1;
-            // null rule : use zero
-        } else {
-            new_column_index = (column_index as isize) + offset.x;
+        if index < length {
+            return index;
         }
 
-        // if column_index == self.width - 1 && offset.x > 0 {
-        //     // column == last column and trying to go one more;
-        //     // null rule : use zero.
-        // } else {
-        //     new_column_index = (column_index as isize) + offset.x;
-        // }
-
-        return new_column_index;
+        return 2 * length - 1 - index;
     }
 
 }
@@ -0,0 +1,441 @@
+// Object census of settled soups, following apgsearch.
+//
+// The live cells of a grid are split into objects : the 8-connected components, merged when
+// two of them come within `MERGE_DISTANCE` cells (close enough to interact). Each object is
+// then run on its own, on an unbounded plane (see `hashlife`), until it comes back to its
+// first phase, possibly displaced : a still life (period 1), an oscillator or a spaceship.
+// Pseudo objects, whose components evolve as if they were alone (e.g., the four blinkers of a
+// traffic light), are split back into their components.
+//
+// Objects are named by their apgcode : xs{population} for still lifes, xp{period} for
+// oscillators and xq{period} for spaceships, followed by the extended Wechsler format of the
+// phase and orientation with the shortest (then lexicographically smallest) encoding.
+
+use crate::error::Error;
+use crate::grid::{Connectivity, Grid};
+use crate::hashlife::HashLife;
+
+use petgraph::unionfind::UnionFind;
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Components closer than this (in cells, along rows, columns or diagonals) are one object.
+pub const MERGE_DISTANCE : usize = 2;
+
+/// Objects that don't come back to their first phase within this many generations are
+/// `PATHOLOGICAL`.
+pub const MAX_PERIOD : usize = 1000;
+
+// extended Wechsler digits.
+const ALPHABET : &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// What an object does when run on its own.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObjectKind {
+    StillLife,
+    Oscillator { period : usize },
+    /// moves by (rows, columns) every period.
+    Spaceship { period : usize, displacement : (i64, i64) },
+    /// dies, grows, or has a period above the limit.
+    Pathological,
+}
+
+/// An object of a grid.
+#[derive(Clone)]
+pub struct Object {
+    pub row_index : usize, // top left corner of `cells` in the grid
+    pub column_index : usize,
+    pub cells : Grid,
+    pub kind : ObjectKind,
+    pub apgcode : String
+}
+
+/// The number of objects of each apgcode, over one soup or many.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Census {
+    counts : BTreeMap<String, usize>
+}
+
+impl Census {
+
+    pub fn new() -> Census {
+        return Census::default();
+    }
+
+    pub fn add(&mut self, apgcode : &str) {
+        *self.counts.entry(apgcode.to_string()).or_insert(0) += 1;
+    }
+
+    /// Adds the counts of `other`, e.g., the census of another soup.
+    pub fn merge(&mut self, other : &Census) {
+        for (apgcode, count) in other.counts.iter() {
+            *self.counts.entry(apgcode.clone()).or_insert(0) += count;
+        }
+    }
+
+    pub fn get_count(&self, apgcode : &str) -> usize {
+        return self.counts.get(apgcode).copied().unwrap_or(0);
+    }
+
+    /// Returns the number of objects.
+    pub fn get_total(&self) -> usize {
+        return self.counts.values().sum();
+    }
+
+    /// Returns the apgcodes and their counts, most common first.
+    pub fn get_sorted(&self) -> Vec<(&str, usize)> {
+        let mut sorted : Vec<(&str, usize)> = self.counts.iter().map(|(apgcode, count)| (apgcode.as_str(), *count)).collect();
+        sorted.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+        return sorted;
+    }
+}
+
+impl fmt::Display for Census {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (apgcode, count) in self.get_sorted() {
+            writeln!(f, "{} {}", apgcode, count)?;
+        }
+
+        return Ok(());
+    }
+}
+
+/// Splits the nonzero cells of `grid` into objects : 8-connected components, merged when they
+/// come within `merge_distance` of each other. Each object keeps only its own cells.
+pub fn get_objects(grid : &Grid, merge_distance : usize) -> Vec<(usize, usize, Grid)> {
+    let width : usize = grid.get_width();
+    let height : usize = grid.get_height();
+
+    let mut binary : Grid = Grid::empty(width, height);
+    binary.set_connectivity(Connectivity::EightConnected);
+    for (index, cell) in grid.get_grid().iter().enumerate() {
+        binary.set_value(index / width, index % width, (*cell != 0) as usize);
+    }
+
+    let labels : Vec<usize> = binary.get_connected_components(true);
+    let components : usize = labels.iter().copied().max().unwrap_or(0);
+
+    // merge the components that come close
+    let mut uf = UnionFind::<usize>::new(components + 1);
+    let distance : isize = merge_distance as isize;
+    for (index, label) in labels.iter().enumerate().filter(|(_, label)| **label != 0) {
+        let (row_index, column_index) : (isize, isize) = ((index / width) as isize, (index % width) as isize);
+
+        for row in (row_index - distance).max(0)..=(row_index + distance).min(height as isize - 1) {
+            for column in (column_index - distance).max(0)..=(column_index + distance).min(width as isize - 1) {
+                let other : usize = labels[row as usize * width + column as usize];
+                if other != 0 {
+                    uf.union(*label, other);
+                }
+            }
+        }
+    }
+
+    // bounding box of every object, in order of first cell
+    let mut boxes = BTreeMap::<usize, (usize, usize, usize, usize)>::new();
+    let mut order : Vec<usize> = Vec::new();
+    for (index, label) in labels.iter().enumerate().filter(|(_, label)| **label != 0) {
+        let object : usize = uf.find(*label);
+        let (row_index, column_index) : (usize, usize) = (index / width, index % width);
+
+        let bounds = boxes.entry(object).or_insert_with(|| {
+            order.push(object);
+            (row_index, column_index, row_index, column_index)
+        });
+        *bounds = (bounds.0.min(row_index), bounds.1.min(column_index), bounds.2.max(row_index), bounds.3.max(column_index));
+    }
+
+    return order.iter().map(|object| {
+        let (min_row, min_column, max_row, max_column) : (usize, usize, usize, usize) = boxes[object];
+        let mut cells : Grid = Grid::empty(max_column - min_column + 1, max_row - min_row + 1);
+
+        for row_index in min_row..=max_row {
+            for column_index in min_column..=max_column {
+                let label : usize = labels[row_index * width + column_index];
+                if label != 0 && uf.find(label) == *object {
+                    cells.set_value(row_index - min_row, column_index - min_column, 1);
+                }
+            }
+        }
+
+        (min_row, min_column, cells)
+    }).collect();
+}
+
+/// Runs `cells` on their own under the outer totalistic `code` (Moore, no B0) for up to
+/// `max_period` generations, and returns what they do and their apgcode.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::Grid;
+/// use gol_rs::census::{self, ObjectKind};
+///
+/// let glider = Grid::from_vec(3, 3, vec![
+///     0, 1, 0,
+///     0, 0, 1,
+///     1, 1, 1]).unwrap();
+///
+/// let (kind, apgcode) = census::classify(&glider, 224, 100).unwrap();
+/// assert_eq!(kind, ObjectKind::Spaceship { period : 4, displacement : (1, 1) });
+/// assert_eq!(apgcode, "xq4_153");
+///
+/// let blinker = Grid::from_vec(3, 1, vec![1, 1, 1]).unwrap();
+/// assert_eq!(census::classify(&blinker, 224, 100).unwrap(), (ObjectKind::Oscillator { period : 2 }, String::from("xp2_7")));
+/// ```
+pub fn classify(cells : &Grid, code : usize, max_period : usize) -> Result<(ObjectKind, String), Error> {
+    let mut hashlife = HashLife::new(code)?;
+    hashlife.import_grid(cells, 0, 0);
+
+    let first : Option<(i64, i64, Grid)> = get_phase(&hashlife);
+    let (first_row, first_column, first_phase) : (i64, i64, Grid) = match first {
+        Some(phase) => phase,
+        None => return Ok((ObjectKind::Pathological, String::from("PATHOLOGICAL")))
+    };
+
+    let mut phases : Vec<Grid> = vec![first_phase.clone()];
+
+    for period in 1..=max_period {
+        hashlife.step(1)?;
+
+        let (row_index, column_index, phase) : (i64, i64, Grid) = match get_phase(&hashlife) {
+            Some(phase) => phase,
+            None => break // died out
+        };
+
+        if phase.get_width() == first_phase.get_width() && phase.get_grid() == first_phase.get_grid() {
+            let displacement : (i64, i64) = (row_index - first_row, column_index - first_column);
+
+            let (kind, prefix) : (ObjectKind, String) = if displacement != (0, 0) {
+                (ObjectKind::Spaceship { period : period, displacement : displacement }, format!("xq{}", period))
+            } else if period == 1 {
+                (ObjectKind::StillLife, format!("xs{}", first_phase.get_population()))
+            } else {
+                (ObjectKind::Oscillator { period : period }, format!("xp{}", period))
+            };
+
+            return Ok((kind, format!("{}_{}", prefix, get_canonical_wechsler(&phases))));
+        }
+
+        phases.push(phase);
+    }
+
+    return Ok((ObjectKind::Pathological, String::from("PATHOLOGICAL")));
+}
+
+/// Splits `grid` into objects (see `get_objects`) and classifies each one (see `classify`);
+/// pseudo objects are split into their components.
+pub fn get_classified_objects(grid : &Grid, code : usize) -> Result<Vec<Object>, Error> {
+    let mut objects : Vec<Object> = Vec::new();
+
+    for (row_index, column_index, cells) in get_objects(grid, MERGE_DISTANCE) {
+        let (kind, apgcode) : (ObjectKind, String) = classify(&cells, code, MAX_PERIOD)?;
+        let components : Vec<(usize, usize, Grid)> = get_objects(&cells, 1);
+
+        let period : usize = match kind {
+            ObjectKind::StillLife => 1,
+            ObjectKind::Oscillator { period } | ObjectKind::Spaceship { period, .. } => period,
+            ObjectKind::Pathological => 0
+        };
+
+        if components.len() > 1 && period > 0 && is_separable(&cells, &components, code, 2 * period)? {
+            for (component_row_index, component_column_index, component) in components {
+                let (kind, apgcode) : (ObjectKind, String) = classify(&component, code, MAX_PERIOD)?;
+
+                objects.push(Object {
+                    row_index : row_index + component_row_index,
+                    column_index : column_index + component_column_index,
+                    cells : component,
+                    kind : kind,
+                    apgcode : apgcode
+                });
+            }
+            continue;
+        }
+
+        objects.push(Object {
+            row_index : row_index,
+            column_index : column_index,
+            cells : cells,
+            kind : kind,
+            apgcode : apgcode
+        });
+    }
+
+    return Ok(objects);
+}
+
+// whether `components` of `cells`, run apart, give the same cells as `cells` for `generations`.
+fn is_separable(cells : &Grid, components : &[(usize, usize, Grid)], code : usize, generations : usize) -> Result<bool, Error> {
+    let mut whole = HashLife::new(code)?;
+    whole.import_grid(cells, 0, 0);
+
+    let mut parts : Vec<HashLife> = Vec::new();
+    for (row_index, column_index, component) in components {
+        let mut part = HashLife::new(code)?;
+        part.import_grid(component, *row_index as i64, *column_index as i64);
+        parts.push(part);
+    }
+
+    for _ in 0..generations {
+        whole.step(1)?;
+        for part in parts.iter_mut() {
+            part.step(1)?;
+        }
+
+        // the parts must not overlap, and must make up the whole
+        let population : u64 = parts.iter().map(|part| part.get_population()).sum();
+        if population != whole.get_population() {
+            return Ok(false);
+        }
+
+        let (min_row, min_column, max_row, max_column) : (i64, i64, i64, i64) = match whole.get_bounding_box() {
+            Some(bounding_box) => bounding_box,
+            None => return Ok(true)
+        };
+        let (width, height) : (usize, usize) = ((max_column - min_column + 1) as usize, (max_row - min_row + 1) as usize);
+
+        let mut union : Grid = Grid::new(width, height)?;
+        for part in parts.iter() {
+            let part_cells : Grid = part.export_grid(min_row, min_column, width, height)?;
+            for (index, cell) in part_cells.get_grid().iter().enumerate() {
+                if *cell != 0 {
+                    union.set_value(index / width, index % width, 1);
+                }
+            }
+        }
+
+        if union.get_population() as u64 != population || union.get_grid() != whole.export_grid(min_row, min_column, width, height)?.get_grid() {
+            return Ok(false);
+        }
+    }
+
+    return Ok(true);
+}
+
+/// Returns the census of a settled `grid` under the outer totalistic `code`.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::Grid;
+/// use gol_rs::census;
+///
+/// // two blocks and a blinker
+/// let grid = Grid::from_vec(12, 4, vec![
+///     1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1,
+///     1, 1, 0, 0, 1, 1, 1, 0, 0, 0, 1, 1,
+///     0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+///     0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+///
+/// let census = census::take_census(&grid, 224).unwrap();
+/// assert_eq!(census.get_count("xs4_33"), 2);
+/// assert_eq!(census.get_count("xp2_7"), 1);
+/// assert_eq!(census.to_string(), "xs4_33 2\nxp2_7 1\n");
+/// ```
+pub fn take_census(grid : &Grid, code : usize) -> Result<Census, Error> {
+    let mut census = Census::new();
+
+    for object in get_classified_objects(grid, code)? {
+        census.add(&object.apgcode);
+    }
+
+    return Ok(census);
+}
+
+/// Returns the extended Wechsler format of the nonzero cells of `grid` : strips of 5 rows,
+/// one digit per column (the top row is the lowest bit), runs of empty columns shortened to
+/// 0, w, x or y{n}, and strips separated by z.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::Grid;
+/// use gol_rs::census;
+///
+/// let block = Grid::from_vec(2, 2, vec![1, 1, 1, 1]).unwrap();
+/// assert_eq!(census::get_wechsler(&block), "33");
+/// ```
+pub fn get_wechsler(grid : &Grid) -> String {
+    let mut strips : Vec<String> = Vec::new();
+
+    for strip_index in 0..grid.get_height().div_ceil(5) {
+        let mut strip : String = String::new();
+        let mut zeros : usize = 0;
+
+        for column_index in 0..grid.get_width() {
+            let mut digit : usize = 0;
+            for bit in 0..5 {
+                let row_index : usize = 5 * strip_index + bit;
+                if row_index < grid.get_height() && grid.get_value(row_index, column_index) != 0 {
+                    digit |= 1 << bit;
+                }
+            }
+
+            if digit == 0 {
+                zeros += 1;
+                continue;
+            }
+
+            // empty columns are only written before a nonempty one
+            while zeros >= 40 {
+                strip.push_str("yz");
+                zeros -= 39;
+            }
+            match zeros {
+                0 => {},
+                1 => strip.push('0'),
+                2 => strip.push('w'),
+                3 => strip.push('x'),
+                _ => {
+                    strip.push('y');
+                    strip.push(ALPHABET[zeros - 4] as char);
+                }
+            }
+            zeros = 0;
+
+            strip.push(ALPHABET[digit] as char);
+        }
+
+        strips.push(strip);
+    }
+
+    return strips.join("z");
+}
+
+// the shortest, then smallest, encoding over every phase and orientation.
+fn get_canonical_wechsler(phases : &[Grid]) -> String {
+    let mut canonical : Option<String> = None;
+
+    for phase in phases {
+        let mut orientation : Grid = phase.clone();
+
+        for turn in 0..8 {
+            if turn == 4 {
+                orientation = orientation.reflect();
+            }
+
+            let wechsler : String = get_wechsler(&orientation);
+            let shorter : bool = canonical.as_ref().is_none_or(|canonical|
+                (wechsler.len(), &wechsler) < (canonical.len(), canonical));
+            if shorter {
+                canonical = Some(wechsler);
+            }
+
+            orientation = orientation.rotate_clockwise();
+        }
+    }
+
+    return canonical.unwrap_or_default();
+}
+
+// the top left corner and the cells of the bounding box of the live cells, if any.
+fn get_phase(hashlife : &HashLife) -> Option<(i64, i64, Grid)> {
+    let (min_row, min_column, max_row, max_column) : (i64, i64, i64, i64) = hashlife.get_bounding_box()?;
+    let (width, height) : (usize, usize) = ((max_column - min_column + 1) as usize, (max_row - min_row + 1) as usize);
+
+    // the bounding box is never empty
+    return Some((min_row, min_column, hashlife.export_grid(min_row, min_column, width, height).unwrap()));
+}
@@ -0,0 +1,692 @@
+// Command line interface :
+//
+//      gol-rs eca [--rule 30] [--states 2] [--radius 1] [--totalistic] [--width 200]
+//                 [--height 130] [--seed single|random|patch|0110...] [--boundary null]
+//                 [--update synchronous|second-order|asynchronous|alpha:0.5]
+//                 [--output rule30.png] [--csv rule30.csv] [--ccl]
+//      gol-rs eca --stream [--generations 1000000] ...
+//      gol-rs eca --all [--seeds 0..8] [--directory eca] [--no-images]
+//      gol-rs tca [--rule 224 | --rulestring B3/S23] [--totalistic] [--boundary null]
+//                 [--neighbourhood moore|von-neumann] [--depth 1] [--generations 100] [--width 200] [--height 130]
+//                 [--input grid.csv|pattern.rle] [--view | --output page.csv|page.rle] [--backend reference|bit-sliced]
+//                 [--threads 8] [--pattern glider [--lexicon lexicon.txt] [--rotate 1] [--reflect]
+//                 [--blend overwrite|or|xor|ignore-zeros]]
+//      gol-rs tca --unbounded [--generations 100] ...
+//      gol-rs sweep [--rules 0..1000] [--kind outer|totalistic|both]
+//                 [--neighbourhood moore|von-neumann|both] [--width 200] [--height 130]
+//                 [--generations 100] [--seeds 0..8] [--threads 8] [--format csv|json]
+//                 [--output sweep.csv] [--resume]
+//      gol-rs hashlife [--rule 224 | --rulestring B3/S23] [--totalistic] [--input grid.csv|pattern.rle]
+//                 [--generations 1000000000] [--output bounding-box.csv|bounding-box.rle]
+//      gol-rs census [--rule 224 | --rulestring B3/S23] [--seeds 0..100] [--width 16] [--height 16]
+//                 [--generations 4096]
+//      gol-rs analyze <grid.csv> [--connectivity 4|8]
+//
+// pattern files (.rle, .cells, .lif, .mc) can be given for --input and --output; their rule,
+// if any, is used unless --rule or --rulestring is given.
+//
+// --view keys : space pause, n step, = / - faster / slower, r new soup, c colouring,
+// s screenshot, p save the page as rle, escape quit; the mouse draws (left), erases (right),
+// selects (shift + left), pans (middle, or arrows) and zooms (scroll, or [ ]), and a selection
+// is copied, cut and pasted with ctrl + c, x, v, rotated with o and cleared with delete.
+//
+// random initial configurations take [--random-seed 42] [--density 0.5]
+// [--soup asymmetric|c2|c4|d4|patch] [--patch 16x16]; the seed is always printed so a run
+// can be re-created.
+
+use gol_rs::{BoundaryType, ECA, ECARule, Error, Grid, TCA2D};
+use gol_rs::census::{self, Census};
+use gol_rs::eca::{self, UpdateMode};
+use gol_rs::eca_stream::{CsvSink, ECAStream, PngSink};
+use gol_rs::grid::{Connectivity, Symmetry};
+use gol_rs::hashlife::HashLife;
+use gol_rs::pattern_io::{self, Pattern};
+use gol_rs::patterns::{self, Lexicon};
+use gol_rs::rule::{Rule, Totalistic, OuterTotalistic};
+use gol_rs::sparse::SparseUniverse;
+use gol_rs::sweep::{self, OutputFormat, RuleKind, SweepConfiguration, SweepResult};
+use gol_rs::tca2d::{Backend, BlendMode, Placement};
+use gol_rs::tca2d_packed;
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::thread;
+
+pub const USAGE : &str = "usage : gol-rs <eca | tca | sweep | hashlife | census | analyze> [--option value]...
+
+    eca      run an elementary cellular automaton and save its spacetime diagram
+             --rule 30 --states 2 --radius 1 --totalistic
+             --width 200 --height 130 --seed single|random|patch|0120...
+             --boundary null|periodic|reflective|constant:1
+             --update synchronous|second-order|asynchronous|alpha:0.5
+             --output rule30.png --csv rule30.csv --ccl
+             --stream --generations 1000000 keeps two rows in memory, not --height
+             --all runs the 88 inequivalent rules on random seeds into a directory
+             --seeds 0..8 --directory eca --no-images
+    tca      run a two dimensional automaton
+             --rule 224 | --rulestring B3/S23 --totalistic
+             --boundary null|cylinder|moebius|torus|klein|reflective|constant:1
+             --neighbourhood moore|von-neumann --depth 1 --width 200 --height 130
+             --generations 100 (default : depth - 1) steps that far, keeping the last --depth
+             generations, and outputs the last one
+             --input grid.csv|pattern.rle --view | --output page.csv|page.rle
+             (--view keys : space pause, n step, = / - faster / slower, r new soup,
+             c raw cells / components, s screenshot, p save the page as rle, escape quit;
+             mouse : left draws, right erases, shift + left selects, middle or arrows pan,
+             scroll or [ ] zoom, home fits; ctrl + c / x / v copy, cut and paste the
+             selection, o rotates it, delete clears it)
+             (pattern files : .rle, .cells, .lif, .mc; their rule is used unless --rule or
+             --rulestring is given)
+             --backend reference|bit-sliced (bit-sliced whenever the rule allows it)
+             --threads 8 (default : one per core)
+             --pattern glider starts from a built-in (or --lexicon lexicon.txt) pattern in the
+             middle of an empty grid (or of --input), --rotate 1 quarter turns clockwise, --reflect,
+             --blend overwrite|or|xor|ignore-zeros
+             --unbounded --generations 100 runs on a sparse, unbounded plane instead, and
+             outputs the bounding box of the last generation
+    sweep    run a range of two dimensional rules headless, in parallel, and write statistics
+             --rules 0..1000 --kind outer|totalistic|both
+             --neighbourhood moore|von-neumann|both --width 200 --height 130
+             --generations 100 --seeds 0..8 --threads 8 --format csv|json
+             --output sweep.csv --resume
+    hashlife run a Moore outer totalistic rule on an unbounded plane and print the population
+             and bounding box at every power of two generations
+             --rule 224 | --rulestring B3/S23 --totalistic --input grid.csv|pattern.rle
+             --generations 1000000000 --output bounding-box.csv|bounding-box.rle
+    census   run random soups on an unbounded plane and count the objects they settle into,
+             by apgcode (Moore outer totalistic rules)
+             --rule 224 | --rulestring B3/S23 --seeds 0..100 --width 16 --height 16
+             --generations 4096
+    analyze  print connected component and topology statistics of a csv grid
+             <grid.csv> --connectivity 4|8
+
+    random initial configurations take --random-seed 42 --density 0.5
+    --soup asymmetric|c2|c4|d4|patch --patch 16x16";
+
+/// Subcommand, `--name value` options, `--name` flags and positional arguments.
+pub struct Arguments {
+    pub subcommand : String,
+    options : HashMap<String, String>,
+    flags : HashSet<String>,
+    positional : Vec<String>
+}
+
+impl Arguments {
+
+    pub fn parse(args : Vec<String>) -> Result<Arguments, Error> {
+        let mut args = args.into_iter().peekable();
+
+        let subcommand : String = args.next()
+            .ok_or_else(|| Error::Parse(String::from("missing subcommand")))?;
+
+        let mut options = HashMap::<String, String>::new();
+        let mut flags = HashSet::<String>::new();
+        let mut positional = Vec::<String>::new();
+
+        while let Some(arg) = args.next() {
+            if let Some(name) = arg.strip_prefix("--") {
+                if let Some((name, value)) = name.split_once('=') {
+                    options.insert(name.to_string(), value.to_string());
+                } else if args.peek().is_some_and(|next| !next.starts_with("--")) {
+                    options.insert(name.to_string(), args.next().unwrap());
+                } else {
+                    flags.insert(name.to_string());
+                }
+            } else {
+                positional.push(arg);
+            }
+        }
+
+        return Ok(Arguments {
+            subcommand : subcommand,
+            options : options,
+            flags : flags,
+            positional : positional
+        });
+    }
+
+    fn get<T : FromStr>(&self, name : &str, default : T) -> Result<T, Error>
+        where T::Err : std::fmt::Display {
+        return match self.options.get(name) {
+            Some(value) => value.parse::<T>()
+                .map_err(|e| Error::Parse(format!("--{} {} : {}", name, value, e))),
+            None => Ok(default)
+        };
+    }
+
+    fn get_str(&self, name : &str) -> Option<&str> {
+        return self.options.get(name).map(|value| value.as_str());
+    }
+
+    fn has_flag(&self, name : &str) -> bool {
+        return self.flags.contains(name);
+    }
+}
+
+pub fn run(args : Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let arguments = Arguments::parse(args)?;
+
+    return match arguments.subcommand.as_str() {
+        "eca" => run_eca(&arguments),
+        "tca" => run_tca(&arguments),
+        "sweep" => run_sweep(&arguments),
+        "hashlife" => run_hashlife(&arguments),
+        "census" => run_census(&arguments),
+        "analyze" => run_analyze(&arguments),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        },
+        subcommand => Err(Box::new(Error::Parse(format!("unknown subcommand {}", subcommand))))
+    };
+}
+
+fn run_eca(arguments : &Arguments) -> Result<(), Box<dyn std::error::Error>> {
+    if arguments.has_flag("all") {
+        return run_eca_batch(arguments);
+    }
+
+    let states : usize = arguments.get("states", 2)?;
+    let radius : usize = arguments.get("radius", 1)?;
+    let code : &str = arguments.get_str("rule").unwrap_or("30");
+    let rule : ECARule = if arguments.has_flag("totalistic") {
+        ECARule::totalistic(states, radius, code)?
+    } else {
+        ECARule::from_code(states, radius, code)?
+    };
+    let width : usize = arguments.get("width", 200)?;
+    let height : usize = arguments.get("height", 130)?;
+
+    let random_seed : u64 = get_random_seed(arguments)?;
+    let density : f64 = arguments.get("density", 0.5)?;
+
+    let seed_name : &str = arguments.get_str("seed").unwrap_or("single");
+    let seed : Vec<usize> = match seed_name {
+        "single" => single_seed(width),
+        "random" => eca::random_states_configuration(width, states, density, random_seed)?,
+        "patch" => eca::random_patch_configuration(width, get_patch(arguments)?.0.min(width), density, random_seed)?,
+        bits => parse_bits(bits)?
+    };
+    let update_mode : UpdateMode = arguments.get("update", UpdateMode::Synchronous)?;
+    let is_random : bool = seed_name == "random" || seed_name == "patch"
+        || matches!(update_mode, UpdateMode::Asynchronous | UpdateMode::AlphaAsynchronous(_));
+
+    let boundary_type : BoundaryType = arguments.get("boundary", BoundaryType::Null)?;
+
+    let name : String = format!("{}{}", eca::get_file_stem(&rule), update_mode.get_suffix());
+    let filename : String = match arguments.get_str("output") {
+        Some(output) => output.to_string(),
+        None if is_random => format!("./{}length{}seed{}.png", name, width, random_seed),
+        None => format!("./{}length{}.png", name, width)
+    };
+    let rule_name : String = format!("{}{}", rule, update_mode.get_suffix());
+    let seed_field : String = if is_random { format!("|seed:{}", random_seed) } else { String::new() };
+
+    if arguments.has_flag("stream") {
+        let generations : usize = arguments.get("generations", height.max(1) - 1)?;
+
+        let mut stream = ECAStream::new(rule, seed, boundary_type)?;
+        stream.set_update_mode(update_mode, random_seed)?;
+
+        let mut png = PngSink::create(&filename, width, generations + 1, states)?;
+        let statistics = match arguments.get_str("csv") {
+            Some(csv_filename) => stream.run(generations, &mut [&mut png, &mut CsvSink::create(csv_filename)?])?,
+            None => stream.run(generations, &mut [&mut png])?
+        };
+
+        println!("rule:{}|width:{}|generations:{}{}|mean_density:{:.6}|final_density:{:.6}|output:{}",
+            rule_name, width, generations, seed_field, statistics.get_mean_density(),
+            statistics.get_final_density(), filename);
+
+        return Ok(());
+    }
+
+    let mut automata = ECA::with_rule(rule, width, height, seed, boundary_type)?;
+    automata.set_update_mode(update_mode, random_seed)?;
+    automata.generate();
+
+    if let Some(csv_filename) = arguments.get_str("csv") {
+        automata.get_universe().write_csv(csv_filename)?;
+    }
+
+    if arguments.has_flag("ccl") {
+        automata.generate_connected_components();
+    }
+
+    automata.save_to_png(&filename)?;
+
+    println!("rule:{}|width:{}|height:{}{}|output:{}", rule_name, width, height, seed_field, filename);
+
+    return Ok(());
+}
+
+fn run_eca_batch(arguments : &Arguments) -> Result<(), Box<dyn std::error::Error>> {
+    let width : usize = arguments.get("width", 200)?;
+    let height : usize = arguments.get("height", 130)?;
+    let density : f64 = arguments.get("density", 0.5)?;
+    let directory : &str = arguments.get_str("directory").unwrap_or("eca");
+
+    let seeds : Vec<u64> = match arguments.get_str("seeds") {
+        Some(seeds) => parse_range(seeds)?.into_iter().map(|seed| seed as u64).collect(),
+        None => vec![get_random_seed(arguments)?]
+    };
+
+    let results = eca::generate_batch(width, height, density, &seeds, directory, !arguments.has_flag("no-images"))?;
+    println!("runs:{}|seeds:{}|directory:{}", results.len(), seeds.len(), directory);
+
+    return Ok(());
+}
+
+fn run_tca(arguments : &Arguments) -> Result<(), Box<dyn std::error::Error>> {
+    let input : Option<Pattern> = match arguments.get_str("input") {
+        Some(path) => Some(read_input(path)?),
+        None => None
+    };
+    let rule : Box<dyn Rule + Sync> = get_input_rule(arguments, input.as_ref())?;
+    let boundary_type : BoundaryType = arguments.get("boundary", BoundaryType::Null)?;
+    let moore : bool = get_neighbourhood(arguments)?;
+    let depth : usize = arguments.get("depth", 1)?;
+
+    let pattern : Option<Grid> = get_pattern(arguments)?;
+    let quarter_turns : usize = arguments.get("rotate", 0)?;
+    let reflect : bool = arguments.has_flag("reflect");
+
+    // also the seed --view restarts after
+    let random_seed : u64 = get_random_seed(arguments)?;
+
+    let initial_configuration : Grid = match (input, &pattern) {
+        (Some(input), _) => input.cells,
+        (None, Some(_)) => Grid::new(arguments.get("width", 200)?, arguments.get("height", 130)?)?,
+        (None, None) => {
+            eprintln!("seed : {}", random_seed);
+            get_soup(arguments, arguments.get("width", 200)?, arguments.get("height", 130)?, random_seed)?
+        }
+    };
+    let width : usize = initial_configuration.get_width();
+    let height : usize = initial_configuration.get_height();
+
+    if arguments.has_flag("unbounded") {
+        let mut universe = SparseUniverse::new(rule, moore)?;
+        universe.import_grid(&initial_configuration, 0, 0);
+        if let Some(pattern) = &pattern {
+            universe.import_grid(&patterns::orient(pattern, quarter_turns, reflect), 0, 0);
+        }
+        universe.step(arguments.get("generations", 100)?);
+
+        let last_page : Grid = match universe.get_bounding_box() {
+            Some((min_row, min_column, max_row, max_column)) => universe.export_grid(min_row, min_column,
+                (max_column - min_column + 1) as usize, (max_row - min_row + 1) as usize)?,
+            None => Grid::new(1, 1)?
+        };
+        eprintln!("population : {}, bounding box : {:?}", universe.get_population(), universe.get_bounding_box());
+
+        match arguments.get_str("output") {
+            Some(path) => write_output(path, &Pattern::with_rule(last_page, universe.get_rule()))?,
+            None => print!("{}", last_page)
+        }
+
+        return Ok(());
+    }
+
+    let backend : Backend = match arguments.get_str("backend") {
+        Some(backend) => backend.parse()?,
+        None if tca2d_packed::check(rule.get_outer_totalistic_code(), boundary_type, &initial_configuration).is_ok() => Backend::BitSliced,
+        None => Backend::Reference
+    };
+
+    let mut gol = TCA2D::with_backend(rule, width, height, depth, moore, boundary_type, initial_configuration, backend)?;
+
+    if let Some(pattern) = &pattern {
+        let oriented : Grid = patterns::orient(pattern, quarter_turns, reflect);
+        let row_index : isize = (height as isize - oriented.get_height() as isize) / 2;
+        let column_index : isize = (width as isize - oriented.get_width() as isize) / 2;
+
+        let placement : Placement = gol.insert_at(0, row_index, column_index, &oriented, arguments.get("blend", BlendMode::Overwrite)?)?;
+        eprintln!("placement : {:?}", placement);
+    }
+
+    if arguments.has_flag("view") {
+        // R restarts from the seeds after the one printed
+        gol.display_infinite(|seed| get_soup(arguments, width, height, seed), random_seed)?;
+        return Ok(());
+    }
+
+    // the stack holds generations 0 to depth - 1, then steps on to `generations`
+    let generations : usize = arguments.get("generations", depth - 1)?;
+    if generations < depth - 1 {
+        return Err(Box::new(Error::Parse(format!("--generations {} is below --depth {} - 1", generations, depth))));
+    }
+
+    let threads : usize = arguments.get("threads", 0)?;
+    gol.generate_parallel(threads);
+    gol.step_parallel(generations - (depth - 1), threads);
+
+    match arguments.get_str("output") {
+        Some(path) => write_output(path, &gol.get_pattern(depth - 1))?,
+        None => print!("{}", gol.get_page(depth - 1))
+    }
+
+    return Ok(());
+}
+
+fn run_sweep(arguments : &Arguments) -> Result<(), Box<dyn std::error::Error>> {
+    let codes : Vec<usize> = parse_range(arguments.get_str("rules").unwrap_or("0..1000"))?;
+    let width : usize = arguments.get("width", 200)?;
+    let height : usize = arguments.get("height", 130)?;
+    let generations : usize = arguments.get("generations", 100)?;
+
+    let kinds : Vec<RuleKind> = match arguments.get_str("kind").unwrap_or("both") {
+        "outer" => vec![RuleKind::OuterTotalistic],
+        "totalistic" => vec![RuleKind::Totalistic],
+        "both" => vec![RuleKind::Totalistic, RuleKind::OuterTotalistic],
+        kind => return Err(Box::new(Error::Parse(format!("unknown kind {}", kind))))
+    };
+
+    // every rule runs on the same soups; the seeds are part of the output.
+    let seeds : Vec<u64> = match arguments.get_str("seeds") {
+        Some(seeds) => parse_range(seeds)?.into_iter().map(|seed| seed as u64).collect(),
+        None => vec![get_random_seed(arguments)?]
+    };
+
+    let configuration = SweepConfiguration {
+        codes : codes,
+        kinds : kinds,
+        neighbourhoods : get_neighbourhoods(arguments)?,
+        seeds : seeds,
+        width : width,
+        height : height,
+        density : arguments.get("density", 0.5)?,
+        symmetry : get_symmetry(arguments)?,
+        generations : generations,
+        threads : arguments.get("threads", thread::available_parallelism().map_or(1, |threads| threads.get()))?,
+    };
+
+    let format : OutputFormat = match arguments.get_str("format").unwrap_or("csv") {
+        "csv" => OutputFormat::Csv,
+        "json" => OutputFormat::Json,
+        format => return Err(Box::new(Error::Parse(format!("unknown format {}", format))))
+    };
+
+    match arguments.get_str("output") {
+        Some(path) => {
+            let count : usize = sweep::run_sweep(&configuration, path, format, arguments.has_flag("resume"))?;
+            eprintln!("{} configurations written to {}", count, path);
+        },
+        None => {
+            if format == OutputFormat::Csv {
+                println!("{}", SweepResult::get_csv_header());
+            }
+            for result in sweep::collect_sweep(&configuration)? {
+                match format {
+                    OutputFormat::Csv => println!("{}", result.get_csv_row()),
+                    OutputFormat::Json => println!("{}", result.get_json()),
+                }
+            }
+        }
+    }
+
+    return Ok(());
+}
+
+fn run_hashlife(arguments : &Arguments) -> Result<(), Box<dyn std::error::Error>> {
+    let input : Option<Pattern> = match arguments.get_str("input") {
+        Some(path) => Some(read_input(path)?),
+        None => None
+    };
+    let code : usize = get_input_rule(arguments, input.as_ref())?.get_outer_totalistic_code()
+        .ok_or_else(|| Error::UnsupportedBackend(String::from("the rule has no outer totalistic code")))?;
+    let generations : u64 = arguments.get("generations", 1 << 20)?;
+
+    let initial_configuration : Grid = match input {
+        Some(input) => input.cells,
+        None => {
+            let random_seed : u64 = get_random_seed(arguments)?;
+            eprintln!("seed : {}", random_seed);
+            get_soup(arguments, arguments.get("width", 16)?, arguments.get("height", 16)?, random_seed)?
+        }
+    };
+
+    let mut hashlife = HashLife::new(code)?;
+    hashlife.import_grid(&initial_configuration, 0, 0);
+
+    println!("generation,population,min_row,min_column,max_row,max_column");
+    let print_row = |hashlife : &HashLife| {
+        let (min_row, min_column, max_row, max_column) : (i64, i64, i64, i64) = hashlife.get_bounding_box().unwrap_or((0, 0, -1, -1));
+        println!("{},{},{},{},{},{}", hashlife.get_generation(), hashlife.get_population(), min_row, min_column, max_row, max_column);
+    };
+    print_row(&hashlife);
+
+    // 1, 2, 4, ... then the rest
+    if generations > 0 {
+        hashlife.step_pow2(0)?;
+        print_row(&hashlife);
+    }
+    let mut exponent : usize = 0;
+    while hashlife.get_generation() <= generations / 2 {
+        hashlife.step_pow2(exponent)?;
+        exponent += 1;
+        print_row(&hashlife);
+    }
+    if hashlife.get_generation() < generations {
+        hashlife.step(generations - hashlife.get_generation())?;
+        print_row(&hashlife);
+    }
+
+    if let Some(path) = arguments.get_str("output") {
+        if let Some((min_row, min_column, max_row, max_column)) = hashlife.get_bounding_box() {
+            let (width, height) : (i64, i64) = (max_column - min_column + 1, max_row - min_row + 1);
+            if width * height > 1 << 28 {
+                return Err(Box::new(Error::InvalidDimensions { width : width as usize, height : height as usize }));
+            }
+
+            let last_page : Grid = hashlife.export_grid(min_row, min_column, width as usize, height as usize)?;
+            write_output(path, &Pattern::with_rule(last_page, &OuterTotalistic::new(code)))?;
+        }
+    }
+
+    return Ok(());
+}
+
+fn run_census(arguments : &Arguments) -> Result<(), Box<dyn std::error::Error>> {
+    let code : usize = get_rule(arguments)?.get_outer_totalistic_code()
+        .ok_or_else(|| Error::UnsupportedBackend(String::from("the rule has no outer totalistic code")))?;
+    let generations : u64 = arguments.get("generations", 4096)?;
+    let width : usize = arguments.get("width", 16)?;
+    let height : usize = arguments.get("height", 16)?;
+
+    let seeds : Vec<u64> = match arguments.get_str("seeds") {
+        Some(seeds) => parse_range(seeds)?.into_iter().map(|seed| seed as u64).collect(),
+        None => vec![get_random_seed(arguments)?]
+    };
+    eprintln!("seeds : {:?}", seeds);
+
+    let mut total = Census::new();
+    for seed in seeds {
+        let mut hashlife = HashLife::new(code)?;
+        hashlife.import_grid(&get_soup(arguments, width, height, seed)?, 0, 0);
+        hashlife.step(generations)?;
+
+        if let Some((min_row, min_column, max_row, max_column)) = hashlife.get_bounding_box() {
+            let settled : Grid = hashlife.export_grid(min_row, min_column,
+                (max_column - min_column + 1) as usize, (max_row - min_row + 1) as usize)?;
+            total.merge(&census::take_census(&settled, code)?);
+        }
+    }
+
+    print!("{}", total);
+
+    return Ok(());
+}
+
+fn run_analyze(arguments : &Arguments) -> Result<(), Box<dyn std::error::Error>> {
+    let path : &str = arguments.positional.first()
+        .ok_or_else(|| Error::Parse(String::from("missing input file")))?;
+
+    let mut grid : Grid = Grid::read_csv(path)?;
+    grid.set_connectivity(match arguments.get("connectivity", 4)? {
+        4 => Connectivity::FourConnected,
+        8 => Connectivity::EightConnected,
+        connectivity => return Err(Box::new(Error::Parse(format!("unknown connectivity {}", connectivity))))
+    });
+
+    let (b0, b1) : (usize, usize) = grid.get_betti_numbers();
+
+    println!("file : {}", path);
+    println!("width : {}", grid.get_width());
+    println!("height : {}", grid.get_height());
+    println!("population : {}", grid.get_population());
+    println!("density : {:.6}", grid.get_density());
+    println!("components (b0) : {}", b0);
+    println!("holes (b1) : {}", b1);
+    println!("euler characteristic : {}", b0 as isize - b1 as isize);
+
+    return Ok(());
+}
+
+// --rule is an outer totalistic code unless --totalistic is set; --rulestring wins over --rule.
+fn get_rule(arguments : &Arguments) -> Result<Box<dyn Rule + Sync>, Error> {
+    if let Some(rulestring) = arguments.get_str("rulestring") {
+        return Ok(Box::new(OuterTotalistic::from_rulestring(rulestring)?));
+    }
+
+    let code : usize = arguments.get("rule", 224)?;
+
+    if arguments.has_flag("totalistic") {
+        return Ok(Box::new(Totalistic::new(code)));
+    }
+
+    return Ok(Box::new(OuterTotalistic::new(code)));
+}
+
+// the rule of --rule or --rulestring, or else the rule named by the --input pattern, if any.
+fn get_input_rule(arguments : &Arguments, input : Option<&Pattern>) -> Result<Box<dyn Rule + Sync>, Error> {
+    let given : bool = arguments.get_str("rule").is_some() || arguments.get_str("rulestring").is_some();
+
+    if let (false, Some(rule)) = (given, input.and_then(|input| input.get_outer_totalistic())) {
+        return Ok(Box::new(rule));
+    }
+
+    return get_rule(arguments);
+}
+
+// --input : a csv grid, or a pattern file in a format of pattern_io.
+fn read_input(path : &str) -> Result<Pattern, Box<dyn std::error::Error>> {
+    if path.ends_with(".csv") {
+        return Ok(Pattern::new(Grid::read_csv(path)?));
+    }
+
+    return pattern_io::read(path);
+}
+
+// --output : a csv grid, or a pattern file in a format of pattern_io.
+fn write_output(path : &str, pattern : &Pattern) -> Result<(), Box<dyn std::error::Error>> {
+    if path.ends_with(".csv") {
+        return pattern.cells.write_csv(path);
+    }
+
+    return pattern_io::write(path, pattern);
+}
+
+// returns the values of `moore` to run.
+fn get_neighbourhoods(arguments : &Arguments) -> Result<Vec<bool>, Error> {
+    return match arguments.get_str("neighbourhood").unwrap_or("moore") {
+        "moore" => Ok(vec![true]),
+        "von-neumann" | "von_neumann" => Ok(vec![false]),
+        "both" => Ok(vec![false, true]),
+        neighbourhood => Err(Error::Parse(format!("unknown neighbourhood {}", neighbourhood)))
+    };
+}
+
+// whether --neighbourhood is Moore; `both` is only for sweeps.
+fn get_neighbourhood(arguments : &Arguments) -> Result<bool, Error> {
+    return match get_neighbourhoods(arguments)?.as_slice() {
+        [moore] => Ok(*moore),
+        _ => Err(Error::Parse(String::from("--neighbourhood both only applies to sweep")))
+    };
+}
+
+// parses `a..b` (exclusive), `a..=b` (inclusive) or a comma separated list.
+fn parse_range(range : &str) -> Result<Vec<usize>, Error> {
+    let parse = |value : &str| value.trim().parse::<usize>()
+        .map_err(|e| Error::Parse(format!("range {} : {}", range, e)));
+
+    if let Some((start, end)) = range.split_once("..=") {
+        return Ok((parse(start)?..=parse(end)?).collect());
+    }
+
+    if let Some((start, end)) = range.split_once("..") {
+        return Ok((parse(start)?..parse(end)?).collect());
+    }
+
+    return range.split(',').map(parse).collect();
+}
+
+fn parse_bits(bits : &str) -> Result<Vec<usize>, Error> {
+    return bits.chars()
+        .map(|bit| bit.to_digit(10).map(|bit| bit as usize)
+            .ok_or_else(|| Error::Parse(format!("invalid seed {}", bits))))
+        .collect();
+}
+
+// single 1 in the middle.
+fn single_seed(width : usize) -> Vec<usize> {
+    let mut initial_configuration : Vec<usize> = vec![0; width];
+    if width > 0 {
+        initial_configuration[width / 2] = 1;
+    }
+
+    return initial_configuration;
+}
+
+// --random-seed, or a fresh one; callers print it so the run can be re-created.
+fn get_random_seed(arguments : &Arguments) -> Result<u64, Error> {
+    return arguments.get("random-seed", rand::random::<u64>());
+}
+
+// --patch WxH, defaults to 16x16.
+fn get_patch(arguments : &Arguments) -> Result<(usize, usize), Error> {
+    let patch : &str = arguments.get_str("patch").unwrap_or("16x16");
+    let (width, height) : (&str, &str) = patch.split_once('x')
+        .ok_or_else(|| Error::Parse(format!("invalid patch {}, expected WxH", patch)))?;
+    let parse = |value : &str| value.parse::<usize>()
+        .map_err(|e| Error::Parse(format!("invalid patch {} : {}", patch, e)));
+
+    return Ok((parse(width)?, parse(height)?));
+}
+
+fn get_symmetry(arguments : &Arguments) -> Result<Symmetry, Error> {
+    return match arguments.get_str("soup").unwrap_or("asymmetric") {
+        "asymmetric" => Ok(Symmetry::Asymmetric),
+        "c2" | "C2" => Ok(Symmetry::C2),
+        "c4" | "C4" => Ok(Symmetry::C4),
+        "d4" | "D4" => Ok(Symmetry::D4),
+        soup => Err(Error::Parse(format!("unknown soup {}", soup)))
+    };
+}
+
+// --pattern, from --lexicon if given, else from the built-in library.
+fn get_pattern(arguments : &Arguments) -> Result<Option<Grid>, Box<dyn std::error::Error>> {
+    let name : &str = match arguments.get_str("pattern") {
+        Some(name) => name,
+        None => return Ok(None)
+    };
+
+    return match arguments.get_str("lexicon") {
+        Some(path) => match Lexicon::read(path)?.get(name) {
+            Some(pattern) => Ok(Some(pattern.clone())),
+            None => Err(Box::new(Error::Parse(format!("no pattern {} in {}", name, path))))
+        },
+        None => Ok(Some(patterns::get_pattern(name)?))
+    };
+}
+
+fn get_soup(arguments : &Arguments, width : usize, height : usize, random_seed : u64) -> Result<Grid, Error> {
+    let density : f64 = arguments.get("density", 0.5)?;
+
+    if arguments.get_str("soup") == Some("patch") {
+        let (patch_width, patch_height) : (usize, usize) = get_patch(arguments)?;
+        return Grid::random_patch(width, height, patch_width, patch_height, density, random_seed);
+    }
+
+    return Grid::random_symmetric(width, height, density, get_symmetry(arguments)?, random_seed);
+}
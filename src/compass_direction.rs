@@ -12,7 +12,7 @@
 ///
 /// ```
 /// /// NorthEast
-/// use compass_direction::CompassDirection;
+/// use gol_rs::compass_direction::CompassDirection;
 /// let ne = CompassDirection {x : 1, y : -1, cardinal : false, active : true}; // NorthEast
 /// ```
 pub struct CompassDirection {
@@ -23,12 +23,14 @@ pub struct CompassDirection {
 }
 
 
+/// The nine directions of a Moore neighbourhood, in row-major order starting at NorthWest.
 pub struct Compass {
     compass : Vec<CompassDirection>
 }
 
 impl Compass {
 
+    /// Returns the compass; diagonals are inactive when `cardinals_only` is set.
     pub fn new(cardinals_only : bool) -> Compass {
         let compass : Vec<CompassDirection> = vec![
             CompassDirection {x : -1, y : -1, cardinal : false, active : !cardinals_only}, // NorthWest
@@ -44,23 +46,16 @@ impl Compass {
         return Compass{compass : compass};
     }
 
-    // pub fn get_cardinals(&self) -> Vec<CompassDirection> {
-    //     let mut cardinals : Vec<CompassDirection> = Vec::<CompassDirection>::new();
-    //     for direction in self.compass {
-    //         if direction.cardinal {
-    //             cardinals.push(direction);
-    //         }
-    //     }
-    //     return cardinals;
-    // }
-    //
-    // pub fn get_active(&self) -> Vec<CompassDirection>{
-    //     let mut active_directions = Vec::<CompassDirection>::new();
-    //     for direction in self.compass {
-    //         if direction.active {
-    //             active_directions.push(direction);
-    //         }
-    //     }
-    //     return active_directions;
-    // }
+    /// Returns all nine directions, active or not.
+    pub fn get_directions(&self) -> &Vec<CompassDirection> {
+        return &self.compass;
+    }
+
+    pub fn get_cardinals(&self) -> Vec<&CompassDirection> {
+        return self.compass.iter().filter(|direction| direction.cardinal).collect();
+    }
+
+    pub fn get_active(&self) -> Vec<&CompassDirection> {
+        return self.compass.iter().filter(|direction| direction.active).collect();
+    }
 }
@@ -0,0 +1,101 @@
+// Cycle detection for deterministic automata.
+//
+// Every generation is hashed; the first time a hash comes back, the system has entered a
+// cycle : it will repeat the generations since the first occurrence forever. Only hashes are
+// kept, so two different generations with the same 64 bit hash would be taken for a cycle.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+/// A cycle : generation `start + period` is generation `start` again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cycle {
+    pub start : usize, // generation the cycle began
+    pub period : usize,
+    pub transient : usize, // generations from the first one seen to the start of the cycle
+}
+
+impl Cycle {
+    /// Returns the generation at which the cycle is detected, `start + period`.
+    pub fn get_detected(&self) -> usize {
+        return self.start + self.period;
+    }
+}
+
+/// Hashes generations as they come and reports the first cycle.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::cycle::CycleDetector;
+///
+/// // 7, 5, 1, 2, 3, 1, ... enters a cycle of period 3 at generation 2
+/// let mut detector = CycleDetector::new();
+/// let generations : [&[usize]; 6] = [&[7], &[5], &[1], &[2], &[3], &[1]];
+///
+/// let cycles : Vec<_> = generations.iter().enumerate()
+///     .map(|(generation, cells)| detector.push(generation, cells))
+///     .collect();
+///
+/// assert!(cycles[..5].iter().all(|cycle| cycle.is_none()));
+/// let cycle = cycles[5].unwrap();
+/// assert_eq!((cycle.start, cycle.period, cycle.transient), (2, 3, 2));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CycleDetector {
+    first : Option<usize>, // first generation pushed
+    seen : HashMap<u64, usize>, // hash -> generation
+    cycle : Option<Cycle>
+}
+
+impl CycleDetector {
+
+    pub fn new() -> CycleDetector {
+        return CycleDetector::default();
+    }
+
+    /// Records the cells of `generation`, and returns the cycle once one is found; later
+    /// generations are ignored.
+    pub fn push(&mut self, generation : usize, cells : &[usize]) -> Option<Cycle> {
+        return self.push_hash(generation, get_hash(cells));
+    }
+
+    /// Records the hash of `generation`; see `push`.
+    pub fn push_hash(&mut self, generation : usize, hash : u64) -> Option<Cycle> {
+        if self.cycle.is_some() {
+            return self.cycle;
+        }
+
+        let first : usize = *self.first.get_or_insert(generation);
+
+        if let Some(start) = self.seen.insert(hash, generation) {
+            self.cycle = Some(Cycle {
+                start : start,
+                period : generation - start,
+                transient : start - first
+            });
+        }
+
+        return self.cycle;
+    }
+
+    pub fn get_cycle(&self) -> Option<Cycle> {
+        return self.cycle;
+    }
+
+    /// Forgets every generation.
+    pub fn clear(&mut self) {
+        self.first = None;
+        self.seen.clear();
+        self.cycle = None;
+    }
+}
+
+/// Returns the hash of a generation.
+pub fn get_hash(cells : &[usize]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    cells.hash(&mut hasher);
+
+    return hasher.finish();
+}
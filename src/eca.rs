@@ -1,54 +1,370 @@
 extern crate petgraph;
 extern crate image;
 
+use crate::boundary::{Boundary, BoundaryType};
+use crate::compass_direction::CompassDirection;
+use crate::cycle::{self, Cycle, CycleDetector};
+use crate::eca_packed::PackedECA;
+use crate::eca_rule::ECARule;
+use crate::error::Error;
 use crate::grid::Grid;
 
+use rand::{Rng, SeedableRng};
+use rand::seq::SliceRandom;
+use rand_pcg::Pcg64;
+
 use std::fmt;
-use std::fmt::Write as FmtWrite;
+use std::str::FromStr;
 
 use std::fs::File;
 use std::path::Path;
-use std::io::prelude::*;
 
-const RULES : [u8; 88] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 18, 19, 22, 23,
+/// The 88 elementary rules that are inequivalent under reflection and complement.
+pub const RULES : [u8; 88] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 18, 19, 22, 23,
     24, 25, 26, 27, 28, 29, 30, 32, 33, 34, 35, 36, 37, 38, 40, 41, 42, 43, 44, 45, 46, 50, 51,
     54, 56, 57, 58, 60, 62, 72, 73, 74, 76, 77, 78, 90, 94, 104, 105, 106, 108, 110, 122, 126,
     128, 130, 132, 134, 136, 138, 140, 142, 146, 150, 152, 154, 156, 160, 162, 164, 168, 170,
     172, 178, 184, 200, 204, 232];
 
-// todo : would be nice to have all the equivalence classes available too.
-// todo : would be nice to have hash with universality type
+/// Wolfram's classification of the long term behaviour of a rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WolframClass {
+    /// evolves to a homogeneous state.
+    I,
+    /// evolves to stable or periodic structures.
+    II,
+    /// evolves to chaotic, aperiodic patterns.
+    III,
+    /// evolves to complex, localised structures that interact.
+    IV,
+}
+
+impl fmt::Display for WolframClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{:?}", self);
+    }
+}
+
+/// The Wolfram class of each of the 88 representatives in `RULES`.
+pub const WOLFRAM_CLASSES : [(u8, WolframClass); 88] = [
+    (0, WolframClass::I), (1, WolframClass::II), (2, WolframClass::II), (3, WolframClass::II),
+    (4, WolframClass::II), (5, WolframClass::II), (6, WolframClass::II), (7, WolframClass::II),
+    (8, WolframClass::I), (9, WolframClass::II), (10, WolframClass::II), (11, WolframClass::II),
+    (12, WolframClass::II), (13, WolframClass::II), (14, WolframClass::II), (15, WolframClass::II),
+    (18, WolframClass::III), (19, WolframClass::II), (22, WolframClass::III),
+    (23, WolframClass::II), (24, WolframClass::II), (25, WolframClass::II), (26, WolframClass::II),
+    (27, WolframClass::II), (28, WolframClass::II), (29, WolframClass::II),
+    (30, WolframClass::III), (32, WolframClass::I), (33, WolframClass::II), (34, WolframClass::II),
+    (35, WolframClass::II), (36, WolframClass::II), (37, WolframClass::II), (38, WolframClass::II),
+    (40, WolframClass::I), (41, WolframClass::IV), (42, WolframClass::II), (43, WolframClass::II),
+    (44, WolframClass::II), (45, WolframClass::III), (46, WolframClass::II),
+    (50, WolframClass::II), (51, WolframClass::II), (54, WolframClass::IV), (56, WolframClass::II),
+    (57, WolframClass::II), (58, WolframClass::II), (60, WolframClass::III),
+    (62, WolframClass::II), (72, WolframClass::II), (73, WolframClass::II), (74, WolframClass::II),
+    (76, WolframClass::II), (77, WolframClass::II), (78, WolframClass::II),
+    (90, WolframClass::III), (94, WolframClass::II), (104, WolframClass::II),
+    (105, WolframClass::III), (106, WolframClass::IV), (108, WolframClass::II),
+    (110, WolframClass::IV), (122, WolframClass::III), (126, WolframClass::III),
+    (128, WolframClass::I), (130, WolframClass::II), (132, WolframClass::II),
+    (134, WolframClass::II), (136, WolframClass::I), (138, WolframClass::II),
+    (140, WolframClass::II), (142, WolframClass::II), (146, WolframClass::III),
+    (150, WolframClass::III), (152, WolframClass::II), (154, WolframClass::II),
+    (156, WolframClass::II), (160, WolframClass::I), (162, WolframClass::II),
+    (164, WolframClass::II), (168, WolframClass::I), (170, WolframClass::II),
+    (172, WolframClass::II), (178, WolframClass::II), (184, WolframClass::II),
+    (200, WolframClass::II), (204, WolframClass::II), (232, WolframClass::II)
+];
+
+/// Returns the mirror image of `rule` : f'(a, b, c) = f(c, b, a).
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::eca;
+///
+/// assert_eq!(eca::get_reflection(110), 124);
+/// assert_eq!(eca::get_reflection(90), 90);
+/// ```
+pub fn get_reflection(rule : u8) -> u8 {
+    let mut reflection : u8 = 0;
+
+    for neighbourhood in 0..8 {
+        // swap a and c in (a, b, c)
+        let reflected : u8 = (neighbourhood & 0b010) | ((neighbourhood & 0b001) << 2) | ((neighbourhood & 0b100) >> 2);
+        reflection |= ((rule >> neighbourhood) & 1) << reflected;
+    }
+
+    return reflection;
+}
+
+/// Returns the complement of `rule`, which swaps 0 and 1 : f'(a, b, c) = 1 - f(1 - a, 1 - b, 1 - c).
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::eca;
+///
+/// assert_eq!(eca::get_complement(110), 137);
+/// ```
+pub fn get_complement(rule : u8) -> u8 {
+    let mut complement : u8 = 0;
+
+    for neighbourhood in 0..8 {
+        complement |= (1 - ((rule >> (7 - neighbourhood)) & 1)) << neighbourhood;
+    }
+
+    return complement;
+}
+
+/// Returns the complement of the reflection of `rule`.
+pub fn get_reflection_complement(rule : u8) -> u8 {
+    return get_complement(get_reflection(rule));
+}
+
+/// Returns the rules equivalent to `rule` under reflection and complement, `rule` included,
+/// sorted and without duplicates.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::eca;
+///
+/// assert_eq!(eca::get_equivalence_class(110), vec![110, 124, 137, 193]);
+/// assert_eq!(eca::get_equivalence_class(150), vec![150]);
+/// ```
+pub fn get_equivalence_class(rule : u8) -> Vec<u8> {
+    let mut class : Vec<u8> = vec![rule, get_reflection(rule), get_complement(rule), get_reflection_complement(rule)];
+    class.sort_unstable();
+    class.dedup();
+
+    return class;
+}
+
+/// Returns the smallest rule equivalent to `rule`; it is in `RULES`.
+pub fn get_representative(rule : u8) -> u8 {
+    return get_equivalence_class(rule)[0];
+}
+
+/// Returns the Wolfram class of `rule`, the class of its representative.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::eca::{self, WolframClass};
+///
+/// assert_eq!(eca::get_wolfram_class(30), WolframClass::III);
+/// assert_eq!(eca::get_wolfram_class(193), WolframClass::IV);
+/// ```
+pub fn get_wolfram_class(rule : u8) -> WolframClass {
+    let representative : u8 = get_representative(rule);
 
+    return WOLFRAM_CLASSES.iter()
+        .find(|(rule, _)| *rule == representative)
+        .map(|(_, class)| *class)
+        .unwrap(); // every representative is in the table
+}
+
+/// How a generation is computed from the previous ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UpdateMode {
+    /// every cell is updated at once, from the previous generation.
+    Synchronous,
+    /// Fredkin's second order rule : the synchronous update minus the generation before the
+    /// previous one, modulo the number of states (XOR, for 2 states), like rule 30R. These
+    /// rules are reversible; see `ECA::run_backwards`.
+    SecondOrder,
+    /// the cells are updated one at a time, in a random order, each seeing the cells updated
+    /// before it.
+    Asynchronous,
+    /// each cell is updated with the given probability, otherwise it keeps its state.
+    AlphaAsynchronous(f64),
+}
+
+impl UpdateMode {
+
+    /// Returns the suffix of a rule run with this mode : `R` for second order rules, as in 30R.
+    pub fn get_suffix(&self) -> String {
+        return match self {
+            UpdateMode::Synchronous => String::new(),
+            UpdateMode::SecondOrder => String::from("R"),
+            UpdateMode::Asynchronous => String::from("async"),
+            UpdateMode::AlphaAsynchronous(alpha) => format!("alpha{}", alpha),
+        };
+    }
+}
+
+impl FromStr for UpdateMode {
+    type Err = Error;
+
+    /// Parses `synchronous`, `second-order` (or `reversible`), `asynchronous` or `alpha:{alpha}`.
+    fn from_str(update_mode : &str) -> Result<UpdateMode, Error> {
+        let lowercase : String = update_mode.to_lowercase();
+
+        if let Some(alpha) = lowercase.strip_prefix("alpha:") {
+            return alpha.parse::<f64>()
+                .map(UpdateMode::AlphaAsynchronous)
+                .map_err(|e| Error::Parse(format!("invalid update mode {} : {}", update_mode, e)));
+        }
+
+        return match lowercase.as_str() {
+            "synchronous" => Ok(UpdateMode::Synchronous),
+            "second-order" | "reversible" => Ok(UpdateMode::SecondOrder),
+            "asynchronous" => Ok(UpdateMode::Asynchronous),
+            _ => Err(Error::Parse(format!("unknown update mode {}", update_mode)))
+        };
+    }
+}
+
+/// One dimensional cellular automaton : a spacetime diagram of `height` generations, each a row
+/// of `width` cells, where row `i` is the generation following row `i - 1`.
+///
+/// The rule is elementary by default; see `ECA::with_rule` for k states and radius r. For runs
+/// too long to keep every generation, see `eca_stream::ECAStream`.
 pub struct ECA {
-    // pub states : u8, // number of states
-    // pub radius : u8, //
-    // pub lattice : Vec<u32>,
-    rule : u8,
+    rule : ECARule,
     width : usize,
     height : usize,
     initial_configuration : Vec<usize>,
-    universe : Grid
+    compass : Vec<CompassDirection>, // -radius, ..., Origin, ..., radius
+    boundary : Boundary, // a single row
+    universe : Grid,
+    labelled : bool, // cells are connected component labels, not states
+    update_mode : UpdateMode,
+    previous_configuration : Vec<usize>, // generation before the first, for second order rules
+    seed : u64, // of the random update order
+    rng : Pcg64
 }
 
 impl ECA {
 
-    pub fn new(rule : u8, width : usize, height : usize, initial_configuration : Vec<usize>) -> ECA {
+    /// Returns a new elementary automaton whose first row is `initial_configuration`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, ECA};
+    ///
+    /// let mut automata = ECA::new(90, 5, 3, vec![0, 0, 1, 0, 0], BoundaryType::Null).unwrap();
+    /// automata.generate();
+    /// assert_eq!(automata.get_value(1, 1), 1);
+    /// assert_eq!(automata.get_value(1, 2), 0);
+    /// assert!(ECA::new(90, 5, 3, vec![1], BoundaryType::Null).is_err());
+    /// ```
+    pub fn new(rule : u8,
+        width : usize,
+        height : usize,
+        initial_configuration : Vec<usize>,
+        boundary_type : BoundaryType) -> Result<ECA, Error> {
+
+        return ECA::with_rule(ECARule::elementary(rule), width, height, initial_configuration, boundary_type);
+    }
+
+    /// Returns a new automaton with any one dimensional `rule` whose first row is
+    /// `initial_configuration`; every cell must be below the number of states of the rule.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, ECA, ECARule};
+    ///
+    /// let rule = ECARule::totalistic(3, 1, "1599").unwrap();
+    /// let mut automata = ECA::with_rule(rule.clone(), 5, 2, vec![0, 0, 1, 0, 0], BoundaryType::Null).unwrap();
+    /// automata.generate();
+    /// assert_eq!(automata.get_universe().get_grid()[5..], [0, 2, 2, 2, 0]);
+    ///
+    /// assert!(ECA::with_rule(rule, 5, 2, vec![0, 0, 3, 0, 0], BoundaryType::Null).is_err());
+    /// ```
+    pub fn with_rule(rule : ECARule,
+        width : usize,
+        height : usize,
+        initial_configuration : Vec<usize>,
+        boundary_type : BoundaryType) -> Result<ECA, Error> {
+
+        if initial_configuration.len() != width {
+            return Err(Error::ConfigurationMismatch { expected : width, found : initial_configuration.len() });
+        }
+
+        let boundary : Boundary = Boundary::new(width, 1, boundary_type);
+        ECA::check_states(&rule, &initial_configuration, &boundary)?;
 
         // initialize universe with initial configuration
-        let mut universe : Grid = Grid::new(width, height);
+        let mut universe : Grid = Grid::new(width, height)?;
 
         for (column_index, cell) in initial_configuration.iter().enumerate() {
             let row_index = 0;
             universe.set_value(row_index, column_index, *cell);
         }
 
-        return ECA {
+        return Ok(ECA {
+            compass : ECA::get_compass(rule.get_radius()),
             rule : rule,
             width : width,
             height : height,
             initial_configuration : initial_configuration.clone(),
-            universe : universe
-        };
+            boundary : boundary,
+            universe : universe,
+            labelled : false,
+            update_mode : UpdateMode::Synchronous,
+            previous_configuration : vec![0; width],
+            seed : 0,
+            rng : Pcg64::seed_from_u64(0)
+        });
+    }
+
+    // every cell, and the cells beyond the edge, must be below the number of states.
+    fn check_states(rule : &ECARule, initial_configuration : &[usize], boundary : &Boundary) -> Result<(), Error> {
+        let states : usize = rule.get_states();
+        let outside_value : usize = boundary.get_outside_value();
+
+        if let Some(state) = initial_configuration.iter().chain(Some(&outside_value)).find(|state| **state >= states) {
+            return Err(Error::InvalidState { state : *state, states : states });
+        }
+
+        return Ok(());
+    }
+
+    // -radius, ..., Origin, ..., radius
+    fn get_compass(radius : usize) -> Vec<CompassDirection> {
+        let radius : isize = radius as isize;
+
+        return (-radius..=radius)
+            .map(|x| CompassDirection {x : x, y : 0, cardinal : x.abs() == 1, active : true})
+            .collect();
+    }
+
+    /// Replaces the rule and resets the universe, keeping its allocation.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, ECA, ECARule};
+    ///
+    /// let mut automata = ECA::new(90, 5, 2, vec![0, 0, 1, 0, 0], BoundaryType::Null).unwrap();
+    /// automata.set_rule(ECARule::elementary(0)).unwrap();
+    /// automata.generate();
+    /// assert_eq!(automata.get_universe().get_population(), 1);
+    /// ```
+    pub fn set_rule(&mut self, rule : ECARule) -> Result<(), Error> {
+        ECA::check_states(&rule, &self.initial_configuration, &self.boundary)?;
+
+        self.compass = ECA::get_compass(rule.get_radius());
+        self.rule = rule;
+        self.reset();
+
+        return Ok(());
+    }
+
+    /// Replaces the initial configuration and resets the universe, keeping its allocation.
+    pub fn set_initial_configuration(&mut self, initial_configuration : Vec<usize>) -> Result<(), Error> {
+        if initial_configuration.len() != self.width {
+            return Err(Error::ConfigurationMismatch { expected : self.width, found : initial_configuration.len() });
+        }
+        ECA::check_states(&self.rule, &initial_configuration, &self.boundary)?;
+
+        self.initial_configuration = initial_configuration;
+        self.reset();
+
+        return Ok(());
     }
 
     pub fn get_value(&self, row_index : usize, column_index : usize) -> usize{
@@ -59,52 +375,377 @@ impl ECA {
         self.universe.set_value(row_index, column_index, value);
     }
 
-    fn increase_generation(&mut self, row_index : usize) {
-        for cell_index in 0..(self.width) {
-            // we look behind to the previous row to generate this row
-            let radius : u8 = self.get_radius(row_index - 1, cell_index);
-            let new_bit : usize = self.rule_lookup(radius);
-            self.set_value(row_index, cell_index, new_bit);
+    /// Sets how generations are computed, and resets the universe; random updates follow
+    /// `seed`, so a run can be repeated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, ECA};
+    /// use gol_rs::eca::UpdateMode;
+    ///
+    /// let mut automata = ECA::new(30, 9, 4, vec![0, 0, 0, 0, 1, 0, 0, 0, 0], BoundaryType::Null).unwrap();
+    /// automata.set_update_mode(UpdateMode::AlphaAsynchronous(0.5), 7).unwrap();
+    /// automata.generate();
+    /// let first_run = automata.get_universe().get_grid().clone();
+    ///
+    /// automata.reset();
+    /// automata.generate();
+    /// assert_eq!(automata.get_universe().get_grid(), &first_run);
+    ///
+    /// assert!(automata.set_update_mode(UpdateMode::AlphaAsynchronous(1.5), 7).is_err());
+    /// ```
+    pub fn set_update_mode(&mut self, update_mode : UpdateMode, seed : u64) -> Result<(), Error> {
+        if let UpdateMode::AlphaAsynchronous(alpha) = update_mode {
+            Grid::check_density(alpha)?;
         }
+
+        self.update_mode = update_mode;
+        self.seed = seed;
+        self.reset();
+
+        return Ok(());
     }
 
+    pub fn get_update_mode(&self) -> UpdateMode {
+        return self.update_mode;
+    }
+
+    /// Sets the generation before the first, used by second order rules (0 by default), and
+    /// resets the universe.
+    pub fn set_previous_configuration(&mut self, previous_configuration : Vec<usize>) -> Result<(), Error> {
+        if previous_configuration.len() != self.width {
+            return Err(Error::ConfigurationMismatch { expected : self.width, found : previous_configuration.len() });
+        }
+        ECA::check_states(&self.rule, &previous_configuration, &self.boundary)?;
+
+        self.previous_configuration = previous_configuration;
+        self.reset();
+
+        return Ok(());
+    }
+
+    pub(crate) fn increase_generation(&mut self, row_index : usize) {
+        match self.update_mode {
+            UpdateMode::Synchronous => {
+                for cell_index in 0..(self.width) {
+                    // we look behind to the previous row to generate this row
+                    let radius : usize = self.get_radius(row_index - 1, cell_index);
+                    let new_state : usize = self.rule_lookup(radius);
+                    self.set_value(row_index, cell_index, new_state);
+                }
+            },
+            UpdateMode::SecondOrder => {
+                let states : usize = self.rule.get_states();
+
+                for cell_index in 0..(self.width) {
+                    let radius : usize = self.get_radius(row_index - 1, cell_index);
+                    let before : usize = if row_index >= 2 {
+                        self.get_value(row_index - 2, cell_index)
+                    } else {
+                        self.previous_configuration[cell_index]
+                    };
+                    let new_state : usize = (self.rule_lookup(radius) + states - before) % states;
+                    self.set_value(row_index, cell_index, new_state);
+                }
+            },
+            UpdateMode::Asynchronous => {
+                // start from the previous row, and update it in place
+                for cell_index in 0..(self.width) {
+                    let cell : usize = self.get_value(row_index - 1, cell_index);
+                    self.set_value(row_index, cell_index, cell);
+                }
+
+                let mut order : Vec<usize> = (0..self.width).collect();
+                order.shuffle(&mut self.rng);
+
+                for cell_index in order {
+                    let radius : usize = self.get_radius(row_index, cell_index);
+                    let new_state : usize = self.rule_lookup(radius);
+                    self.set_value(row_index, cell_index, new_state);
+                }
+            },
+            UpdateMode::AlphaAsynchronous(alpha) => {
+                for cell_index in 0..(self.width) {
+                    let new_state : usize = if self.rng.gen_bool(alpha) {
+                        self.rule_lookup(self.get_radius(row_index - 1, cell_index))
+                    } else {
+                        self.get_value(row_index - 1, cell_index)
+                    };
+                    self.set_value(row_index, cell_index, new_state);
+                }
+            },
+        }
+    }
+
+    /// Fills every row after the first.
+    ///
+    /// Synchronous elementary rules are stepped 64 cells at a time; see `eca_packed`.
     pub fn generate(&mut self) {
+        let rule : Option<u8> = self.rule.get_elementary();
+
+        match rule {
+            Some(rule) if self.update_mode == UpdateMode::Synchronous => self.generate_packed(rule),
+            _ => self.generate_per_cell()
+        }
+    }
+
+    /// Fills every row after the first, one cell at a time; `generate` gives the same
+    /// universe, faster.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, ECA};
+    /// use gol_rs::eca;
+    ///
+    /// let boundary_types = [BoundaryType::Null, BoundaryType::Torus, BoundaryType::Reflective, BoundaryType::Constant(1)];
+    ///
+    /// for width in &[1, 64, 100] {
+    ///     let initial_configuration = eca::random_configuration(*width, 0.5, 1).unwrap();
+    ///
+    ///     for boundary_type in &boundary_types {
+    ///         for rule in 0..=255 {
+    ///             let mut packed = ECA::new(rule, *width, 20, initial_configuration.clone(), *boundary_type).unwrap();
+    ///             let mut per_cell = ECA::new(rule, *width, 20, initial_configuration.clone(), *boundary_type).unwrap();
+    ///             packed.generate();
+    ///             per_cell.generate_per_cell();
+    ///             assert_eq!(packed.get_universe().get_grid(), per_cell.get_universe().get_grid());
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn generate_per_cell(&mut self) {
+        for row_index in 1..self.height {
+            self.increase_generation(row_index);
+        }
+    }
+
+    fn generate_packed(&mut self, rule : u8) {
+        let width : usize = self.width;
+        let first_row : Vec<usize> = self.universe.get_grid()[..width].to_vec();
+
+        // the first row and the boundary have already been checked
+        let mut packed = PackedECA::new(rule, &first_row, self.boundary.boundary_type).unwrap();
+
+        for row_index in 1..self.height {
+            packed.step();
+
+            for column_index in 0..width {
+                self.universe.set_value(row_index, column_index, packed.get_value(column_index));
+            }
+        }
+    }
+
+    // the hash of the state at generation `row_index` : the row, and the row before it for
+    // second order rules.
+    fn get_state_hash(&self, row_index : usize) -> u64 {
+        let width : usize = self.width;
+        let grid : &Vec<usize> = self.universe.get_grid();
+        let row : &[usize] = &grid[row_index * width..(row_index + 1) * width];
+
+        if self.update_mode != UpdateMode::SecondOrder {
+            return cycle::get_hash(row);
+        }
+
+        let before : &[usize] = if row_index >= 1 {
+            &grid[(row_index - 1) * width..row_index * width]
+        } else {
+            &self.previous_configuration
+        };
+
+        return cycle::get_hash(&[before, row].concat());
+    }
+
+    /// Returns the first cycle among the rows of the universe, if any; only synchronous and
+    /// second order automata are deterministic, so random updates return None.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, ECA};
+    ///
+    /// // rule 90 on a torus of width 8 : every configuration dies out
+    /// let mut automata = ECA::new(90, 8, 20, vec![0, 0, 0, 1, 0, 0, 0, 0], BoundaryType::Torus).unwrap();
+    /// automata.generate();
+    ///
+    /// let cycle = automata.find_cycle().unwrap();
+    /// assert_eq!((cycle.start, cycle.period), (4, 1));
+    /// ```
+    pub fn find_cycle(&self) -> Option<Cycle> {
+        if !self.is_deterministic() {
+            return None;
+        }
+
+        let mut detector = CycleDetector::new();
+        for row_index in 0..self.height {
+            if let Some(cycle) = detector.push_hash(row_index, self.get_state_hash(row_index)) {
+                return Some(cycle);
+            }
+        }
+
+        return None;
+    }
+
+    /// Fills the rows like `generate`, but stops computing as soon as the automaton enters a
+    /// cycle, and fills the remaining rows by repeating it. Random updates are generated in
+    /// full, and return None.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, ECA};
+    /// use gol_rs::eca::UpdateMode;
+    ///
+    /// let initial_configuration = vec![0, 1, 1, 0, 1, 0, 0, 0, 1, 1];
+    ///
+    /// // rule 30 : period 5 from generation 9, and period 1701 when second order
+    /// for update_mode in [UpdateMode::Synchronous, UpdateMode::SecondOrder].iter() {
+    ///     let mut generated = ECA::new(30, 10, 2000, initial_configuration.clone(), BoundaryType::Torus).unwrap();
+    ///     generated.set_update_mode(*update_mode, 0).unwrap();
+    ///     generated.generate();
+    ///
+    ///     let mut stopped = ECA::new(30, 10, 2000, initial_configuration.clone(), BoundaryType::Torus).unwrap();
+    ///     stopped.set_update_mode(*update_mode, 0).unwrap();
+    ///     let cycle = stopped.generate_until_cycle();
+    ///
+    ///     assert_eq!(cycle, generated.find_cycle());
+    ///     assert_eq!(stopped.get_universe().get_grid(), generated.get_universe().get_grid());
+    /// }
+    ///
+    /// let mut automata = ECA::new(30, 10, 2000, initial_configuration, BoundaryType::Torus).unwrap();
+    /// let cycle = automata.generate_until_cycle().unwrap();
+    /// assert_eq!((cycle.start, cycle.period), (9, 5));
+    /// ```
+    pub fn generate_until_cycle(&mut self) -> Option<Cycle> {
+        if !self.is_deterministic() {
+            self.generate();
+            return None;
+        }
+
+        let width : usize = self.width;
+        let mut detector = CycleDetector::new();
+        detector.push_hash(0, self.get_state_hash(0));
+
         for row_index in 1..self.height {
             self.increase_generation(row_index);
+
+            if let Some(cycle) = detector.push_hash(row_index, self.get_state_hash(row_index)) {
+                // every later row repeats the one a period before
+                let cells : &mut [usize] = self.universe.get_cells_mut();
+                for next_row_index in (row_index + 1)..self.height {
+                    let source : usize = (next_row_index - cycle.period) * width;
+                    cells.copy_within(source..source + width, next_row_index * width);
+                }
+
+                return Some(cycle);
+            }
+        }
+
+        return None;
+    }
+
+    fn is_deterministic(&self) -> bool {
+        return self.update_mode == UpdateMode::Synchronous || self.update_mode == UpdateMode::SecondOrder;
+    }
+
+    /// Runs a second order automaton backwards from its last two generations : afterwards,
+    /// row `i` is the generation that was row `height - 1 - i`, so the last row is the initial
+    /// configuration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, ECA};
+    /// use gol_rs::eca::{self, UpdateMode};
+    ///
+    /// let initial_configuration = eca::random_configuration(32, 0.5, 3).unwrap();
+    /// let mut automata = ECA::new(30, 32, 20, initial_configuration.clone(), BoundaryType::Torus).unwrap();
+    /// automata.set_update_mode(UpdateMode::SecondOrder, 0).unwrap();
+    /// automata.generate();
+    ///
+    /// automata.run_backwards().unwrap();
+    /// assert_eq!(automata.get_universe().get_grid()[19 * 32..], initial_configuration[..]);
+    /// ```
+    pub fn run_backwards(&mut self) -> Result<(), Error> {
+        if self.update_mode != UpdateMode::SecondOrder {
+            return Err(Error::NotReversible);
         }
+
+        let width : usize = self.width;
+        let height : usize = self.height;
+        let states : usize = self.rule.get_states();
+        let grid : &Vec<usize> = self.universe.get_grid();
+
+        let last : Vec<usize> = grid[(height - 1) * width..].to_vec();
+        let before_last : Vec<usize> = if height >= 2 {
+            grid[(height - 2) * width..(height - 1) * width].to_vec()
+        } else {
+            self.previous_configuration.clone()
+        };
+
+        // the generation after the last, which comes before it when running backwards
+        let next : Vec<usize> = (0..width)
+            .map(|cell_index| {
+                let radius : usize = self.get_radius(height - 1, cell_index);
+                (self.rule_lookup(radius) + states - before_last[cell_index]) % states
+            })
+            .collect();
+
+        // the rule is its own inverse : x(t - 1) = f(x(t)) - x(t + 1)
+        self.initial_configuration = last;
+        self.previous_configuration = next;
+        self.reset();
+        self.generate();
+
+        return Ok(());
     }
 
-    fn get_radius(&self, row_index : usize, column_index : usize) -> u8 {
+    fn get_radius(&self, row_index : usize, column_index : usize) -> usize {
+        // the neighbourhood as the digits of a base k number, or its sum if totalistic.
+        let states : usize = self.rule.get_states();
+        let totalistic : bool = self.rule.is_totalistic();
+        let mut radius : usize = 0;
+
+        for direction in &self.compass {
+            // the boundary is a single row; row_index is the generation
+            let cell : usize = match self.boundary.get_neighbour(0, column_index, direction) {
+                Some((_, neighbour_index)) => self.get_value(row_index, neighbour_index),
+                None => self.boundary.get_outside_value()
+            };
 
-         let is_periodic : bool = false; // todo : eventually pass through
+            radius = if totalistic { radius + cell } else { radius * states + cell };
+        }
 
-         let mut a : usize = self.get_value(
-             row_index,
-             (column_index +
-                 if column_index == 0 {self.width} else {0}) - 1
-                 % self.width);
-         let b : usize = self.get_value(row_index, column_index);
-         let mut c : usize = self.get_value(row_index, (column_index + 1) % self.width);
+        return radius; // index in the rule table
+    }
 
-         if is_periodic == false {
-             if (column_index +
-                 if column_index == 0 {self.width} else {0} - 1) % self.width == 0 {
-                 a = (a ^ a) & 1;
-             }
-             if (column_index + 1) % self.width == self.width - 1 {
-                 c = (c ^ c) & 1;
-             }
-         }
+    pub fn get_rule(&self) -> &ECARule {
+        return &self.rule;
+    }
 
-         return (a << 2 | b << 1 | c) as u8; // the new rule, as a number
+    pub fn get_boundary_type(&self) -> BoundaryType {
+        return self.boundary.boundary_type;
     }
 
-    fn rule_lookup(&self, rule : u8) -> usize{
-        return ((self.rule & (1 << rule)) >> rule) as usize; // todo : maybe rename rule to shift
+    fn rule_lookup(&self, radius : usize) -> usize{
+        return self.rule.get_table()[radius];
     }
 
+    /// Returns the universe as RGBA pixels; 0 is white, labels are coloured, and with more
+    /// than 2 states the states are shades of grey, from white to black.
     pub fn get_flattened_universe(&self) -> Vec<u8> {
         let mut universe : Vec<u8> = Vec::new();
+        let states : usize = self.rule.get_states();
+
+        if states > 2 && !self.labelled {
+            for cell in self.universe.get_grid() {
+                let grey : u8 = (255 - cell * 255 / (states - 1)) as u8;
+                universe.extend_from_slice(&[grey, grey, grey, 0xff]);
+            }
+
+            return universe;
+        }
 
         for row_index in 0..self.height {
             for column_index in 0..self.width {
@@ -113,7 +754,6 @@ impl ECA {
                 let a : u8 = (cell & 255) as u8;
                 let b : u8 = (cell & (255 << 8)) as u8;
                 let c : u8 = (cell & (255 << 16)) as u8;
-                let d : u8 = (cell & (255 << 24)) as u8;
 
                 if cell == 0 {
                     universe.push(0xff);
@@ -132,24 +772,45 @@ impl ECA {
         return universe;
     }
 
+    /// Replaces every cell with its connected component label.
     pub fn generate_connected_components(&mut self) {
         self.universe.label_connected_components();
+        self.labelled = true;
     }
 
     pub fn save_to_csv(&self) -> Result<(), Box<dyn std::error::Error>> {
         return self.universe.save_to_csv();
     }
 
+    /// Saves the spacetime diagram as a png image at `path`.
+    pub fn save_to_png(&self, path : &str) -> Result<(), Box<dyn std::error::Error>> {
+        let buffer = self.get_flattened_universe();
+        image::save_buffer(path, buffer.as_slice(), self.width as u32, self.height as u32, image::RGBA(8))?;
+
+        return Ok(());
+    }
+
+    // moves every row up by one, dropping the first; the last row is left as it was.
+    pub(crate) fn scroll(&mut self) {
+        let width : usize = self.width;
+        self.universe.get_cells_mut().copy_within(width.., 0);
+    }
+
+    pub fn get_universe(&self) -> &Grid {
+        return &self.universe;
+    }
+
+    /// Clears the universe back to the initial configuration.
     pub fn reset(&mut self) {
-        // initialize universe with initial configuration
-        let mut universe : Grid = Grid::new(self.width, self.height);
+        self.universe.clear();
+        self.rng = Pcg64::seed_from_u64(self.seed);
 
         for (column_index, cell) in self.initial_configuration.iter().enumerate() {
             let row_index = 0;
-            universe.set_value(row_index, column_index, *cell);
+            self.universe.set_value(row_index, column_index, *cell);
         }
 
-        self.universe = universe;
+        self.labelled = false;
     }
 
 }
@@ -165,11 +826,74 @@ impl fmt::Display for ECA {
     }
 }
 
-pub fn generate_rule(rule : u8, width : usize, height : usize, seed : Vec<usize>, generate_csv : bool, generate_images : bool, resize : bool, ccl : bool) {
+/// Returns a random initial configuration where each cell is 1 with probability `density`.
+///
+/// The same `seed` always gives the same configuration.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::eca;
+///
+/// let seed = eca::random_configuration(100, 0.5, 7).unwrap();
+/// assert_eq!(seed, eca::random_configuration(100, 0.5, 7).unwrap());
+/// ```
+pub fn random_configuration(width : usize, density : f64, seed : u64) -> Result<Vec<usize>, Error> {
+    return Ok(Grid::random(width, 1, density, seed)?.get_grid().clone());
+}
+
+/// Returns a random initial configuration with `states` states, where each cell is non-zero
+/// with probability `density`, each non-zero state being equally likely.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::eca;
+///
+/// let seed = eca::random_states_configuration(100, 3, 0.5, 7).unwrap();
+/// assert!(seed.iter().all(|cell| *cell < 3));
+///
+/// // the state of a live cell does not depend on it being alive
+/// let first_cells : Vec<usize> = (0..2000)
+///     .map(|seed| eca::random_states_configuration(8, 3, 0.5, seed).unwrap()[0])
+///     .filter(|cell| *cell != 0)
+///     .collect();
+/// let ones : f64 = first_cells.iter().filter(|cell| **cell == 1).count() as f64 / first_cells.len() as f64;
+/// assert!((ones - 0.5).abs() < 0.05);
+/// ```
+pub fn random_states_configuration(width : usize, states : usize, density : f64, seed : u64) -> Result<Vec<usize>, Error> {
+    Grid::check_density(density)?;
+    if width == 0 {
+        return Err(Error::InvalidDimensions { width : width, height : 1 });
+    }
 
-    let filename = format!("./rule{}length{}.png", &rule.to_string(), &width.to_string());
+    // liveness and state from one stream, drawn in turn
+    let mut rng = Pcg64::seed_from_u64(seed);
+
+    return Ok((0..width)
+        .map(|_| if rng.gen_bool(density) && states >= 2 { rng.gen_range(1, states) } else { 0 })
+        .collect());
+}
+
+/// Returns an empty initial configuration with `patch_width` random cells in the middle; see
+/// `random_configuration`.
+pub fn random_patch_configuration(width : usize, patch_width : usize, density : f64, seed : u64) -> Result<Vec<usize>, Error> {
+    return Ok(Grid::random_patch(width, 1, patch_width, 1, density, seed)?.get_grid().clone());
+}
+
+/// Runs `rule` from `seed` and saves the spacetime diagram to `./rule{}length{}.png`; see
+/// `get_file_stem` for rules that aren't elementary.
+pub fn generate_rule(rule : &ECARule, width : usize, height : usize, seed : Vec<usize>, generate_csv : bool, generate_images : bool, resize : bool, ccl : bool) -> Result<(), Box<dyn std::error::Error>> {
+
+    let mut automata = ECA::with_rule(rule.clone(), width, height, seed, BoundaryType::Null)?;
+
+    return save_rule(&mut automata, generate_csv, generate_images, resize, ccl);
+}
+
+// generates `automata` and saves it to `./{file stem}length{}.png`.
+fn save_rule(automata : &mut ECA, generate_csv : bool, generate_images : bool, resize : bool, ccl : bool) -> Result<(), Box<dyn std::error::Error>> {
+    let filename = format!("./{}length{}.png", get_file_stem(automata.get_rule()), automata.width);
 
-    let mut automata = ECA::new(rule as u8, width, height, seed);
     automata.generate();
 
     if ccl {
@@ -178,47 +902,168 @@ pub fn generate_rule(rule : u8, width : usize, height : usize, seed : Vec<usize>
     }
 
     if generate_images {
-        let buffer = automata.get_flattened_universe();
-        image::save_buffer(&filename, buffer.as_slice(), width as u32, height as u32, image::RGBA(8)).unwrap();
+        automata.save_to_png(&filename)?;
 
         if resize { // extremely slow
             // resize
-            let mut im = image::open(&filename).unwrap();
+            let im = image::open(&filename)?;
             // let mut fout = File::open(filename);
-            let fout = &mut File::create(&Path::new(&filename)).unwrap();
+            let fout = &mut File::create(Path::new(&filename))?;
             // Write the contents of this image to the Writer in PNG format.
             // im.thumbnail(800, 600).write_to(fout, image::PNG).unwrap();
-            im.resize(1063, 1375, image::FilterType::Nearest).write_to(fout, image::PNG).unwrap();
+            im.resize(1063, 1375, image::FilterType::Nearest).write_to(fout, image::PNG)?;
         }
     }
 
     if generate_csv {
-        automata.save_to_csv().unwrap();
+        automata.save_to_csv()?;
     }
 
+    return Ok(());
 }
 
-pub fn generate_all_rules(width : usize, height : usize, seed : Vec<usize>, generate_csv : bool, generate_images : bool, resize : bool, ccl : bool) {
+/// Runs `generate_rule` for all 256 rules, or only for the 88 in `RULES` if
+/// `representatives_only`.
+pub fn generate_all_rules(width : usize, height : usize, seed : Vec<usize>, generate_csv : bool, generate_images : bool, resize : bool, ccl : bool, representatives_only : bool) -> Result<(), Box<dyn std::error::Error>> {
 
-    // inequivalent rules
-    // for rule in RULES.iter() {
-    //     generate_rule(*rule, width, height, generate_csv, generate_images, resize, ccl);
-    // }
+    let rules : Vec<u8> = if representatives_only {
+        RULES.to_vec()
+    } else {
+        (0..=255).collect()
+    };
 
-    // todo : should be able to loop and just rewrite the same automata object; then I don't
-    //          have to keep reallocating the universe data on the heap
+    // one automaton, whose universe is rewritten for every rule
+    let mut automata = ECA::new(rules[0], width, height, seed, BoundaryType::Null)?;
 
-    for rule in 0..256 {
-        generate_rule(rule as u8, width, height, seed.clone(), generate_csv, generate_images, resize, ccl);
+    for rule in rules {
+        automata.set_rule(ECARule::elementary(rule))?;
+        save_rule(&mut automata, generate_csv, generate_images, resize, ccl)?;
     }
+
+    return Ok(());
 }
 
 
 
-// todo : write function that takes a random seed and generates all nonequiv rules for it.
+/// Returns `rule{code}` for elementary rules, `rule{code}k{states}r{radius}` otherwise, and
+/// `code{code}k{states}r{radius}` for totalistic rules.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::{eca, ECARule};
+///
+/// assert_eq!(eca::get_file_stem(&ECARule::elementary(30)), "rule30");
+/// assert_eq!(eca::get_file_stem(&ECARule::totalistic(3, 1, "1599").unwrap()), "code1599k3r1");
+/// ```
+pub fn get_file_stem(rule : &ECARule) -> String {
+    if rule.get_elementary().is_some() {
+        return format!("rule{}", rule.get_code());
+    }
+
+    let prefix : &str = if rule.is_totalistic() { "code" } else { "rule" };
+
+    return format!("{}{}k{}r{}", prefix, rule.get_code(), rule.get_states(), rule.get_radius());
+}
 
-// todo : write function that takes a 255 random seeds and runs all nonequiv rules for them.
+/// Statistics of the spacetime diagram of one rule, from one random seed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchResult {
+    pub rule : u8,
+    pub class : WolframClass,
+    pub seed : u64,
+    pub population : usize, // of the whole diagram
+    pub density : f64,
+    pub final_density : f64, // of the last generation
+    pub components : usize,
+    pub holes : usize,
+}
 
-// todo : add proper boundary code
+const BATCH_CSV_HEADER : &str = "rule,class,seed,population,density,final_density,components,holes";
+
+impl BatchResult {
+
+    pub fn get_csv_header() -> &'static str {
+        return BATCH_CSV_HEADER;
+    }
+
+    pub fn get_csv_row(&self) -> String {
+        return format!("{},{},{},{},{:.6},{:.6},{},{}",
+            self.rule,
+            self.class,
+            self.seed,
+            self.population,
+            self.density,
+            self.final_density,
+            self.components,
+            self.holes);
+    }
+}
+
+/// Runs the 88 rules in `RULES` from the random configuration of each seed in `seeds` (see
+/// `random_configuration`), with a null boundary, and returns their statistics.
+///
+/// Everything is written under `directory` :
+///
+/// * `statistics.csv` - one row per rule and seed, rule by rule.
+/// * `rule{rule}/seed{seed}.png` - the spacetime diagrams, if `generate_images`.
+///
+/// A single `ECA` is allocated and rewritten for every run.
+pub fn generate_batch(width : usize,
+    height : usize,
+    density : f64,
+    seeds : &[u64],
+    directory : &str,
+    generate_images : bool) -> Result<Vec<BatchResult>, Box<dyn std::error::Error>> {
+
+    let directory : &Path = Path::new(directory);
+    std::fs::create_dir_all(directory)?;
+
+    let mut automata = ECA::new(RULES[0], width, height, vec![0; width], BoundaryType::Null)?;
+    let mut results : Vec<BatchResult> = Vec::with_capacity(RULES.len() * seeds.len());
+
+    for rule in RULES.iter() {
+        let rule_directory = directory.join(format!("rule{}", rule));
+        if generate_images {
+            std::fs::create_dir_all(&rule_directory)?;
+        }
+
+        automata.set_rule(ECARule::elementary(*rule))?;
+
+        for seed in seeds {
+            automata.set_initial_configuration(random_configuration(width, density, *seed)?)?;
+            automata.generate();
+
+            let universe : &Grid = automata.get_universe();
+            let final_generation : &[usize] = &universe.get_grid()[(height - 1) * width..];
+            let (components, holes) : (usize, usize) = universe.get_betti_numbers();
+
+            results.push(BatchResult {
+                rule : *rule,
+                class : get_wolfram_class(*rule),
+                seed : *seed,
+                population : universe.get_population(),
+                density : universe.get_density(),
+                final_density : final_generation.iter().filter(|cell| **cell != 0).count() as f64 / width as f64,
+                components : components,
+                holes : holes,
+            });
+
+            if generate_images {
+                let filename = rule_directory.join(format!("seed{}.png", seed));
+                automata.save_to_png(&filename.to_string_lossy())?;
+            }
+        }
+    }
+
+    let mut statistics : String = format!("{}\n", BATCH_CSV_HEADER);
+    for result in &results {
+        statistics.push_str(&result.get_csv_row());
+        statistics.push('\n');
+    }
+    std::fs::write(directory.join("statistics.csv"), statistics)?;
+
+    return Ok(results);
+}
 
 // todo : add insert code, after increasing generation
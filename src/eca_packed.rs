@@ -0,0 +1,180 @@
+// Bit-packed elementary automata.
+//
+// A row of cells is stored 64 to a word, cell i at bit i % 64 of word i / 64. The west and east
+// neighbours of every cell of a word are the word shifted left and right by one, with the bit
+// carried in from the next word, so a step is a handful of boolean operations per 64 cells :
+// the rule is the sum of the minterms (a & b & c, a & b & !c, ...) of its 1 bits.
+
+use crate::boundary::BoundaryType;
+use crate::error::Error;
+
+/// An elementary (2 states, radius 1) automaton on a single, bit-packed row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackedECA {
+    rule : u8,
+    width : usize,
+    boundary_type : BoundaryType,
+    words : Vec<u64>, // bits past the width are always 0
+    next : Vec<u64>
+}
+
+impl PackedECA {
+
+    /// Returns a new automaton whose row is `initial_configuration`; every cell, and a
+    /// constant boundary, must be 0 or 1.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::BoundaryType;
+    /// use gol_rs::eca_packed::PackedECA;
+    ///
+    /// let mut automata = PackedECA::new(90, &[0, 0, 1, 0, 0], BoundaryType::Null).unwrap();
+    /// automata.step();
+    /// assert_eq!(automata.get_row(), vec![0, 1, 0, 1, 0]);
+    ///
+    /// assert!(PackedECA::new(90, &[0, 2, 0], BoundaryType::Null).is_err());
+    /// ```
+    pub fn new(rule : u8, initial_configuration : &[usize], boundary_type : BoundaryType) -> Result<PackedECA, Error> {
+        let width : usize = initial_configuration.len();
+        if width == 0 {
+            return Err(Error::InvalidDimensions { width : 0, height : 1 });
+        }
+
+        if let BoundaryType::Constant(value) = boundary_type {
+            if value > 1 {
+                return Err(Error::InvalidState { state : value, states : 2 });
+            }
+        }
+
+        let mut automata = PackedECA {
+            rule : rule,
+            width : width,
+            boundary_type : boundary_type,
+            words : vec![0; width.div_ceil(64)],
+            next : vec![0; width.div_ceil(64)]
+        };
+        automata.set_row(initial_configuration)?;
+
+        return Ok(automata);
+    }
+
+    /// Replaces the row; every cell must be 0 or 1.
+    pub fn set_row(&mut self, row : &[usize]) -> Result<(), Error> {
+        if row.len() != self.width {
+            return Err(Error::ConfigurationMismatch { expected : self.width, found : row.len() });
+        }
+
+        if let Some(state) = row.iter().find(|cell| **cell > 1) {
+            return Err(Error::InvalidState { state : *state, states : 2 });
+        }
+
+        self.words.iter_mut().for_each(|word| *word = 0);
+        for (index, cell) in row.iter().enumerate() {
+            self.words[index / 64] |= (*cell as u64) << (index % 64);
+        }
+
+        return Ok(());
+    }
+
+    pub fn set_rule(&mut self, rule : u8) {
+        self.rule = rule;
+    }
+
+    pub fn get_rule(&self) -> u8 {
+        return self.rule;
+    }
+
+    pub fn get_width(&self) -> usize {
+        return self.width;
+    }
+
+    /// Returns the packed row, 64 cells to a word.
+    pub fn get_words(&self) -> &[u64] {
+        return &self.words;
+    }
+
+    pub fn get_value(&self, index : usize) -> usize {
+        return ((self.words[index / 64] >> (index % 64)) & 1) as usize;
+    }
+
+    /// Returns the row, one cell per value.
+    pub fn get_row(&self) -> Vec<usize> {
+        return (0..self.width).map(|index| self.get_value(index)).collect();
+    }
+
+    /// Writes the row into `row`, which must be `width` long.
+    pub fn write_row(&self, row : &mut [usize]) {
+        for (index, cell) in row.iter_mut().enumerate() {
+            *cell = self.get_value(index);
+        }
+    }
+
+    /// Returns the number of 1 cells.
+    pub fn get_population(&self) -> usize {
+        return self.words.iter().map(|word| word.count_ones() as usize).sum();
+    }
+
+    // values of the cells west of the first cell and east of the last one.
+    fn get_ghost_cells(&self) -> (u64, u64) {
+        let first : u64 = self.words[0] & 1;
+        let last : u64 = self.get_value(self.width - 1) as u64;
+
+        return match self.boundary_type {
+            BoundaryType::Null => (0, 0),
+            BoundaryType::Constant(value) => (value as u64, value as u64),
+            // a single row : every gluing is periodic
+            BoundaryType::Cylinder | BoundaryType::Moebius | BoundaryType::Torus | BoundaryType::Klein => (last, first),
+            BoundaryType::Reflective => (first, last),
+        };
+    }
+
+    /// Computes the next generation.
+    pub fn step(&mut self) {
+        let (west_ghost, east_ghost) : (u64, u64) = self.get_ghost_cells();
+        let length : usize = self.words.len();
+        let last_bit : usize = (self.width - 1) % 64;
+
+        for index in 0..length {
+            let word : u64 = self.words[index];
+
+            let west_carry : u64 = if index == 0 { west_ghost } else { self.words[index - 1] >> 63 };
+            let east_carry : u64 = if index + 1 == length { 0 } else { self.words[index + 1] << 63 };
+
+            let west : u64 = (word << 1) | west_carry;
+            let mut east : u64 = (word >> 1) | east_carry;
+            if index + 1 == length {
+                east |= east_ghost << last_bit; // the bit past the last cell is 0
+            }
+
+            self.next[index] = apply_rule(self.rule, west, word, east);
+        }
+
+        // keep the bits past the width at 0
+        if last_bit < 63 {
+            self.next[length - 1] &= (1 << (last_bit + 1)) - 1;
+        }
+
+        std::mem::swap(&mut self.words, &mut self.next);
+    }
+}
+
+// the next state of 64 cells at once, from their west, own and east bits.
+fn apply_rule(rule : u8, west : u64, origin : u64, east : u64) -> u64 {
+    let mut next : u64 = 0;
+
+    for neighbourhood in 0..8 {
+        if (rule >> neighbourhood) & 1 == 0 {
+            continue;
+        }
+
+        // neighbourhood = 4 * west + 2 * origin + east
+        let a : u64 = if neighbourhood & 0b100 != 0 { west } else { !west };
+        let b : u64 = if neighbourhood & 0b010 != 0 { origin } else { !origin };
+        let c : u64 = if neighbourhood & 0b001 != 0 { east } else { !east };
+
+        next |= a & b & c;
+    }
+
+    return next;
+}
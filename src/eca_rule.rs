@@ -0,0 +1,254 @@
+// Rules of one dimensional automata with k states (colours) and radius r, in Wolfram's
+// numbering.
+//
+// A neighbourhood (x_{-r}, ..., x_0, ..., x_r) is read as a number in base k, with x_{-r} the
+// most significant digit. The rule number is the number whose base k digit at position n is the
+// next state of neighbourhood n, so it has k^(2r + 1) digits; for k = 3, r = 2 that is 3^243,
+// hence rule numbers are kept as decimal strings.
+//
+// A totalistic code only looks at the sum s of the neighbourhood; its base k digit at position
+// s is the next state, for s = 0, 1, ..., (2r + 1)(k - 1).
+
+use crate::error::Error;
+
+use std::convert::TryFrom;
+use std::fmt;
+
+// the most neighbourhoods (or sums) a rule may have : 2 states up to radius 7, 3 states up to
+// radius 4. Codes are converted digit by digit, so longer tables would be too slow to be of use.
+const MAX_TABLE_LENGTH : usize = 1 << 16;
+
+/// A one dimensional rule : `states` colours, `radius` cells on each side.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ECARule {
+    states : usize,
+    radius : usize,
+    totalistic : bool,
+    code : String, // decimal, without leading zeros
+    table : Vec<usize> // next state of each neighbourhood (or sum)
+}
+
+impl ECARule {
+
+    /// Returns the elementary (2 states, radius 1) rule `rule`.
+    pub fn elementary(rule : u8) -> ECARule {
+        return ECARule::from_code(2, 1, &rule.to_string()).unwrap();
+    }
+
+    /// Parses a rule number in Wolfram's numbering.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::eca_rule::ECARule;
+    ///
+    /// let rule = ECARule::from_code(2, 1, "110").unwrap();
+    /// assert_eq!(rule.lookup(&[1, 1, 1]), 0);
+    /// assert_eq!(rule.lookup(&[1, 1, 0]), 1);
+    ///
+    /// // k = 3, r = 1 rules go up to 3^27 - 1
+    /// assert!(ECARule::from_code(3, 1, "7625597484986").is_ok());
+    /// assert!(ECARule::from_code(3, 1, "7625597484987").is_err());
+    ///
+    /// // 2^51 neighbourhoods
+    /// assert!(ECARule::from_code(2, 25, "0").is_err());
+    /// ```
+    pub fn from_code(states : usize, radius : usize, code : &str) -> Result<ECARule, Error> {
+        ECARule::check(states, radius)?;
+
+        let neighbourhoods : usize = radius.checked_mul(2).and_then(|diameter| diameter.checked_add(1))
+            .and_then(|cells| u32::try_from(cells).ok())
+            .and_then(|cells| states.checked_pow(cells))
+            .ok_or_else(|| Error::Parse(format!("too many neighbourhoods for {} states, radius {}", states, radius)))?;
+
+        return ECARule::from_digits(states, radius, false, code, neighbourhoods);
+    }
+
+    /// Parses a totalistic code.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::eca_rule::ECARule;
+    ///
+    /// // 1599 = 2012020 in base 3
+    /// let rule = ECARule::totalistic(3, 1, "1599").unwrap();
+    /// assert_eq!(rule.lookup(&[0, 0, 0]), 0);
+    /// assert_eq!(rule.lookup(&[0, 1, 0]), 2);
+    /// assert_eq!(rule.lookup(&[2, 2, 2]), 2);
+    ///
+    /// assert!(ECARule::totalistic(3, usize::MAX, "0").is_err());
+    /// ```
+    pub fn totalistic(states : usize, radius : usize, code : &str) -> Result<ECARule, Error> {
+        ECARule::check(states, radius)?;
+
+        let sums : usize = radius.checked_mul(2).and_then(|diameter| diameter.checked_add(1))
+            .and_then(|cells| cells.checked_mul(states - 1))
+            .and_then(|sum| sum.checked_add(1))
+            .ok_or_else(|| Error::Parse(format!("too many sums for {} states, radius {}", states, radius)))?;
+
+        return ECARule::from_digits(states, radius, true, code, sums);
+    }
+
+    fn check(states : usize, radius : usize) -> Result<(), Error> {
+        if states < 2 {
+            return Err(Error::Parse(format!("a rule needs at least 2 states, found {}", states)));
+        }
+
+        if radius == 0 {
+            return Err(Error::Parse(String::from("a rule needs a radius of at least 1")));
+        }
+
+        return Ok(());
+    }
+
+    fn from_digits(states : usize,
+        radius : usize,
+        totalistic : bool,
+        code : &str,
+        length : usize) -> Result<ECARule, Error> {
+
+        if length > MAX_TABLE_LENGTH {
+            return Err(Error::Parse(format!("rules of {} states, radius {} have {} entries, more than {}",
+                states, radius, length, MAX_TABLE_LENGTH)));
+        }
+
+        let table : Vec<usize> = to_base(code, states, length)?;
+
+        return Ok(ECARule {
+            states : states,
+            radius : radius,
+            totalistic : totalistic,
+            code : from_base(&table, states),
+            table : table
+        });
+    }
+
+    /// Returns the rule given by the next state of every neighbourhood (or sum, if
+    /// `totalistic`); `table[n]` is the next state of neighbourhood n.
+    pub fn from_table(states : usize, radius : usize, totalistic : bool, table : Vec<usize>) -> Result<ECARule, Error> {
+        if let Some(state) = table.iter().find(|state| **state >= states) {
+            return Err(Error::InvalidState { state : *state, states : states });
+        }
+
+        let code : String = from_base(&table, states);
+
+        if totalistic {
+            return ECARule::totalistic(states, radius, &code);
+        }
+
+        return ECARule::from_code(states, radius, &code);
+    }
+
+    pub fn get_states(&self) -> usize {
+        return self.states;
+    }
+
+    pub fn get_radius(&self) -> usize {
+        return self.radius;
+    }
+
+    pub fn is_totalistic(&self) -> bool {
+        return self.totalistic;
+    }
+
+    /// Returns the rule number, in decimal.
+    pub fn get_code(&self) -> &str {
+        return &self.code;
+    }
+
+    /// Returns the next state of every neighbourhood (or sum).
+    pub fn get_table(&self) -> &Vec<usize> {
+        return &self.table;
+    }
+
+    /// Returns the elementary rule number, if this is an elementary rule.
+    pub fn get_elementary(&self) -> Option<u8> {
+        if self.states != 2 || self.radius != 1 || self.totalistic {
+            return None;
+        }
+
+        return self.code.parse::<u8>().ok();
+    }
+
+    /// Returns the index of `neighbourhood` (2 * radius + 1 cells, left to right) in the table.
+    pub fn get_index(&self, neighbourhood : &[usize]) -> usize {
+        if self.totalistic {
+            return neighbourhood.iter().sum();
+        }
+
+        return neighbourhood.iter().fold(0, |index, cell| index * self.states + cell);
+    }
+
+    /// Returns the next state of the cell in the middle of `neighbourhood`.
+    pub fn lookup(&self, neighbourhood : &[usize]) -> usize {
+        return self.table[self.get_index(neighbourhood)];
+    }
+}
+
+impl fmt::Display for ECARule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.get_elementary().is_some() {
+            return write!(f, "{}", self.code);
+        }
+
+        return write!(f, "{}{} (k={}, r={})", if self.totalistic { "code " } else { "" },
+            self.code, self.states, self.radius);
+    }
+}
+
+// returns the `length` least significant base `base` digits of the decimal number `code`,
+// least significant first; the number must fit in `length` digits.
+fn to_base(code : &str, base : usize, length : usize) -> Result<Vec<usize>, Error> {
+    let code : &str = code.trim();
+
+    // decimal digits, most significant first
+    let mut decimal : Vec<usize> = code.chars()
+        .map(|digit| digit.to_digit(10).map(|digit| digit as usize))
+        .collect::<Option<Vec<usize>>>()
+        .filter(|decimal| !decimal.is_empty())
+        .ok_or_else(|| Error::Parse(format!("invalid rule number {}", code)))?;
+
+    let mut digits : Vec<usize> = Vec::with_capacity(length);
+
+    // long division by base, until nothing is left
+    while decimal.iter().any(|digit| *digit != 0) {
+        if digits.len() == length {
+            return Err(Error::Parse(format!("rule number {} has more than {} digits in base {}", code, length, base)));
+        }
+
+        let mut remainder : usize = 0;
+        for digit in decimal.iter_mut() {
+            let value : usize = remainder * 10 + *digit;
+            *digit = value / base;
+            remainder = value % base;
+        }
+
+        digits.push(remainder);
+    }
+
+    digits.resize(length, 0);
+
+    return Ok(digits);
+}
+
+// inverse of to_base : returns the decimal string of the base `base` digits.
+fn from_base(digits : &[usize], base : usize) -> String {
+    // decimal digits, least significant first
+    let mut decimal : Vec<usize> = vec![0];
+
+    for digit in digits.iter().rev() {
+        let mut carry : usize = *digit;
+        for decimal_digit in decimal.iter_mut() {
+            let value : usize = *decimal_digit * base + carry;
+            *decimal_digit = value % 10;
+            carry = value / 10;
+        }
+        while carry > 0 {
+            decimal.push(carry % 10);
+            carry /= 10;
+        }
+    }
+
+    return decimal.iter().rev().map(|digit| digit.to_string()).collect();
+}
@@ -0,0 +1,266 @@
+// Streaming one dimensional automata, for runs too long to keep in memory.
+//
+// An `ECAStream` keeps the last three generations in an `ECA` of height 3 (second order rules
+// need the generation before the previous one) and hands every generation to one or more
+// `RowSink`s as soon as it is computed : a png encoder, a csv writer or a closure.
+
+use crate::boundary::BoundaryType;
+use crate::eca::{ECA, UpdateMode};
+use crate::eca_rule::ECARule;
+use crate::error::Error;
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// Receives the generations of a stream, one row at a time.
+pub trait RowSink {
+    fn write_row(&mut self, generation : usize, row : &[usize]) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Called once, after the last row.
+    fn finish(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        return Ok(());
+    }
+}
+
+impl<F> RowSink for F where F : FnMut(usize, &[usize]) -> Result<(), Box<dyn std::error::Error>> {
+    fn write_row(&mut self, generation : usize, row : &[usize]) -> Result<(), Box<dyn std::error::Error>> {
+        return self(generation, row);
+    }
+}
+
+/// Writes the rows as a greyscale png, one row per generation; 0 is white and the last state
+/// is black, as in `ECA::get_flattened_universe`.
+pub struct PngSink {
+    writer : Option<png::StreamWriter<'static, BufWriter<File>>>,
+    states : usize,
+    buffer : Vec<u8>
+}
+
+impl PngSink {
+
+    /// Creates the png at `path`; exactly `height` rows of `width` cells must be written.
+    pub fn create(path : &str, width : usize, height : usize, states : usize) -> Result<PngSink, Box<dyn std::error::Error>> {
+        let file = BufWriter::new(File::create(path)?);
+
+        let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        return Ok(PngSink {
+            writer : Some(encoder.write_header()?.into_stream_writer()?),
+            states : states.max(2),
+            buffer : Vec::with_capacity(width)
+        });
+    }
+}
+
+impl RowSink for PngSink {
+    fn write_row(&mut self, _generation : usize, row : &[usize]) -> Result<(), Box<dyn std::error::Error>> {
+        let states : usize = self.states;
+
+        self.buffer.clear();
+        self.buffer.extend(row.iter().map(|cell| (255 - cell.min(&(states - 1)) * 255 / (states - 1)) as u8));
+
+        if let Some(writer) = self.writer.as_mut() {
+            writer.write_all(&self.buffer)?;
+        }
+
+        return Ok(());
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some(writer) = self.writer.take() {
+            writer.finish()?;
+        }
+
+        return Ok(());
+    }
+}
+
+/// Writes the rows as csv lines, in the format of `Grid::write_csv`.
+pub struct CsvSink<W : Write> {
+    writer : W
+}
+
+impl<W : Write> CsvSink<W> {
+    pub fn new(writer : W) -> CsvSink<W> {
+        return CsvSink { writer : writer };
+    }
+}
+
+impl CsvSink<BufWriter<File>> {
+    pub fn create(path : &str) -> Result<CsvSink<BufWriter<File>>, std::io::Error> {
+        return Ok(CsvSink::new(BufWriter::new(File::create(path)?)));
+    }
+}
+
+impl<W : Write> RowSink for CsvSink<W> {
+    fn write_row(&mut self, _generation : usize, row : &[usize]) -> Result<(), Box<dyn std::error::Error>> {
+        let line : Vec<String> = row.iter().map(|cell| cell.to_string()).collect();
+        writeln!(self.writer, "{}", line.join(","))?;
+
+        return Ok(());
+    }
+
+    fn finish(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        self.writer.flush()?;
+
+        return Ok(());
+    }
+}
+
+/// Running statistics of the rows of a stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamStatistics {
+    pub width : usize,
+    pub rows : usize, // rows written, the first generation included
+    pub population : u64, // nonzero cells over every row
+    pub final_population : usize,
+    pub min_population : usize,
+    pub max_population : usize,
+}
+
+impl StreamStatistics {
+
+    fn new(width : usize) -> StreamStatistics {
+        return StreamStatistics {
+            width : width,
+            rows : 0,
+            population : 0,
+            final_population : 0,
+            min_population : usize::MAX,
+            max_population : 0,
+        };
+    }
+
+    fn add_row(&mut self, row : &[usize]) {
+        let population : usize = row.iter().filter(|cell| **cell != 0).count();
+
+        self.rows += 1;
+        self.population += population as u64;
+        self.final_population = population;
+        self.min_population = self.min_population.min(population);
+        self.max_population = self.max_population.max(population);
+    }
+
+    /// Returns the fraction of nonzero cells over every row.
+    pub fn get_mean_density(&self) -> f64 {
+        return self.population as f64 / (self.rows * self.width) as f64;
+    }
+
+    /// Returns the fraction of nonzero cells of the last row.
+    pub fn get_final_density(&self) -> f64 {
+        return self.final_population as f64 / self.width as f64;
+    }
+}
+
+/// A one dimensional automaton that only keeps its last generations.
+pub struct ECAStream {
+    automata : ECA, // height 3 : the current row and the two before it
+    generation : usize
+}
+
+impl ECAStream {
+
+    /// Returns a new stream whose first generation is `initial_configuration`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, ECARule};
+    /// use gol_rs::eca_stream::ECAStream;
+    ///
+    /// let mut initial_configuration = vec![0; 64];
+    /// initial_configuration[63] = 1;
+    /// let mut stream = ECAStream::new(ECARule::elementary(110), initial_configuration, BoundaryType::Null).unwrap();
+    ///
+    /// let mut rows : Vec<Vec<usize>> = Vec::new();
+    /// let mut collect = |_generation : usize, row : &[usize]| {
+    ///     rows.push(row.to_vec());
+    ///     return Ok(());
+    /// };
+    /// let statistics = stream.run(10, &mut [&mut collect]).unwrap();
+    ///
+    /// assert_eq!(statistics.rows, 11);
+    /// assert_eq!(stream.get_generation(), 10);
+    /// assert_eq!(rows[1][61..], [0, 1, 1]);
+    /// ```
+    pub fn new(rule : ECARule,
+        initial_configuration : Vec<usize>,
+        boundary_type : BoundaryType) -> Result<ECAStream, Error> {
+
+        let width : usize = initial_configuration.len();
+
+        return Ok(ECAStream {
+            automata : ECA::with_rule(rule, width, 3, initial_configuration, boundary_type)?,
+            generation : 0
+        });
+    }
+
+    /// Sets how generations are computed (see `ECA::set_update_mode`), and goes back to the
+    /// first generation.
+    pub fn set_update_mode(&mut self, update_mode : UpdateMode, seed : u64) -> Result<(), Error> {
+        self.automata.set_update_mode(update_mode, seed)?;
+        self.generation = 0;
+
+        return Ok(());
+    }
+
+    pub fn get_rule(&self) -> &ECARule {
+        return self.automata.get_rule();
+    }
+
+    pub fn get_width(&self) -> usize {
+        return self.automata.get_universe().get_width();
+    }
+
+    pub fn get_generation(&self) -> usize {
+        return self.generation;
+    }
+
+    /// Returns the current generation.
+    pub fn get_row(&self) -> &[usize] {
+        let width : usize = self.get_width();
+        let row_index : usize = self.generation.min(2);
+
+        return &self.automata.get_universe().get_grid()[row_index * width..(row_index + 1) * width];
+    }
+
+    /// Computes the next generation.
+    pub fn step(&mut self) {
+        if self.generation >= 2 {
+            self.automata.scroll();
+        }
+
+        self.automata.increase_generation((self.generation + 1).min(2));
+        self.generation += 1;
+    }
+
+    /// Writes the current generation and the next `generations` to every sink, then finishes
+    /// them, and returns the statistics of the rows written.
+    pub fn run(&mut self,
+        generations : usize,
+        sinks : &mut [&mut dyn RowSink]) -> Result<StreamStatistics, Box<dyn std::error::Error>> {
+
+        let mut statistics = StreamStatistics::new(self.get_width());
+
+        for step in 0..=generations {
+            if step > 0 {
+                self.step();
+            }
+
+            let row : &[usize] = self.get_row();
+            statistics.add_row(row);
+
+            for sink in sinks.iter_mut() {
+                sink.write_row(self.generation, row)?;
+            }
+        }
+
+        for sink in sinks.iter_mut() {
+            sink.finish()?;
+        }
+
+        return Ok(statistics);
+    }
+}
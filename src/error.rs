@@ -0,0 +1,59 @@
+use std::fmt;
+
+/// Errors returned by the constructors and fallible methods of this crate.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// A grid must have at least one row and one column.
+    InvalidDimensions { width : usize, height : usize },
+    /// A universe must have at least one page.
+    InvalidDepth(usize),
+    /// A page index is not below the depth of the universe.
+    InvalidPage { page_index : usize, depth : usize },
+    /// The given configuration does not match the size of the universe.
+    ConfigurationMismatch { expected : usize, found : usize },
+    /// A density (or probability) must lie in [0, 1].
+    InvalidDensity(f64),
+    /// The symmetry requires a square grid.
+    InvalidSymmetry { width : usize, height : usize },
+    /// A cell state is not below the number of states of the rule.
+    InvalidState { state : usize, states : usize },
+    /// The backend can't run this rule, boundary or configuration.
+    UnsupportedBackend(String),
+    /// A cell lies past an edge of the grid that neither wraps nor drops it.
+    OutOfBounds { row_index : isize, column_index : isize },
+    /// Only second order automata can run backwards.
+    NotReversible,
+    /// A string (rulestring, file contents, argument) could not be parsed.
+    Parse(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            Error::InvalidDimensions { width, height } =>
+                write!(f, "invalid dimensions : width {} x height {}", width, height),
+            Error::InvalidDepth(depth) =>
+                write!(f, "invalid depth : {}", depth),
+            Error::InvalidPage { page_index, depth } =>
+                write!(f, "invalid page : {}, expected a value below the depth {}", page_index, depth),
+            Error::ConfigurationMismatch { expected, found } =>
+                write!(f, "configuration mismatch : expected {} cells, found {}", expected, found),
+            Error::InvalidDensity(density) =>
+                write!(f, "invalid density : {}, expected a value in [0, 1]", density),
+            Error::InvalidSymmetry { width, height } =>
+                write!(f, "invalid symmetry : width {} x height {} is not square", width, height),
+            Error::InvalidState { state, states } =>
+                write!(f, "invalid state : {}, expected a value below {}", state, states),
+            Error::UnsupportedBackend(message) =>
+                write!(f, "unsupported backend : {}", message),
+            Error::OutOfBounds { row_index, column_index } =>
+                write!(f, "out of bounds : cell ({}, {}) lies past an edge of the grid", row_index, column_index),
+            Error::NotReversible =>
+                write!(f, "not reversible : only second order automata can run backwards"),
+            Error::Parse(message) =>
+                write!(f, "parse error : {}", message),
+        };
+    }
+}
+
+impl std::error::Error for Error {}
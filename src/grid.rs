@@ -1,22 +1,40 @@
 extern crate petgraph;
 
-use crate::compass_direction::{CompassDirection, Compass};
+use crate::error::Error;
 
 use petgraph::unionfind::UnionFind;
 
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
 use std::collections::HashMap;
 
 use std::fmt;
-use std::fmt::Write as FmtWrite;
 
+/// Which neighbours are considered adjacent when labelling connected components.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Connectivity {
     FourConnected,
     SixConnected, // for hexagonal grid
     EightConnected
 }
 
+/// Symmetry of a random soup, following apgsearch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Symmetry {
+    Asymmetric,
+    /// invariant under rotation by 180 degrees.
+    C2,
+    /// invariant under rotation by 90 degrees; the grid must be square.
+    C4,
+    /// invariant under reflection in both axes (apgsearch D4_+1).
+    D4,
+}
+
 // todo : move the boundary object in here?
 
+/// A `width` x `height` array of cell values, stored row by row.
+#[derive(Clone)]
 pub struct Grid { // todo : rename to grid2d
     width : usize,
     height : usize,
@@ -28,13 +46,175 @@ pub struct Grid { // todo : rename to grid2d
 
 impl Grid {
 
-    pub fn new(width : usize, height : usize) -> Grid {
-        // returns new empty grid
+    /// Returns a new empty grid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::Grid;
+    ///
+    /// let grid = Grid::new(3, 2).unwrap();
+    /// assert_eq!(grid.get_value(1, 2), 0);
+    /// assert!(Grid::new(0, 2).is_err());
+    /// ```
+    pub fn new(width : usize, height : usize) -> Result<Grid, Error> {
+        Grid::check_dimensions(width, height)?;
+
+        return Ok(Grid::empty(width, height));
+    }
+
+    /// Returns a grid holding the given cells, listed row by row.
+    pub fn from_vec(width : usize, height : usize, cells : Vec<usize>) -> Result<Grid, Error> {
+        Grid::check_dimensions(width, height)?;
+
+        if cells.len() != width * height {
+            return Err(Error::ConfigurationMismatch { expected : width * height, found : cells.len() });
+        }
+
+        let mut grid : Grid = Grid::empty(width, height);
+        grid.grid = cells;
+
+        return Ok(grid);
+    }
+
+    /// Returns an empty grid with a single 1 in the middle.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::Grid;
+    ///
+    /// let grid = Grid::single_cell(3, 3).unwrap();
+    /// assert_eq!(grid.get_grid(), &vec![0, 0, 0, 0, 1, 0, 0, 0, 0]);
+    /// ```
+    pub fn single_cell(width : usize, height : usize) -> Result<Grid, Error> {
+        let mut grid : Grid = Grid::new(width, height)?;
+        grid.set_value(height / 2, width / 2, 1);
+
+        return Ok(grid);
+    }
+
+    /// Returns a random grid where each cell is 1 with probability `density`.
+    ///
+    /// The same `seed` always gives the same grid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::Grid;
+    ///
+    /// let soup = Grid::random(16, 16, 0.5, 42).unwrap();
+    /// assert_eq!(soup.get_grid(), Grid::random(16, 16, 0.5, 42).unwrap().get_grid());
+    /// ```
+    pub fn random(width : usize, height : usize, density : f64, seed : u64) -> Result<Grid, Error> {
+        return Grid::random_symmetric(width, height, density, Symmetry::Asymmetric, seed);
+    }
+
+    /// Returns a random grid with the given symmetry; see `random`.
+    pub fn random_symmetric(width : usize,
+        height : usize,
+        density : f64,
+        symmetry : Symmetry,
+        seed : u64) -> Result<Grid, Error> {
+
+        Grid::check_dimensions(width, height)?;
+        Grid::check_density(density)?;
+
+        if symmetry == Symmetry::C4 && width != height {
+            return Err(Error::InvalidSymmetry { width : width, height : height });
+        }
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+        let mut grid : Grid = Grid::empty(width, height);
+
+        for row_index in 0..height {
+            for column_index in 0..width {
+                // images of this cell under the symmetry; the first visited one is random,
+                // the others are copies.
+                let (h, w) : (usize, usize) = (height - 1, width - 1);
+                let images : Vec<(usize, usize)> = match symmetry {
+                    Symmetry::Asymmetric => vec![],
+                    Symmetry::C2 => vec![(h - row_index, w - column_index)],
+                    Symmetry::C4 => vec![
+                        (column_index, w - row_index),
+                        (h - row_index, w - column_index),
+                        (w - column_index, row_index)],
+                    Symmetry::D4 => vec![
+                        (h - row_index, column_index),
+                        (row_index, w - column_index),
+                        (h - row_index, w - column_index)],
+                };
+
+                let representative : (usize, usize) = images.iter()
+                    .fold((row_index, column_index), |minimum, image| std::cmp::min(minimum, *image));
+
+                let cell : usize = if representative == (row_index, column_index) {
+                    rng.gen_bool(density) as usize
+                } else {
+                    grid.get_value(representative.0, representative.1)
+                };
+
+                grid.set_value(row_index, column_index, cell);
+            }
+        }
+
+        return Ok(grid);
+    }
+
+    /// Returns an empty grid with a random `patch_width` x `patch_height` patch in the middle;
+    /// see `random`.
+    pub fn random_patch(width : usize,
+        height : usize,
+        patch_width : usize,
+        patch_height : usize,
+        density : f64,
+        seed : u64) -> Result<Grid, Error> {
+
+        Grid::check_dimensions(width, height)?;
+
+        if patch_width > width || patch_height > height {
+            return Err(Error::InvalidDimensions { width : patch_width, height : patch_height });
+        }
+
+        let patch : Grid = Grid::random(patch_width, patch_height, density, seed)?;
+        let mut grid : Grid = Grid::empty(width, height);
+
+        let row_offset : usize = (height - patch_height) / 2;
+        let column_offset : usize = (width - patch_width) / 2;
+
+        for row_index in 0..patch_height {
+            for column_index in 0..patch_width {
+                grid.set_value(row_offset + row_index, column_offset + column_index,
+                    patch.get_value(row_index, column_index));
+            }
+        }
+
+        return Ok(grid);
+    }
+
+    pub(crate) fn check_density(density : f64) -> Result<(), Error> {
+        if !(0.0..=1.0).contains(&density) {
+            return Err(Error::InvalidDensity(density));
+        }
+
+        return Ok(());
+    }
+
+    fn check_dimensions(width : usize, height : usize) -> Result<(), Error> {
+        if width == 0 || height == 0 || width.checked_mul(height).is_none() {
+            return Err(Error::InvalidDimensions { width : width, height : height });
+        }
+
+        return Ok(());
+    }
+
+    // returns new empty grid; dimensions are assumed to be valid.
+    pub(crate) fn empty(width : usize, height : usize) -> Grid {
 
         // todo : add to new constructor
         let connectivity : Connectivity = Connectivity::FourConnected;
 
-        let grid : Vec<usize> = vec![0; (width * height) as usize];
+        let grid : Vec<usize> = vec![0; width * height];
 
         return Grid {
                 width : width,
@@ -44,6 +224,23 @@ impl Grid {
         };
     }
 
+    pub fn get_connectivity(&self) -> Connectivity {
+        return self.connectivity;
+    }
+
+    pub fn set_connectivity(&mut self, connectivity : Connectivity) {
+        self.connectivity = connectivity;
+    }
+
+    pub fn get_width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn get_height(&self) -> usize {
+        return self.height;
+    }
+
+    /// Returns the cells, row by row.
     pub fn get_grid(&self) -> &Vec<usize> {
         return &self.grid;
     }
@@ -56,16 +253,33 @@ impl Grid {
         self.grid[row_index * self.width + column_index] = value;
     }
 
+    /// Sets every cell to 0, keeping the allocation.
+    pub fn clear(&mut self) {
+        self.grid.iter_mut().for_each(|cell| *cell = 0);
+    }
+
+    /// Copies the cells of `other`, which must have the same dimensions, keeping the allocation.
+    pub fn copy_from(&mut self, other : &Grid) {
+        assert!(self.width == other.width && self.height == other.height, "grids of different dimensions");
+        self.grid.copy_from_slice(&other.grid);
+    }
+
+    // the cells, row by row, for writing in place.
+    pub(crate) fn get_cells_mut(&mut self) -> &mut [usize] {
+        return &mut self.grid;
+    }
+
+    /// Returns one label per cell; cells with value 1 that are connected share a label,
+    /// every other cell is labelled 0.
     pub fn get_connected_components(&self, make_consecutive_labels : bool) -> Vec<usize> {
         // todo : rewrite this to calculate connected components based on the boundary
 
         // assumes universe is flattened based on width, height
-        let mut uf = UnionFind::<usize>::new((self.width * self.height) as usize);
+        // the extra element is the background; it can't be merged with a foreground cell.
+        let background : usize = self.width * self.height;
+        let mut uf = UnionFind::<usize>::new(background + 1);
 
-        let eight_connected : bool = match self.connectivity {
-            Connectivity::EightConnected => true,
-            _ => false
-        };
+        let eight_connected : bool = matches!(self.connectivity, Connectivity::EightConnected);
 
         for row_index in 0..self.height {
             for cell_index in 0..self.width {
@@ -153,12 +367,18 @@ impl Grid {
                 } else {
                     // background
                     // println!{"background"};
-                    uf.union(0, row_index * self.width + cell_index);
+                    uf.union(background, row_index * self.width + cell_index);
                 }
             }
         }
         // let mut universe_str = String::new();
         let mut universe_labelled : Vec<usize> = uf.into_labeling();
+        let background_label : usize = universe_labelled.pop().unwrap();
+
+        // background is labelled 0; shift foreground labels so none of them is 0.
+        for cell in universe_labelled.iter_mut() {
+            *cell = if *cell == background_label { 0 } else { *cell + 1 };
+        }
         //
         // for (index, cell) in universe_labelled.iter().enumerate() {
         //     universe_str.push_str(&cell.to_string());
@@ -182,7 +402,7 @@ impl Grid {
                 if *cell != 0 {
                     if !consecutive_labels.contains_key(cell) {
                         consecutive_labels.insert(*cell, label_counter);
-                        label_counter = label_counter + 1;
+                        label_counter += 1;
                     }
                 } else {
                     consecutive_labels.insert(*cell, 0);
@@ -191,7 +411,7 @@ impl Grid {
 
             // println!("number of components : {}", consecutive_labels.len() - 1);
 
-            for (index, cell) in universe_labelled.iter_mut().enumerate() {
+            for cell in universe_labelled.iter_mut() {
                 *cell = consecutive_labels[cell];
             }
         }
@@ -199,6 +419,7 @@ impl Grid {
         return universe_labelled;
     }
 
+    /// Returns a grid of consecutive component labels, see `get_connected_components`.
     pub fn get_connected_components_grid(&self) -> Grid{
         let make_consecutive_labels : bool = true;
 
@@ -206,16 +427,12 @@ impl Grid {
             width : self.width,
             height : self.height,
             grid : self.get_connected_components(make_consecutive_labels),
-            connectivity : match self.connectivity {
-                Connectivity::FourConnected => Connectivity::FourConnected,
-                Connectivity::SixConnected => Connectivity::SixConnected,
-                Connectivity::EightConnected => Connectivity::EightConnected,
-                _ =>Connectivity::FourConnected
-            }
+            connectivity : self.connectivity
             // compass : self.compass
         };
     }
 
+    /// Replaces every cell with its consecutive component label.
     pub fn label_connected_components(&mut self) {
         let make_consecutive_labels : bool = true;
 
@@ -233,6 +450,134 @@ impl Grid {
         return max
     }
 
+    /// Returns the grid rotated a quarter turn clockwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::Grid;
+    ///
+    /// let grid = Grid::from_vec(3, 2, vec![
+    ///     1, 2, 3,
+    ///     4, 5, 6]).unwrap();
+    ///
+    /// assert_eq!(grid.rotate_clockwise().get_grid(), &vec![4, 1, 5, 2, 6, 3]);
+    /// assert_eq!(grid.reflect().get_grid(), &vec![3, 2, 1, 6, 5, 4]);
+    /// ```
+    pub fn rotate_clockwise(&self) -> Grid {
+        let mut rotated : Grid = Grid::empty(self.height, self.width);
+        rotated.connectivity = self.connectivity;
+
+        for row_index in 0..rotated.height {
+            for column_index in 0..rotated.width {
+                rotated.set_value(row_index, column_index, self.get_value(self.height - 1 - column_index, row_index));
+            }
+        }
+
+        return rotated;
+    }
+
+    /// Returns the grid mirrored left to right.
+    pub fn reflect(&self) -> Grid {
+        let mut reflected : Grid = self.clone();
+
+        for row in reflected.grid.chunks_mut(self.width) {
+            row.reverse();
+        }
+
+        return reflected;
+    }
+
+    /// Returns the `width` x `height` grid with its top left cell at (`row_index`,
+    /// `column_index`); it must lie inside the grid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::Grid;
+    ///
+    /// let grid = Grid::from_vec(3, 2, vec![
+    ///     1, 2, 3,
+    ///     4, 5, 6]).unwrap();
+    ///
+    /// assert_eq!(grid.get_subgrid(0, 1, 2, 2).unwrap().get_grid(), &vec![2, 3, 5, 6]);
+    /// assert!(grid.get_subgrid(1, 0, 3, 2).is_err());
+    /// ```
+    pub fn get_subgrid(&self, row_index : usize, column_index : usize, width : usize, height : usize) -> Result<Grid, Error> {
+        if row_index + height > self.height || column_index + width > self.width {
+            return Err(Error::OutOfBounds {
+                row_index : (row_index + height) as isize - 1,
+                column_index : (column_index + width) as isize - 1
+            });
+        }
+
+        let mut subgrid : Grid = Grid::new(width, height)?;
+        subgrid.connectivity = self.connectivity;
+
+        for (row, cells) in subgrid.grid.chunks_mut(width).enumerate() {
+            let start : usize = (row_index + row) * self.width + column_index;
+            cells.copy_from_slice(&self.grid[start..start + width]);
+        }
+
+        return Ok(subgrid);
+    }
+
+    /// Returns the number of nonzero cells.
+    pub fn get_population(&self) -> usize {
+        return self.grid.iter().filter(|cell| **cell != 0).count();
+    }
+
+    /// Returns the fraction of nonzero cells.
+    pub fn get_density(&self) -> f64 {
+        return self.get_population() as f64 / self.grid.len() as f64;
+    }
+
+    /// Returns the Betti numbers (b0, b1) of the cells with value 1 : the number of connected
+    /// components and the number of holes.
+    ///
+    /// Holes are the components of the complement that don't reach the edge of the grid; they
+    /// are labelled with the dual connectivity (four for eight, eight for four).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::Grid;
+    ///
+    /// // a ring and a single cell
+    /// let grid = Grid::from_vec(5, 3, vec![
+    ///     1, 1, 1, 0, 0,
+    ///     1, 0, 1, 0, 1,
+    ///     1, 1, 1, 0, 0]).unwrap();
+    /// assert_eq!(grid.get_betti_numbers(), (2, 1));
+    /// ```
+    pub fn get_betti_numbers(&self) -> (usize, usize) {
+        let b0 : usize = self.get_connected_components_grid().get_number_of_components();
+
+        // complement, padded with one row / column of background on every side so that
+        // everything outside is one component.
+        let mut complement : Grid = Grid::empty(self.width + 2, self.height + 2);
+        complement.connectivity = match self.connectivity {
+            Connectivity::FourConnected => Connectivity::EightConnected,
+            Connectivity::SixConnected => Connectivity::SixConnected,
+            Connectivity::EightConnected => Connectivity::FourConnected
+        };
+
+        for row_index in 0..complement.height {
+            for column_index in 0..complement.width {
+                let inside : bool = row_index > 0 && row_index <= self.height &&
+                    column_index > 0 && column_index <= self.width;
+
+                if !inside || self.get_value(row_index - 1, column_index - 1) != 1 {
+                    complement.set_value(row_index, column_index, 1);
+                }
+            }
+        }
+
+        let b1 : usize = complement.get_connected_components_grid().get_number_of_components() - 1;
+
+        return (b0, b1);
+    }
+
     pub fn get_filter_l1(&mut self) {
         let max_label = self.width + self.height;
 
@@ -270,6 +615,7 @@ impl Grid {
 
     }
 
+    /// Writes the grid to `./width{}|height{}.csv`.
     pub fn save_to_csv(&self) -> Result<(), Box<dyn std::error::Error>>{
         // This assumes you've run generate.
 
@@ -278,14 +624,62 @@ impl Grid {
 
         let csv_filename = format!("./width{}|height{}.csv", self.width, self.height);
 
+        return self.write_csv(&csv_filename);
+    }
+
+    /// Writes the grid to `path`, in the format of `get_grid_str`.
+    pub fn write_csv(&self, path : &str) -> Result<(), Box<dyn std::error::Error>> {
         let grid_as_string : String = self.get_grid_str();
 
         // println!("{}", universe_as_string);
-        std::fs::write(csv_filename, grid_as_string).unwrap();
+        std::fs::write(path, grid_as_string)?;
 
         return Ok(());
     }
 
+    /// Reads a grid written by `write_csv`.
+    pub fn read_csv(path : &str) -> Result<Grid, Box<dyn std::error::Error>> {
+        let grid_as_string : String = std::fs::read_to_string(path)?;
+
+        return Ok(Grid::from_csv_str(&grid_as_string)?);
+    }
+
+    /// Parses comma separated rows, as returned by `get_grid_str`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::Grid;
+    ///
+    /// let grid = Grid::from_csv_str("0,1\n1,0\n").unwrap();
+    /// assert_eq!(grid.get_value(1, 0), 1);
+    /// assert_eq!(grid.get_grid_str(), "0,1\n1,0\n");
+    /// ```
+    pub fn from_csv_str(grid_as_string : &str) -> Result<Grid, Error> {
+        let mut cells : Vec<usize> = Vec::new();
+        let mut width : usize = 0;
+        let mut height : usize = 0;
+
+        for line in grid_as_string.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) {
+            let row : Vec<usize> = line.split(',')
+                .map(|cell| cell.trim().parse::<usize>())
+                .collect::<Result<Vec<usize>, _>>()
+                .map_err(|e| Error::Parse(format!("row {} : {}", height, e)))?;
+
+            if height == 0 {
+                width = row.len();
+            } else if row.len() != width {
+                return Err(Error::Parse(format!("row {} has {} cells, expected {}", height, row.len(), width)));
+            }
+
+            cells.extend(row);
+            height += 1;
+        }
+
+        return Grid::from_vec(width, height, cells);
+    }
+
+    /// Returns the grid as comma separated rows.
     pub fn get_grid_str(&self) -> String {
         let mut grid_as_string : String = String::new();
 
@@ -298,7 +692,7 @@ impl Grid {
                 // println!("cell : {}", 0);
             }
 
-            if index % self.width as usize == (self.width - 1) as usize {
+            if index % self.width == self.width - 1 {
                 grid_as_string.push('\n');
             } else {
                 grid_as_string.push(',');
@@ -307,8 +701,6 @@ impl Grid {
 
         return grid_as_string;
     }
-
-    // todo : add rotate grid method
 }
 
 impl fmt::Display for Grid {
@@ -321,7 +713,3 @@ impl fmt::Display for Grid {
         return write!(f, "{}", self.get_grid_str());
     }
 }
-
-// todo write function to return grid with single 1 in the middle.
-
-// todo write function to return grid of a specific game of life object, based on the lexicon.
@@ -0,0 +1,577 @@
+// HashLife : unbounded evolution of 2 state, Moore, outer totalistic rules.
+//
+// The plane is a quadtree whose nodes are shared : a node of level L is a 2^L x 2^L square made
+// of four nodes of level L - 1, and equal squares are the same node (hash consing). The result
+// of a node of level L is its centre square, of level L - 1, 2^j generations later
+// (j <= L - 2) : it only depends on the node, so it is memoised, and repeated or empty regions
+// are computed once. Stepping 2^j generations is then a single result of the root.
+//
+// Cells are addressed by (row, column), with row 0 column 0 at the centre of the root; rows
+// grow southwards, as in `Grid`. Rules with B0 (a dead cell with no live neighbour is born)
+// are not supported : the empty plane would not stay empty.
+
+use crate::error::Error;
+use crate::grid::Grid;
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+// the two cells, level 0.
+const DEAD : usize = 0;
+const ALIVE : usize = 1;
+
+// levels are kept below 62 so that the coordinates fit in an i64.
+const MAX_LEVEL : usize = 61;
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    level : usize,
+    children : [usize; 4], // nw, ne, sw, se; unused for cells
+    population : u64
+}
+
+/// A memoised quadtree universe, unbounded in every direction.
+pub struct HashLife {
+    code : usize, // outer totalistic code, bit 2n + a is f(a, n)
+    nodes : Vec<Node>,
+    index : HashMap<[usize; 4], usize>, // children -> node
+    empty : Vec<usize>, // the empty node of each level
+    results : HashMap<(usize, usize), usize>, // (node, j) -> centre 2^j generations later
+    root : usize,
+    generation : u64,
+    max_nodes : usize
+}
+
+impl HashLife {
+
+    /// Returns an empty universe running the outer totalistic `code` (see `rule`) on the Moore
+    /// neighbourhood.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::hashlife::HashLife;
+    /// use gol_rs::rule::OuterTotalistic;
+    ///
+    /// let life = HashLife::new(OuterTotalistic::from_rulestring("B3/S23").unwrap().code).unwrap();
+    /// assert_eq!(life.get_population(), 0);
+    ///
+    /// // B0 : the empty plane turns full
+    /// assert!(HashLife::new(OuterTotalistic::from_rulestring("B03/S23").unwrap().code).is_err());
+    /// ```
+    pub fn new(code : usize) -> Result<HashLife, Error> {
+        if code >> 18 != 0 {
+            return Err(Error::Parse(format!("invalid outer totalistic code {}", code)));
+        }
+
+        if code & 1 == 1 {
+            return Err(Error::UnsupportedBackend(String::from("B0 rules can't run on hashlife")));
+        }
+
+        let cell = |population : u64| Node { level : 0, children : [0; 4], population : population };
+
+        let mut hashlife = HashLife {
+            code : code,
+            nodes : vec![cell(0), cell(1)],
+            index : HashMap::new(),
+            empty : vec![DEAD],
+            results : HashMap::new(),
+            root : DEAD,
+            generation : 0,
+            max_nodes : 1 << 22
+        };
+        hashlife.root = hashlife.get_empty(3);
+
+        return Ok(hashlife);
+    }
+
+    pub fn get_code(&self) -> usize {
+        return self.code;
+    }
+
+    pub fn get_generation(&self) -> u64 {
+        return self.generation;
+    }
+
+    /// Returns the number of live cells.
+    pub fn get_population(&self) -> u64 {
+        return self.nodes[self.root].population;
+    }
+
+    /// Returns the level of the root : the universe currently spans 2^level x 2^level cells.
+    pub fn get_level(&self) -> usize {
+        return self.nodes[self.root].level;
+    }
+
+    /// Returns the number of nodes in memory.
+    pub fn get_node_count(&self) -> usize {
+        return self.nodes.len();
+    }
+
+    /// Sets the number of nodes above which memory is collected after a step (default 2^22).
+    pub fn set_max_nodes(&mut self, max_nodes : usize) {
+        self.max_nodes = max_nodes;
+    }
+
+    /// Returns the value of a cell; every cell is 0 or 1.
+    pub fn get_value(&self, row_index : i64, column_index : i64) -> usize {
+        let half : i64 = 1 << (self.get_level() - 1);
+        if row_index < -half || row_index >= half || column_index < -half || column_index >= half {
+            return 0;
+        }
+
+        let mut node : usize = self.root;
+        let (mut row_index, mut column_index) : (i64, i64) = (row_index + half, column_index + half);
+
+        while self.nodes[node].level > 0 {
+            let half : i64 = 1 << (self.nodes[node].level - 1);
+            let quadrant : usize = 2 * (row_index >= half) as usize + (column_index >= half) as usize;
+
+            node = self.nodes[node].children[quadrant];
+            row_index %= half;
+            column_index %= half;
+        }
+
+        return node;
+    }
+
+    /// Sets a cell to 0, or to 1 for any other value; the universe grows to contain it.
+    ///
+    /// # Panics
+    ///
+    /// If a coordinate lies 2^60 or more from the origin.
+    pub fn set_value(&mut self, row_index : i64, column_index : i64, value : usize) {
+        while !self.contains(row_index, column_index) {
+            self.expand().expect("cell too far from the origin");
+        }
+
+        let half : i64 = 1 << (self.get_level() - 1);
+        let cell : usize = if value == 0 { DEAD } else { ALIVE };
+        self.root = self.set_cell(self.root, row_index + half, column_index + half, cell);
+    }
+
+    /// Writes the cells of `grid` with its top left cell at (`row_index`, `column_index`);
+    /// cells other than 0 are live.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::Grid;
+    /// use gol_rs::hashlife::HashLife;
+    ///
+    /// let glider = Grid::from_vec(3, 3, vec![
+    ///     0, 1, 0,
+    ///     0, 0, 1,
+    ///     1, 1, 1]).unwrap();
+    ///
+    /// let mut life = HashLife::new(224).unwrap();
+    /// life.import_grid(&glider, -1, -1);
+    /// life.step(4).unwrap(); // the glider moves one cell south east
+    ///
+    /// assert_eq!(life.get_population(), 5);
+    /// assert_eq!(life.export_grid(0, 0, 3, 3).unwrap().get_grid(), glider.get_grid());
+    /// ```
+    pub fn import_grid(&mut self, grid : &Grid, row_index : i64, column_index : i64) {
+        for grid_row_index in 0..grid.get_height() {
+            for grid_column_index in 0..grid.get_width() {
+                let value : usize = grid.get_value(grid_row_index, grid_column_index);
+                let (row, column) : (i64, i64) = (row_index + grid_row_index as i64, column_index + grid_column_index as i64);
+
+                if value != 0 || self.get_value(row, column) != 0 {
+                    self.set_value(row, column, value);
+                }
+            }
+        }
+    }
+
+    /// Returns the `width` x `height` region whose top left cell is (`row_index`,
+    /// `column_index`).
+    pub fn export_grid(&self, row_index : i64, column_index : i64, width : usize, height : usize) -> Result<Grid, Error> {
+        let mut grid : Grid = Grid::new(width, height)?;
+
+        let half : i64 = 1 << (self.get_level() - 1);
+        let region : [i64; 4] = [row_index, column_index, row_index + height as i64, column_index + width as i64];
+        self.write_region(self.root, -half, -half, &region, &mut grid);
+
+        return Ok(grid);
+    }
+
+    /// Returns the smallest rectangle holding every live cell, as (min row, min column,
+    /// max row, max column), or None for an empty universe.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::hashlife::HashLife;
+    ///
+    /// let mut life = HashLife::new(224).unwrap();
+    /// assert_eq!(life.get_bounding_box(), None);
+    ///
+    /// life.set_value(-20, 3, 1);
+    /// life.set_value(7, -5, 1);
+    /// assert_eq!(life.get_bounding_box(), Some((-20, -5, 7, 3)));
+    /// ```
+    pub fn get_bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        let half : i64 = 1 << (self.get_level() - 1);
+        let mut bounds = HashMap::<usize, Option<[i64; 4]>>::new();
+
+        return self.get_bounds(self.root, &mut bounds)
+            .map(|[min_row, min_column, max_row, max_column]| (min_row - half, min_column - half, max_row - half, max_column - half));
+    }
+
+    /// Advances the universe 2^`exponent` generations at once.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::Grid;
+    /// use gol_rs::hashlife::HashLife;
+    ///
+    /// // a glider travels one cell diagonally every 4 generations
+    /// let glider = Grid::from_vec(3, 3, vec![
+    ///     0, 1, 0,
+    ///     0, 0, 1,
+    ///     1, 1, 1]).unwrap();
+    ///
+    /// let mut life = HashLife::new(224).unwrap();
+    /// life.import_grid(&glider, 0, 0);
+    /// life.step_pow2(40).unwrap();
+    ///
+    /// assert_eq!(life.get_generation(), 1 << 40);
+    /// assert_eq!(life.get_population(), 5);
+    /// assert_eq!(life.get_bounding_box(), Some((1 << 38, 1 << 38, (1 << 38) + 2, (1 << 38) + 2)));
+    ///
+    /// // the root would outgrow the coordinates
+    /// assert!(life.step_pow2(60).is_err());
+    /// assert_eq!(life.get_generation(), 1 << 40);
+    /// ```
+    pub fn step_pow2(&mut self, exponent : usize) -> Result<(), Error> {
+        let generation : u64 = u32::try_from(exponent).ok()
+            .and_then(|exponent| 1u64.checked_shl(exponent))
+            .and_then(|generations| self.generation.checked_add(generations))
+            .ok_or_else(|| Error::UnsupportedBackend(format!("generation {} + 2^{} overflows", self.generation, exponent)))?;
+
+        // the live cells must stay within the centre of the root, which is all the result
+        // keeps : they are kept in the middle quarter, at least 2^exponent from its edge.
+        while self.get_level() < exponent.saturating_add(3) || !self.is_padded() {
+            self.expand()?;
+        }
+
+        self.root = self.get_result(self.root, exponent);
+        self.generation = generation;
+
+        if self.nodes.len() > self.max_nodes {
+            self.collect_garbage();
+        }
+
+        return Ok(());
+    }
+
+    /// Advances the universe `generations` generations, one power of two at a time. On error,
+    /// the universe is left at the last power of two reached, see `get_generation`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::hashlife::HashLife;
+    ///
+    /// let mut life = HashLife::new(224).unwrap();
+    /// life.set_value(0, 0, 1);
+    /// assert!(life.step(1 << 62).is_err());
+    /// ```
+    pub fn step(&mut self, generations : u64) -> Result<(), Error> {
+        for exponent in 0..64 {
+            if (generations >> exponent) & 1 == 1 {
+                self.step_pow2(exponent)?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    /// Drops every node unreachable from the root, and the memoised results.
+    pub fn collect_garbage(&mut self) {
+        let mut nodes : Vec<Node> = self.nodes[..2].to_vec();
+        let mut index = HashMap::<[usize; 4], usize>::new();
+        let mut copies = HashMap::<usize, usize>::new();
+        copies.insert(DEAD, DEAD);
+        copies.insert(ALIVE, ALIVE);
+
+        let root : usize = copy_node(&self.nodes, self.root, &mut nodes, &mut index, &mut copies);
+
+        self.nodes = nodes;
+        self.index = index;
+        self.results.clear();
+        self.empty.truncate(1);
+        self.root = root;
+    }
+
+    // whether the cell lies inside the root.
+    fn contains(&self, row_index : i64, column_index : i64) -> bool {
+        let half : i64 = 1 << (self.get_level() - 1);
+        return row_index >= -half && row_index < half && column_index >= -half && column_index < half;
+    }
+
+    // whether every live cell lies in the middle quarter of the root.
+    fn is_padded(&self) -> bool {
+        let root : Node = self.nodes[self.root];
+        let [nw, ne, sw, se] : [usize; 4] = root.children;
+        let [nw, ne, sw, se] : [Node; 4] = [self.nodes[nw], self.nodes[ne], self.nodes[sw], self.nodes[se]];
+
+        let middle : u64 = self.nodes[self.nodes[nw.children[3]].children[3]].population
+            + self.nodes[self.nodes[ne.children[2]].children[2]].population
+            + self.nodes[self.nodes[sw.children[1]].children[1]].population
+            + self.nodes[self.nodes[se.children[0]].children[0]].population;
+
+        return middle == root.population;
+    }
+
+    // returns the node with these children.
+    fn join(&mut self, children : [usize; 4]) -> usize {
+        if let Some(node) = self.index.get(&children) {
+            return *node;
+        }
+
+        let node = Node {
+            level : self.nodes[children[0]].level + 1,
+            children : children,
+            population : children.iter().map(|child| self.nodes[*child].population).sum()
+        };
+
+        self.nodes.push(node);
+        self.index.insert(children, self.nodes.len() - 1);
+
+        return self.nodes.len() - 1;
+    }
+
+    fn get_empty(&mut self, level : usize) -> usize {
+        while self.empty.len() <= level {
+            let empty : usize = *self.empty.last().unwrap();
+            let next : usize = self.join([empty; 4]);
+            self.empty.push(next);
+        }
+
+        return self.empty[level];
+    }
+
+    // doubles the root, keeping it in the middle.
+    fn expand(&mut self) -> Result<(), Error> {
+        let level : usize = self.get_level();
+        if level >= MAX_LEVEL {
+            return Err(Error::UnsupportedBackend(format!("hashlife universe larger than 2^{}", MAX_LEVEL)));
+        }
+
+        let empty : usize = self.get_empty(level - 1);
+        let [nw, ne, sw, se] : [usize; 4] = self.nodes[self.root].children;
+
+        let nw : usize = self.join([empty, empty, empty, nw]);
+        let ne : usize = self.join([empty, empty, ne, empty]);
+        let sw : usize = self.join([empty, sw, empty, empty]);
+        let se : usize = self.join([se, empty, empty, empty]);
+
+        self.root = self.join([nw, ne, sw, se]);
+
+        return Ok(());
+    }
+
+    // sets the cell at (row, column) of `node`, counted from its top left corner.
+    fn set_cell(&mut self, node : usize, row_index : i64, column_index : i64, cell : usize) -> usize {
+        let level : usize = self.nodes[node].level;
+        if level == 0 {
+            return cell;
+        }
+
+        let half : i64 = 1 << (level - 1);
+        let quadrant : usize = 2 * (row_index >= half) as usize + (column_index >= half) as usize;
+
+        let mut children : [usize; 4] = self.nodes[node].children;
+        children[quadrant] = self.set_cell(children[quadrant], row_index % half, column_index % half, cell);
+
+        return self.join(children);
+    }
+
+    // the centre of `node`, one level down, 2^exponent generations later (exponent <= level - 2).
+    fn get_result(&mut self, node : usize, exponent : usize) -> usize {
+        let level : usize = self.nodes[node].level;
+
+        if self.nodes[node].population == 0 {
+            return self.get_empty(level - 1);
+        }
+
+        if let Some(result) = self.results.get(&(node, exponent)) {
+            return *result;
+        }
+
+        let result : usize = if level == 2 {
+            self.get_base_result(node)
+        } else {
+            // the nine overlapping squares of level - 1 : n00 n01 n02 / n10 n11 n12 / n20 n21 n22
+            let [nw, ne, sw, se] : [usize; 4] = self.nodes[node].children;
+            let [_, nw_ne, nw_sw, nw_se] : [usize; 4] = self.nodes[nw].children;
+            let [ne_nw, _, ne_sw, ne_se] : [usize; 4] = self.nodes[ne].children;
+            let [sw_nw, sw_ne, _, sw_se] : [usize; 4] = self.nodes[sw].children;
+            let [se_nw, se_ne, se_sw, _] : [usize; 4] = self.nodes[se].children;
+
+            let squares : [usize; 9] = [
+                nw,
+                self.join([nw_ne, ne_nw, nw_se, ne_sw]),
+                ne,
+                self.join([nw_sw, nw_se, sw_nw, sw_ne]),
+                self.join([nw_se, ne_sw, sw_ne, se_nw]),
+                self.join([ne_sw, ne_se, se_nw, se_ne]),
+                sw,
+                self.join([sw_ne, se_nw, sw_se, se_sw]),
+                se,
+            ];
+
+            // the full step takes two half steps; a shorter one only advances in the second
+            let full : bool = exponent == level - 2;
+            let mut centres : [usize; 9] = [0; 9];
+            for (centre, square) in centres.iter_mut().zip(squares.iter()) {
+                *centre = if full { self.get_result(*square, exponent - 1) } else { self.get_centre(*square) };
+            }
+
+            let next_exponent : usize = if full { exponent - 1 } else { exponent };
+            let mut quadrants : [usize; 4] = [0; 4];
+            for (quadrant, (row, column)) in quadrants.iter_mut().zip([(0, 0), (0, 1), (1, 0), (1, 1)].iter()) {
+                let corner : usize = 3 * row + column;
+                let square : usize = self.join([centres[corner], centres[corner + 1], centres[corner + 3], centres[corner + 4]]);
+                *quadrant = self.get_result(square, next_exponent);
+            }
+
+            self.join(quadrants)
+        };
+
+        self.results.insert((node, exponent), result);
+
+        return result;
+    }
+
+    // the centre of `node`, one level down, at the same generation.
+    fn get_centre(&mut self, node : usize) -> usize {
+        let [nw, ne, sw, se] : [usize; 4] = self.nodes[node].children;
+
+        return self.join([self.nodes[nw].children[3], self.nodes[ne].children[2], self.nodes[sw].children[1], self.nodes[se].children[0]]);
+    }
+
+    // the 2 x 2 centre of a 4 x 4 node, one generation later.
+    fn get_base_result(&mut self, node : usize) -> usize {
+        let mut cells : [[usize; 4]; 4] = [[0; 4]; 4];
+        for (row_index, row) in cells.iter_mut().enumerate() {
+            for (column_index, cell) in row.iter_mut().enumerate() {
+                *cell = self.get_cell_of(node, row_index, column_index);
+            }
+        }
+
+        let mut next : [usize; 4] = [DEAD; 4];
+        for (quadrant, (row_index, column_index)) in [(1, 1), (1, 2), (2, 1), (2, 2)].iter().enumerate() {
+            let mut neighbours : usize = cells[(row_index - 1)..=(row_index + 1)].iter()
+                .map(|row| row[(column_index - 1)..=(column_index + 1)].iter().sum::<usize>())
+                .sum();
+
+            let a : usize = cells[*row_index][*column_index];
+            neighbours -= a;
+            next[quadrant] = (self.code >> (2 * neighbours + a)) & 1;
+        }
+
+        return self.join(next);
+    }
+
+    // the cell at (row, column) of a small node, counted from its top left corner.
+    fn get_cell_of(&self, node : usize, row_index : usize, column_index : usize) -> usize {
+        let mut node : usize = node;
+        let (mut row_index, mut column_index) : (usize, usize) = (row_index, column_index);
+
+        while self.nodes[node].level > 0 {
+            let half : usize = 1 << (self.nodes[node].level - 1);
+            node = self.nodes[node].children[2 * (row_index >= half) as usize + (column_index >= half) as usize];
+            row_index %= half;
+            column_index %= half;
+        }
+
+        return node;
+    }
+
+    // writes the live cells of `node`, whose top left cell is (top, left), that lie in
+    // `region` = [first row, first column, last row, last column) into `grid`.
+    fn write_region(&self, node : usize, top : i64, left : i64, region : &[i64; 4], grid : &mut Grid) {
+        let level : usize = self.nodes[node].level;
+        let size : i64 = 1 << level;
+
+        if self.nodes[node].population == 0
+            || top >= region[2] || left >= region[3] || top + size <= region[0] || left + size <= region[1] {
+            return;
+        }
+
+        if level == 0 {
+            grid.set_value((top - region[0]) as usize, (left - region[1]) as usize, 1);
+            return;
+        }
+
+        let half : i64 = size / 2;
+        let [nw, ne, sw, se] : [usize; 4] = self.nodes[node].children;
+        self.write_region(nw, top, left, region, grid);
+        self.write_region(ne, top, left + half, region, grid);
+        self.write_region(sw, top + half, left, region, grid);
+        self.write_region(se, top + half, left + half, region, grid);
+    }
+
+    // the bounding box of `node` from its top left corner, memoised per node.
+    fn get_bounds(&self, node : usize, bounds : &mut HashMap<usize, Option<[i64; 4]>>) -> Option<[i64; 4]> {
+        if self.nodes[node].population == 0 {
+            return None;
+        }
+
+        let level : usize = self.nodes[node].level;
+        if level == 0 {
+            return Some([0; 4]);
+        }
+
+        if let Some(node_bounds) = bounds.get(&node) {
+            return *node_bounds;
+        }
+
+        let half : i64 = 1 << (level - 1);
+        let mut node_bounds : Option<[i64; 4]> = None;
+
+        for (quadrant, child) in self.nodes[node].children.iter().enumerate() {
+            let (row_offset, column_offset) : (i64, i64) = (half * (quadrant / 2) as i64, half * (quadrant % 2) as i64);
+
+            if let Some([min_row, min_column, max_row, max_column]) = self.get_bounds(*child, bounds) {
+                let child_bounds : [i64; 4] = [min_row + row_offset, min_column + column_offset, max_row + row_offset, max_column + column_offset];
+
+                node_bounds = Some(match node_bounds {
+                    Some([a, b, c, d]) => [a.min(child_bounds[0]), b.min(child_bounds[1]), c.max(child_bounds[2]), d.max(child_bounds[3])],
+                    None => child_bounds
+                });
+            }
+        }
+
+        bounds.insert(node, node_bounds);
+
+        return node_bounds;
+    }
+}
+
+// copies `node` and its descendants into `nodes`, and returns the copy.
+fn copy_node(old_nodes : &[Node],
+    node : usize,
+    nodes : &mut Vec<Node>,
+    index : &mut HashMap<[usize; 4], usize>,
+    copies : &mut HashMap<usize, usize>) -> usize {
+
+    if let Some(copy) = copies.get(&node) {
+        return *copy;
+    }
+
+    let mut children : [usize; 4] = old_nodes[node].children;
+    for child in children.iter_mut() {
+        *child = copy_node(old_nodes, *child, nodes, index, copies);
+    }
+
+    nodes.push(Node { children : children, ..old_nodes[node] });
+    index.insert(children, nodes.len() - 1);
+    copies.insert(node, nodes.len() - 1);
+
+    return nodes.len() - 1;
+}
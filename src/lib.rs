@@ -0,0 +1,51 @@
+//! Two dimensional (totalistic and outer totalistic) and elementary cellular automata.
+//!
+//! The main types are :
+//!
+//! * `Grid` - a `width` x `height` array of cells, with connected component labelling.
+//! * `TCA2D` - a two dimensional automaton, generic over a `Rule`.
+//! * `ECA` - a one dimensional automaton and its spacetime diagram.
+//! * `ECARule` - a one dimensional rule with k states and radius r.
+//! * `Boundary` - how the edges of a grid are glued together.
+//! * `Compass` - the directions of a neighbourhood.
+//!
+//! # Example
+//!
+//! ```
+//! use gol_rs::{Grid, TCA2D, BoundaryType};
+//! use gol_rs::rule::OuterTotalistic;
+//!
+//! let initial_configuration = Grid::new(10, 10).unwrap();
+//! let mut gol = TCA2D::new(OuterTotalistic::new(224), 10, 10, 4, true, BoundaryType::Null,
+//!     initial_configuration).unwrap();
+//! gol.generate();
+//! ```
+
+pub mod boundary;
+pub mod census;
+pub mod compass_direction;
+pub mod cycle;
+pub mod eca;
+pub mod eca_packed;
+pub mod eca_rule;
+pub mod eca_stream;
+pub mod error;
+pub mod grid;
+pub mod hashlife;
+pub mod pattern_io;
+pub mod patterns;
+pub mod rule;
+pub mod sparse;
+pub mod sweep;
+pub mod tca2d;
+pub mod tca2d_packed;
+pub mod viewer;
+
+pub use boundary::{Boundary, BoundaryType};
+pub use compass_direction::{Compass, CompassDirection};
+pub use eca::ECA;
+pub use eca_rule::ECARule;
+pub use error::Error;
+pub use grid::Grid;
+pub use rule::Rule;
+pub use tca2d::TCA2D;
@@ -0,0 +1,82 @@
+// Plaintext patterns (https://conwaylife.com/wiki/Plaintext) :
+//
+//      !Name: Glider
+//      !A comment.
+//      .O.
+//      ..O
+//      OOO
+//
+// Lines starting with `!` are comments, `!Name:` giving the name; the others are rows of `.`
+// (dead) and `O` (alive), padded with dead cells to the longest row. `*` is read as `O`.
+
+use crate::error::Error;
+use crate::grid::Grid;
+use crate::pattern_io::{self, Pattern};
+
+/// Parses a plaintext pattern.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::pattern_io::cells;
+///
+/// let pattern = cells::parse("!Name: Glider\n!\n.O\n..O\nOOO\n").unwrap();
+/// assert_eq!(pattern.name.as_deref(), Some("Glider"));
+/// assert_eq!(pattern.cells.get_grid(), &vec![0, 1, 0, 0, 0, 1, 1, 1, 1]);
+/// ```
+pub fn parse(text : &str) -> Result<Pattern, Error> {
+    let mut pattern : Pattern = Pattern::new(Grid::empty(0, 0));
+    let mut rows : Vec<&str> = Vec::new();
+
+    for line in text.lines().map(|line| line.trim_end()) {
+        match line.strip_prefix('!') {
+            Some(comment) => match comment.strip_prefix("Name:") {
+                Some(name) => pattern.name = Some(name.trim().to_string()),
+                None if !comment.trim().is_empty() => pattern.comments.push(comment.trim().to_string()),
+                None => {}
+            },
+            None => rows.push(line)
+        }
+    }
+
+    // blank lines after the last row are not empty rows
+    while rows.last().is_some_and(|row| row.is_empty()) {
+        rows.pop();
+    }
+
+    let width : usize = rows.iter().map(|row| row.chars().count()).max().unwrap_or(0).max(1);
+    pattern_io::check_size(width, rows.len().max(1))?;
+    pattern.cells = Grid::new(width, rows.len().max(1))?;
+
+    for (row_index, row) in rows.iter().enumerate() {
+        for (column_index, cell) in row.chars().enumerate() {
+            match cell {
+                '.' => {},
+                'O' | '*' => pattern.cells.set_value(row_index, column_index, 1),
+                _ => return Err(Error::Parse(format!("invalid cell {} in plaintext row {}", cell, row_index + 1)))
+            }
+        }
+    }
+
+    return Ok(pattern);
+}
+
+/// Returns the pattern as plaintext, every row as wide as the grid; the cells must be 0 or 1.
+pub fn to_string(pattern : &Pattern) -> Result<String, Error> {
+    pattern_io::check_two_states(&pattern.cells)?;
+
+    let mut text : String = String::new();
+    if let Some(name) = &pattern.name {
+        text.push_str(&format!("!Name: {}\n", name));
+    }
+    for comment in pattern.comments.iter() {
+        text.push_str(&format!("!{}\n", comment));
+    }
+
+    for row in pattern.cells.get_grid().chunks(pattern.cells.get_width()) {
+        text.extend(row.iter().map(|cell| if *cell == 0 { '.' } else { 'O' }));
+        text.push('\n');
+    }
+
+    return Ok(text);
+}
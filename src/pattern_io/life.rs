@@ -0,0 +1,163 @@
+// Life 1.05 and Life 1.06 patterns (https://conwaylife.com/wiki/Life_1.05) :
+//
+//      #Life 1.05              #Life 1.06
+//      #D A comment.           0 -1
+//      #N                      1 0
+//      #P -1 -1                -1 1
+//      .*.                     0 1
+//      ..*                     1 1
+//      ***
+//
+// Life 1.05 lists blocks of `.` and `*` rows, each after a `#P x y` line giving the position
+// of its top left cell; `#D` lines are comments, `#N` is the normal Life rule and `#R 23/3`
+// any other survival / birth rule. Life 1.06 lists the `x y` coordinates of the live cells.
+// In both, x is the column and y the row; the grid read is the bounding box of the cells.
+
+use crate::error::Error;
+use crate::grid::Grid;
+use crate::pattern_io::{self, Pattern};
+
+/// Parses a Life 1.05 or Life 1.06 pattern, according to its header.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::pattern_io::life;
+///
+/// let life_105 = life::parse("#Life 1.05\n#R 23/36\n#P -1 -1\n.*.\n..*\n***\n").unwrap();
+/// let life_106 = life::parse("#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n").unwrap();
+///
+/// assert_eq!(life_105.cells.get_grid(), &vec![0, 1, 0, 0, 0, 1, 1, 1, 1]);
+/// assert_eq!(life_105.get_outer_totalistic().unwrap().get_rulestring(), "B36/S23");
+/// assert_eq!(life_106.cells.get_grid(), life_105.cells.get_grid());
+/// ```
+pub fn parse(text : &str) -> Result<Pattern, Error> {
+    let mut lines = text.lines().map(|line| line.trim()).filter(|line| !line.is_empty());
+
+    return match lines.next() {
+        Some(header) if header.starts_with("#Life 1.05") => parse_105(lines),
+        Some(header) if header.starts_with("#Life 1.06") => parse_106(lines),
+        _ => Err(Error::Parse(String::from("missing #Life 1.05 or #Life 1.06 header")))
+    };
+}
+
+/// Returns the pattern in Life 1.05, as one block centred on the origin, with the name as
+/// the first comment; the cells must be 0 or 1.
+pub fn to_string_105(pattern : &Pattern) -> Result<String, Error> {
+    pattern_io::check_two_states(&pattern.cells)?;
+
+    let mut text : String = String::from("#Life 1.05\n");
+    for comment in pattern.name.iter().chain(pattern.comments.iter()) {
+        text.push_str(&format!("#D {}\n", comment));
+    }
+
+    match (pattern.get_outer_totalistic(), &pattern.rule) {
+        (Some(rule), _) if rule.code == 224 => text.push_str("#N\n"),
+        (Some(rule), _) => {
+            // B3/S23 -> 23/3
+            let rulestring : String = rule.get_rulestring();
+            let (birth, survival) : (&str, &str) = rulestring.split_once('/').unwrap_or(("B", "S"));
+            text.push_str(&format!("#R {}/{}\n", &survival[1..], &birth[1..]));
+        },
+        (None, Some(rule)) => text.push_str(&format!("#R {}\n", rule)),
+        (None, None) => {}
+    }
+
+    let cells : &Grid = &pattern.cells;
+    text.push_str(&format!("#P {} {}\n", -(cells.get_width() as i64 / 2), -(cells.get_height() as i64 / 2)));
+
+    for row in cells.get_grid().chunks(cells.get_width()) {
+        // trailing dead cells are dropped, but a row keeps at least one cell
+        let length : usize = row.iter().rposition(|cell| *cell != 0).map_or(1, |index| index + 1);
+        text.extend(row[..length].iter().map(|cell| if *cell == 0 { '.' } else { '*' }));
+        text.push('\n');
+    }
+
+    return Ok(text);
+}
+
+/// Returns the pattern in Life 1.06, with the top left cell of the grid at the origin; the
+/// cells must be 0 or 1.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::Grid;
+/// use gol_rs::pattern_io::{life, Pattern};
+///
+/// let blinker = Grid::from_vec(3, 1, vec![1, 1, 1]).unwrap();
+/// assert_eq!(life::to_string_106(&Pattern::new(blinker)).unwrap(), "#Life 1.06\n0 0\n1 0\n2 0\n");
+/// ```
+pub fn to_string_106(pattern : &Pattern) -> Result<String, Error> {
+    pattern_io::check_two_states(&pattern.cells)?;
+
+    let cells : &Grid = &pattern.cells;
+    let mut text : String = String::from("#Life 1.06\n");
+
+    for (index, _) in cells.get_grid().iter().enumerate().filter(|(_, cell)| **cell != 0) {
+        text.push_str(&format!("{} {}\n", index % cells.get_width(), index / cells.get_width()));
+    }
+
+    return Ok(text);
+}
+
+fn parse_105<'a>(lines : impl Iterator<Item = &'a str>) -> Result<Pattern, Error> {
+    let mut pattern : Pattern = Pattern::new(Grid::empty(0, 0));
+    let mut cells : Vec<(i64, i64, usize)> = Vec::new();
+    let (mut row_index, mut column_index) : (i64, i64) = (0, 0); // of the current row
+
+    for line in lines {
+        if let Some(comment) = line.strip_prefix("#D") {
+            pattern.comments.push(comment.trim().to_string());
+        } else if line.starts_with("#N") {
+            pattern.rule = Some(String::from("B3/S23"));
+        } else if let Some(rule) = line.strip_prefix("#R") {
+            pattern.rule = Some(rule.trim().to_string());
+        } else if let Some(position) = line.strip_prefix("#P") {
+            let (x, y) : (i64, i64) = parse_coordinates(position)?;
+            row_index = y;
+            column_index = x;
+        } else if line.starts_with('#') {
+            continue;
+        } else {
+            let past_edge = || Error::Parse(format!("Life 1.05 row {} past the largest coordinate", line));
+
+            for (offset, cell) in line.chars().enumerate() {
+                match cell {
+                    '.' => {},
+                    '*' => cells.push((row_index, column_index.checked_add(offset as i64).ok_or_else(past_edge)?, 1)),
+                    _ => return Err(Error::Parse(format!("invalid cell {} in Life 1.05 row {}", cell, line)))
+                }
+            }
+            row_index = row_index.checked_add(1).ok_or_else(past_edge)?;
+        }
+    }
+
+    pattern.cells = pattern_io::from_coordinates(&cells)?;
+
+    return Ok(pattern);
+}
+
+fn parse_106<'a>(lines : impl Iterator<Item = &'a str>) -> Result<Pattern, Error> {
+    let mut cells : Vec<(i64, i64, usize)> = Vec::new();
+
+    for line in lines.filter(|line| !line.starts_with('#')) {
+        let (x, y) : (i64, i64) = parse_coordinates(line)?;
+        cells.push((y, x, 1));
+    }
+
+    return Ok(Pattern::new(pattern_io::from_coordinates(&cells)?));
+}
+
+// reads `x y`.
+fn parse_coordinates(line : &str) -> Result<(i64, i64), Error> {
+    let coordinates : Vec<i64> = line.split_whitespace()
+        .map(|coordinate| coordinate.parse::<i64>())
+        .collect::<Result<Vec<i64>, _>>()
+        .map_err(|e| Error::Parse(format!("invalid coordinates {} : {}", line, e)))?;
+
+    return match coordinates[..] {
+        [x, y] => Ok((x, y)),
+        _ => Err(Error::Parse(format!("expected two coordinates, found {}", line)))
+    };
+}
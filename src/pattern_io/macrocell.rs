@@ -0,0 +1,333 @@
+// Golly's Macrocell patterns (https://conwaylife.com/wiki/Macrocell) :
+//
+//      [M2] (gol-rs)
+//      #R B3/S23
+//      .*$..*$***$
+//      4 1 0 0 0
+//
+// The pattern is a quadtree written bottom up, one node per line, numbered from 1; the last
+// node is the root, and 0 stands for an empty node of any size. A node of level k is 2^k
+// cells wide :
+//
+// * two state leaves are 8 x 8 (level 3), rows of `.` and `*` each ended by `$`, trailing
+//   dead cells and empty rows dropped;
+// * multi-state leaves are `1 nw ne sw se`, the states of a 2 x 2 square;
+// * other nodes are `k nw ne sw se`, the numbers of their four children of level k - 1.
+//
+// Identical subtrees are written once, so huge but regular patterns stay small. `#R` is the
+// rule and `#C` lines are comments; other `#` lines are ignored.
+
+use crate::error::Error;
+use crate::grid::Grid;
+use crate::pattern_io::{self, Pattern};
+
+use std::collections::HashMap;
+
+// the deepest tree read; the side of the root must fit an i64.
+const MAX_LEVEL : usize = 62;
+
+enum Node {
+    Leaf(Vec<(usize, usize)>), // 8 x 8, the (row, column) of the live cells
+    States([usize; 4]), // 2 x 2
+    Branch(usize, [usize; 4]) // level, children
+}
+
+/// Parses a Macrocell pattern into the bounding box of its cells.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::pattern_io::macrocell;
+///
+/// // the glider, in the north east quadrant of a 16 x 16 tree
+/// let pattern = macrocell::parse("[M2] (golly 4.0)\n#R B3/S23\n.*$..*$***$\n4 0 1 0 0\n").unwrap();
+/// assert_eq!(pattern.get_outer_totalistic().unwrap().code, 224);
+/// assert_eq!(pattern.cells.get_grid(), &vec![0, 1, 0, 0, 0, 1, 1, 1, 1]);
+/// ```
+pub fn parse(text : &str) -> Result<Pattern, Error> {
+    let mut lines = text.lines().map(|line| line.trim()).filter(|line| !line.is_empty());
+    if !lines.next().is_some_and(|header| header.starts_with("[M2]")) {
+        return Err(Error::Parse(String::from("missing [M2] header")));
+    }
+
+    let mut pattern : Pattern = Pattern::new(Grid::empty(0, 0));
+    let mut nodes : Vec<Node> = Vec::new();
+
+    for line in lines {
+        if let Some(rule) = line.strip_prefix("#R") {
+            pattern.rule = Some(rule.trim().to_string());
+        } else if let Some(comment) = line.strip_prefix("#C") {
+            pattern.comments.push(comment.trim().to_string());
+        } else if line.starts_with('#') {
+            continue;
+        } else if line.starts_with(|first : char| first.is_ascii_digit()) {
+            nodes.push(parse_branch(line, &nodes)?);
+        } else {
+            nodes.push(parse_leaf(line)?);
+        }
+    }
+
+    let root : usize = nodes.len();
+    if root == 0 {
+        return Err(Error::Parse(String::from("no nodes in macrocell")));
+    }
+
+    // the bounding box first, so that the grid is no larger than the cells
+    let mut bounding_boxes : Vec<Option<(i64, i64, i64, i64)>> = Vec::with_capacity(nodes.len());
+    for node in nodes.iter() {
+        let bounding_box : Option<(i64, i64, i64, i64)> = get_bounding_box(node, &bounding_boxes);
+        bounding_boxes.push(bounding_box);
+    }
+
+    let (min_row, min_column, max_row, max_column) : (i64, i64, i64, i64) = match bounding_boxes[root - 1] {
+        Some(bounding_box) => bounding_box,
+        None => {
+            pattern.cells = Grid::new(1, 1)?;
+            return Ok(pattern);
+        }
+    };
+
+    pattern.cells = pattern_io::from_coordinates(&[(min_row, min_column, 0), (max_row, max_column, 0)])?;
+    fill(&nodes, root, -min_row, -min_column, &mut pattern.cells);
+
+    return Ok(pattern);
+}
+
+/// Returns the pattern as a Macrocell, with two state leaves if every cell is 0 or 1 and
+/// multi-state leaves otherwise; the name is written as the first comment.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::Grid;
+/// use gol_rs::pattern_io::{macrocell, Pattern};
+/// use gol_rs::rule::OuterTotalistic;
+///
+/// let glider = Grid::from_vec(3, 3, vec![0, 1, 0, 0, 0, 1, 1, 1, 1]).unwrap();
+/// assert_eq!(macrocell::to_string(&Pattern::with_rule(glider, &OuterTotalistic::new(224))),
+///     "[M2] (gol-rs)\n#R B3/S23\n.*$..*$***$\n4 1 0 0 0\n");
+/// ```
+pub fn to_string(pattern : &Pattern) -> String {
+    let cells : &Grid = &pattern.cells;
+    let multi_state : bool = cells.get_grid().iter().any(|cell| *cell > 1);
+
+    let mut text : String = String::from("[M2] (gol-rs)\n");
+    if let Some(rule) = &pattern.rule {
+        text.push_str(&format!("#R {}\n", rule));
+    }
+    for comment in pattern.name.iter().chain(pattern.comments.iter()) {
+        text.push_str(&format!("#C {}\n", comment));
+    }
+
+    // the root is a branch over leaves : 16 x 16 cells at least for two states, 4 x 4 for more
+    let leaf_level : usize = if multi_state { 1 } else { 3 };
+    let mut level : usize = leaf_level + 1;
+    while (1 << level) < cells.get_width().max(cells.get_height()) {
+        level += 1;
+    }
+
+    let mut lines : Vec<String> = Vec::new();
+    let mut numbers = HashMap::<String, usize>::new();
+    let root : usize = write_node(cells, level, leaf_level, 0, 0, &mut lines, &mut numbers);
+
+    // an empty pattern still needs a root
+    if root == 0 {
+        lines.push(format!("{} 0 0 0 0", level));
+    }
+
+    for line in lines {
+        text.push_str(&line);
+        text.push('\n');
+    }
+
+    return text;
+}
+
+// the number of the node of `level` whose top left cell is (`row_index`, `column_index`),
+// writing its line and those of its children unless they were already written; 0 if empty.
+fn write_node(cells : &Grid,
+    level : usize,
+    leaf_level : usize,
+    row_index : usize,
+    column_index : usize,
+    lines : &mut Vec<String>,
+    numbers : &mut HashMap<String, usize>) -> usize {
+    let size : usize = 1 << level;
+    if row_index >= cells.get_height() || column_index >= cells.get_width() {
+        return 0;
+    }
+
+    let get_value = |row_offset : usize, column_offset : usize| -> usize {
+        let (row, column) : (usize, usize) = (row_index + row_offset, column_index + column_offset);
+        return if row < cells.get_height() && column < cells.get_width() { cells.get_value(row, column) } else { 0 };
+    };
+
+    let line : String = if level == 3 && leaf_level == 3 {
+        let rows : Vec<String> = (0..8).map(|row_offset| {
+            let row : String = (0..8).map(|column_offset| if get_value(row_offset, column_offset) == 0 { '.' } else { '*' }).collect();
+            return row.trim_end_matches('.').to_string();
+        }).collect();
+
+        match rows.iter().rposition(|row| !row.is_empty()) {
+            Some(last) => rows[..=last].iter().map(|row| format!("{}$", row)).collect(),
+            None => return 0
+        }
+    } else {
+        let half : usize = size / 2;
+        let quadrants : [(usize, usize); 4] = [(0, 0), (0, half), (half, 0), (half, half)];
+
+        let children : Vec<usize> = if level == 1 {
+            quadrants.iter().map(|(row_offset, column_offset)| get_value(*row_offset, *column_offset)).collect()
+        } else {
+            quadrants.iter().map(|(row_offset, column_offset)|
+                write_node(cells, level - 1, leaf_level, row_index + row_offset, column_index + column_offset, lines, numbers)).collect()
+        };
+
+        if children.iter().all(|child| *child == 0) {
+            return 0;
+        }
+
+        format!("{} {} {} {} {}", level, children[0], children[1], children[2], children[3])
+    };
+
+    if let Some(number) = numbers.get(&line) {
+        return *number;
+    }
+
+    lines.push(line.clone());
+    numbers.insert(line, lines.len());
+
+    return lines.len();
+}
+
+// `k nw ne sw se`, whose children must already be read.
+fn parse_branch(line : &str, nodes : &[Node]) -> Result<Node, Error> {
+    let numbers : Vec<usize> = line.split_whitespace()
+        .map(|number| number.parse::<usize>())
+        .collect::<Result<Vec<usize>, _>>()
+        .map_err(|e| Error::Parse(format!("invalid macrocell node {} : {}", line, e)))?;
+
+    let (level, children) : (usize, [usize; 4]) = match numbers[..] {
+        [level, nw, ne, sw, se] => (level, [nw, ne, sw, se]),
+        _ => return Err(Error::Parse(format!("expected five numbers in macrocell node {}", line)))
+    };
+
+    if level == 1 {
+        return Ok(Node::States(children));
+    }
+
+    if level == 0 || level > MAX_LEVEL {
+        return Err(Error::Parse(format!("invalid macrocell level {}, expected 1 to {}", level, MAX_LEVEL)));
+    }
+
+    for child in children.iter().filter(|child| **child != 0) {
+        let child_level : Option<usize> = nodes.get(child - 1).map(get_level);
+        if child_level != Some(level - 1) {
+            return Err(Error::Parse(format!("invalid child {} in macrocell node {}", child, line)));
+        }
+    }
+
+    return Ok(Node::Branch(level, children));
+}
+
+// rows of `.` and `*`, each ended by `$`.
+fn parse_leaf(line : &str) -> Result<Node, Error> {
+    let mut cells : Vec<(usize, usize)> = Vec::new();
+    let (mut row_index, mut column_index) : (usize, usize) = (0, 0);
+
+    for cell in line.chars() {
+        match cell {
+            '$' => {
+                row_index += 1;
+                column_index = 0;
+                continue;
+            },
+            '*' => cells.push((row_index, column_index)),
+            '.' => {},
+            _ => return Err(Error::Parse(format!("invalid cell {} in macrocell leaf {}", cell, line)))
+        }
+
+        if row_index >= 8 || column_index >= 8 {
+            return Err(Error::Parse(format!("macrocell leaf {} is larger than 8 x 8", line)));
+        }
+        column_index += 1;
+    }
+
+    return Ok(Node::Leaf(cells));
+}
+
+fn get_level(node : &Node) -> usize {
+    return match node {
+        Node::Leaf(_) => 3,
+        Node::States(_) => 1,
+        Node::Branch(level, _) => *level
+    };
+}
+
+// the bounding box of the live cells of `node`, relative to its top left cell, from those of
+// the nodes before it.
+fn get_bounding_box(node : &Node, bounding_boxes : &[Option<(i64, i64, i64, i64)>]) -> Option<(i64, i64, i64, i64)> {
+    let mut bounding_box : Option<(i64, i64, i64, i64)> = None;
+    let mut include = |(min_row, min_column, max_row, max_column) : (i64, i64, i64, i64)| {
+        bounding_box = Some(match bounding_box {
+            Some((row, column, last_row, last_column)) =>
+                (row.min(min_row), column.min(min_column), last_row.max(max_row), last_column.max(max_column)),
+            None => (min_row, min_column, max_row, max_column)
+        });
+    };
+
+    match node {
+        Node::Leaf(cells) => for (row_index, column_index) in cells.iter() {
+            include((*row_index as i64, *column_index as i64, *row_index as i64, *column_index as i64));
+        },
+        Node::States(states) => for (quadrant, state) in states.iter().enumerate() {
+            if *state != 0 {
+                let (row_index, column_index) : (i64, i64) = ((quadrant / 2) as i64, (quadrant % 2) as i64);
+                include((row_index, column_index, row_index, column_index));
+            }
+        },
+        Node::Branch(level, children) => {
+            let half : i64 = 1 << (level - 1);
+            for (quadrant, child) in children.iter().enumerate().filter(|(_, child)| **child != 0) {
+                let (row_offset, column_offset) : (i64, i64) = ((quadrant / 2) as i64 * half, (quadrant % 2) as i64 * half);
+                if let Some((min_row, min_column, max_row, max_column)) = bounding_boxes[child - 1] {
+                    include((min_row + row_offset, min_column + column_offset, max_row + row_offset, max_column + column_offset));
+                }
+            }
+        }
+    }
+
+    return bounding_box;
+}
+
+// writes the live cells of node `number` into `grid`, its top left cell at
+// (`row_offset`, `column_offset`) of the grid.
+fn fill(nodes : &[Node], number : usize, row_offset : i64, column_offset : i64, grid : &mut Grid) {
+    match &nodes[number - 1] {
+        Node::Leaf(cells) => for (row_index, column_index) in cells.iter() {
+            set_value(grid, row_offset + *row_index as i64, column_offset + *column_index as i64, 1);
+        },
+        Node::States(states) => for (quadrant, state) in states.iter().enumerate() {
+            set_value(grid, row_offset + (quadrant / 2) as i64, column_offset + (quadrant % 2) as i64, *state);
+        },
+        Node::Branch(level, children) => {
+            let half : i64 = 1 << (level - 1);
+            for (quadrant, child) in children.iter().enumerate().filter(|(_, child)| **child != 0) {
+                let (row_index, column_index) : (i64, i64) = (row_offset + (quadrant / 2) as i64 * half, column_offset + (quadrant % 2) as i64 * half);
+
+                // subtrees outside of the grid hold no live cell
+                if row_index < grid.get_height() as i64 && column_index < grid.get_width() as i64
+                    && row_index + half > 0 && column_index + half > 0 {
+                    fill(nodes, *child, row_index, column_index, grid);
+                }
+            }
+        }
+    }
+}
+
+// sets a cell of `grid`, if it lies in it.
+fn set_value(grid : &mut Grid, row_index : i64, column_index : i64, state : usize) {
+    if row_index >= 0 && column_index >= 0 && (row_index as usize) < grid.get_height() && (column_index as usize) < grid.get_width() {
+        grid.set_value(row_index as usize, column_index as usize, state);
+    }
+}
@@ -0,0 +1,203 @@
+// Pattern files : reading and writing the formats patterns are distributed in.
+//
+// Every format goes through `Pattern` : the cells as a `Grid`, and the rule and comments the
+// file carries, if any. `read` and `write` pick the format from the file extension; `.lif`
+// files are read as Life 1.05 or 1.06 from their header, and written as Life 1.06.
+
+pub mod cells;
+pub mod life;
+pub mod macrocell;
+pub mod rle;
+
+use crate::error::Error;
+use crate::grid::Grid;
+use crate::rule::{OuterTotalistic, Rule};
+
+use std::convert::TryFrom;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A pattern file format.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    /// Golly / LifeWiki run length encoding, `.rle`.
+    Rle,
+    /// Plaintext rows of `.` and `O`, `.cells`.
+    Cells,
+    /// Blocks of `.` and `*` rows at given positions, `.lif`.
+    Life105,
+    /// One `x y` coordinate pair per live cell, `.lif`.
+    Life106,
+    /// Golly's quadtree, where identical subtrees are written once, `.mc`.
+    Macrocell,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    /// Parses a format name or file extension : `rle`, `cells`, `life105`, `life106` (or
+    /// `lif`), `mc`.
+    fn from_str(format : &str) -> Result<Format, Error> {
+        return match format.to_lowercase().as_str() {
+            "rle" => Ok(Format::Rle),
+            "cells" => Ok(Format::Cells),
+            "life105" => Ok(Format::Life105),
+            "life106" | "lif" | "life" => Ok(Format::Life106),
+            "mc" | "macrocell" => Ok(Format::Macrocell),
+            _ => Err(Error::Parse(format!("unknown pattern format {}", format)))
+        };
+    }
+}
+
+impl Format {
+
+    /// Returns the format of a file, from its extension.
+    pub fn from_path(path : &str) -> Result<Format, Error> {
+        return match Path::new(path).extension().and_then(|extension| extension.to_str()) {
+            Some(extension) => extension.parse(),
+            None => Err(Error::Parse(format!("no extension in {}", path)))
+        };
+    }
+
+    pub fn parse(&self, text : &str) -> Result<Pattern, Error> {
+        return match self {
+            Format::Rle => rle::parse(text),
+            Format::Cells => cells::parse(text),
+            Format::Life105 | Format::Life106 => life::parse(text),
+            Format::Macrocell => macrocell::parse(text)
+        };
+    }
+
+    /// Returns the pattern in this format; the plaintext and Life formats only hold cells in
+    /// states 0 and 1.
+    pub fn to_string(&self, pattern : &Pattern) -> Result<String, Error> {
+        return match self {
+            Format::Rle => rle::to_string(pattern),
+            Format::Cells => cells::to_string(pattern),
+            Format::Life105 => life::to_string_105(pattern),
+            Format::Life106 => life::to_string_106(pattern),
+            Format::Macrocell => Ok(macrocell::to_string(pattern))
+        };
+    }
+}
+
+/// The cells of a pattern file, with its rule and comments.
+#[derive(Clone)]
+pub struct Pattern {
+    pub cells : Grid,
+    pub rule : Option<String>, // as written in the file, e.g. "B3/S23"
+    pub name : Option<String>,
+    pub comments : Vec<String>
+}
+
+impl Pattern {
+
+    /// Returns a pattern with no rule, name or comments.
+    pub fn new(cells : Grid) -> Pattern {
+        return Pattern {
+            cells : cells,
+            rule : None,
+            name : None,
+            comments : Vec::new()
+        };
+    }
+
+    /// Returns a pattern whose rule is the rulestring of `rule`, if it has an outer
+    /// totalistic code.
+    pub fn with_rule<R : Rule + ?Sized>(cells : Grid, rule : &R) -> Pattern {
+        let mut pattern : Pattern = Pattern::new(cells);
+        pattern.rule = rule.get_outer_totalistic_code().map(|code| OuterTotalistic::new(code).get_rulestring());
+
+        return pattern;
+    }
+
+    /// Returns the rule of the pattern as an outer totalistic rule, if it has one that
+    /// parses; a bounded grid suffix such as `:T100,100` is ignored.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::Grid;
+    /// use gol_rs::pattern_io::Pattern;
+    ///
+    /// let mut pattern = Pattern::new(Grid::new(1, 1).unwrap());
+    /// assert!(pattern.get_outer_totalistic().is_none());
+    ///
+    /// pattern.rule = Some(String::from("B36/S23:T100,100"));
+    /// assert_eq!(pattern.get_outer_totalistic().unwrap().code, 224 | 1 << 12);
+    ///
+    /// pattern.rule = Some(String::from("Life"));
+    /// assert_eq!(pattern.get_outer_totalistic().unwrap().code, 224);
+    /// ```
+    pub fn get_outer_totalistic(&self) -> Option<OuterTotalistic> {
+        let rule : &str = self.rule.as_ref()?.split(':').next()?.trim();
+
+        if rule.eq_ignore_ascii_case("life") {
+            return Some(OuterTotalistic::new(224));
+        }
+
+        return OuterTotalistic::from_rulestring(rule).ok();
+    }
+}
+
+/// Reads a pattern file, in the format given by its extension.
+pub fn read(path : &str) -> Result<Pattern, Box<dyn std::error::Error>> {
+    let format : Format = Format::from_path(path)?;
+
+    return Ok(format.parse(&fs::read_to_string(path)?)?);
+}
+
+/// Writes a pattern file, in the format given by its extension.
+pub fn write(path : &str, pattern : &Pattern) -> Result<(), Box<dyn std::error::Error>> {
+    let format : Format = Format::from_path(path)?;
+    fs::write(path, format.to_string(pattern)?)?;
+
+    return Ok(());
+}
+
+// returns an error for a cell in a state above 1.
+fn check_two_states(cells : &Grid) -> Result<(), Error> {
+    return match cells.get_grid().iter().find(|cell| **cell > 1) {
+        Some(state) => Err(Error::InvalidState { state : *state, states : 2 }),
+        None => Ok(())
+    };
+}
+
+// the most cells a pattern read may have, so a corrupt or hostile file is an error rather than
+// an allocation failure.
+const MAX_CELLS : usize = 1 << 28;
+
+// returns an error for a grid of more than MAX_CELLS cells.
+fn check_size(width : usize, height : usize) -> Result<(), Error> {
+    if width.checked_mul(height).is_none_or(|size| size > MAX_CELLS) {
+        return Err(Error::InvalidDimensions { width : width, height : height });
+    }
+
+    return Ok(());
+}
+
+// the smallest grid holding the (row, column, state) cells, whose top left cell is the
+// smallest row and column; a 1 x 1 grid if there are none.
+fn from_coordinates(cells : &[(i64, i64, usize)]) -> Result<Grid, Error> {
+    let min_row : i64 = cells.iter().map(|cell| cell.0).min().unwrap_or(0);
+    let min_column : i64 = cells.iter().map(|cell| cell.1).min().unwrap_or(0);
+    let max_row : i64 = cells.iter().map(|cell| cell.0).max().unwrap_or(0);
+    let max_column : i64 = cells.iter().map(|cell| cell.1).max().unwrap_or(0);
+
+    // usize::MAX, and so too large, if the span overflows
+    let get_length = |min : i64, max : i64| -> usize {
+        return max.checked_sub(min).and_then(|span| span.checked_add(1))
+            .and_then(|length| usize::try_from(length).ok())
+            .unwrap_or(usize::MAX);
+    };
+    let (width, height) : (usize, usize) = (get_length(min_column, max_column), get_length(min_row, max_row));
+    check_size(width, height)?;
+
+    let mut grid : Grid = Grid::new(width, height)?;
+    for (row_index, column_index, state) in cells.iter() {
+        grid.set_value((row_index - min_row) as usize, (column_index - min_column) as usize, *state);
+    }
+
+    return Ok(grid);
+}
@@ -0,0 +1,284 @@
+// Run length encoded patterns (https://conwaylife.com/wiki/Run_Length_Encoded) :
+//
+//      #N Glider
+//      #C A comment.
+//      x = 3, y = 3, rule = B3/S23
+//      bob$2bo$3o!
+//
+// The body lists runs of cells row by row : an optional count, then a tag; `$` ends a row and
+// `!` ends the pattern. Two state patterns use `b` (dead) and `o` (alive); multi-state
+// patterns use `.` for 0, `A` to `X` for 1 to 24, and prefixes `p` to `y` for 25 to 255
+// (`pA` is 25, `yO` is 255). Lines starting with `#` are comments : `#N` is the name, `#C`
+// and `#c` the comments, `#r` an old form of the rule; the others are ignored.
+
+use crate::error::Error;
+use crate::grid::Grid;
+use crate::pattern_io::{self, Pattern};
+
+// the longest line written.
+const LINE_LENGTH : usize = 70;
+
+/// Parses a run length encoded pattern.
+///
+/// The grid is `x` x `y`, or larger if the body says so.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::pattern_io::rle;
+///
+/// let pattern = rle::parse("#N Glider\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!").unwrap();
+/// assert_eq!(pattern.name.as_deref(), Some("Glider"));
+/// assert_eq!(pattern.get_outer_totalistic().unwrap().code, 224);
+/// assert_eq!(pattern.cells.get_grid(), &vec![0, 1, 0, 0, 0, 1, 1, 1, 1]);
+///
+/// // multi-state
+/// let pattern = rle::parse("x = 4, y = 1\n.AB pA!").unwrap();
+/// assert_eq!(pattern.cells.get_grid(), &vec![0, 1, 2, 25]);
+/// ```
+pub fn parse(text : &str) -> Result<Pattern, Error> {
+    let mut pattern : Pattern = Pattern::new(Grid::empty(0, 0));
+    let mut size : (usize, usize) = (0, 0); // (x, y) from the header
+    let mut body : String = String::new();
+    let mut header_found : bool = false;
+
+    for line in text.lines().map(|line| line.trim()) {
+        if let Some(comment) = line.strip_prefix('#') {
+            let (kind, content) : (&str, &str) = comment.split_at(comment.chars().next().map_or(0, |kind| kind.len_utf8()));
+            let content : String = content.trim().to_string();
+
+            match kind {
+                "N" => pattern.name = Some(content),
+                "C" | "c" => pattern.comments.push(content),
+                "r" if pattern.rule.is_none() => pattern.rule = Some(content),
+                _ => {}
+            }
+        } else if !header_found && line.starts_with('x') {
+            size = parse_header(line, &mut pattern)?;
+            header_found = true;
+        } else if header_found {
+            body.push_str(line);
+            if line.contains('!') {
+                break;
+            }
+        }
+    }
+
+    if !header_found {
+        return Err(Error::Parse(String::from("missing x = .., y = .. header")));
+    }
+
+    let runs : Vec<(usize, usize, usize, usize)> = parse_body(&body)?;
+    let width : usize = runs.iter().map(|(_, column_index, run, _)| column_index + run).max().unwrap_or(0).max(size.0).max(1);
+    let height : usize = runs.iter().map(|(row_index, _, _, _)| row_index + 1).max().unwrap_or(0).max(size.1).max(1);
+    pattern_io::check_size(width, height)?;
+
+    pattern.cells = Grid::new(width, height)?;
+    for (row_index, column_index, run, state) in runs {
+        for column_index in column_index..(column_index + run) {
+            pattern.cells.set_value(row_index, column_index, state);
+        }
+    }
+
+    return Ok(pattern);
+}
+
+/// Returns the pattern run length encoded, with `b` and `o` if every cell is 0 or 1, and
+/// multi-state tags otherwise; states above 255 have no tag and are an error.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::Grid;
+/// use gol_rs::pattern_io::{rle, Pattern};
+/// use gol_rs::rule::OuterTotalistic;
+///
+/// let glider = Grid::from_vec(3, 3, vec![0, 1, 0, 0, 0, 1, 1, 1, 1]).unwrap();
+/// assert_eq!(rle::to_string(&Pattern::with_rule(glider, &OuterTotalistic::new(224))).unwrap(), "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+///
+/// let large = Grid::from_vec(2, 1, vec![1, 300]).unwrap();
+/// assert!(rle::to_string(&Pattern::new(large)).is_err());
+/// ```
+pub fn to_string(pattern : &Pattern) -> Result<String, Error> {
+    let cells : &Grid = &pattern.cells;
+    if let Some(state) = cells.get_grid().iter().find(|cell| **cell > 255) {
+        return Err(Error::InvalidState { state : *state, states : 256 });
+    }
+    let multi_state : bool = cells.get_grid().iter().any(|cell| *cell > 1);
+
+    let mut text : String = String::new();
+    if let Some(name) = &pattern.name {
+        text.push_str(&format!("#N {}\n", name));
+    }
+    for comment in pattern.comments.iter() {
+        text.push_str(&format!("#C {}\n", comment));
+    }
+
+    text.push_str(&format!("x = {}, y = {}", cells.get_width(), cells.get_height()));
+    if let Some(rule) = &pattern.rule {
+        text.push_str(&format!(", rule = {}", rule));
+    }
+    text.push('\n');
+
+    // (count, tag) runs; trailing dead cells of a row and trailing empty rows are dropped
+    let mut runs : Vec<(usize, String)> = Vec::new();
+    let mut empty_rows : usize = 0;
+
+    for row in cells.get_grid().chunks(cells.get_width()) {
+        let length : usize = row.iter().rposition(|cell| *cell != 0).map_or(0, |index| index + 1);
+        if length == 0 {
+            empty_rows += 1;
+            continue;
+        }
+
+        if !runs.is_empty() || empty_rows > 0 {
+            // ends the previous row, and the empty ones after it
+            let count : usize = empty_rows + !runs.is_empty() as usize;
+            push_run(&mut runs, count, String::from("$"));
+        }
+        empty_rows = 0;
+
+        for cell in row[..length].iter() {
+            push_run(&mut runs, 1, get_tag(*cell, multi_state));
+        }
+    }
+
+    // lines of at most LINE_LENGTH characters, never splitting a run
+    let mut line : String = String::new();
+    for (count, tag) in runs.iter() {
+        let run : String = if *count > 1 { format!("{}{}", count, tag) } else { tag.clone() };
+
+        if line.len() + run.len() > LINE_LENGTH {
+            text.push_str(&line);
+            text.push('\n');
+            line.clear();
+        }
+        line.push_str(&run);
+    }
+
+    if line.len() + 1 > LINE_LENGTH {
+        text.push_str(&line);
+        text.push('\n');
+        line.clear();
+    }
+    text.push_str(&line);
+    text.push_str("!\n");
+
+    return Ok(text);
+}
+
+// reads `x = 3, y = 3, rule = B3/S23`, sets the rule of `pattern`, and returns (x, y).
+fn parse_header(line : &str, pattern : &mut Pattern) -> Result<(usize, usize), Error> {
+    let mut size : (usize, usize) = (0, 0);
+
+    // the rule runs to the end of the line : bounded grids hold a comma, as in B3/S23:T100,100
+    let (fields, rule) : (&str, Option<&str>) = match line.find("rule") {
+        Some(start) => (&line[..start], Some(&line[start..])),
+        None => (line, None)
+    };
+
+    if let Some(rule) = rule {
+        let (_, value) : (&str, &str) = rule.split_once('=')
+            .ok_or_else(|| Error::Parse(format!("invalid header field {}", rule)))?;
+        pattern.rule = Some(value.trim().to_string());
+    }
+
+    for field in fields.split(',').filter(|field| !field.trim().is_empty()) {
+        let (key, value) : (&str, &str) = field.split_once('=')
+            .ok_or_else(|| Error::Parse(format!("invalid header field {}", field)))?;
+        let value : &str = value.trim();
+        let parse_size = |value : &str| value.parse::<usize>()
+            .map_err(|e| Error::Parse(format!("invalid size {} in header : {}", value, e)));
+
+        match key.trim() {
+            "x" => size.0 = parse_size(value)?,
+            "y" => size.1 = parse_size(value)?,
+            _ => {}
+        }
+    }
+
+    return Ok(size);
+}
+
+// the (row, column, run, state) of every run of nonzero cells of the body; rows and columns
+// past MAX_CELLS are an error.
+fn parse_body(body : &str) -> Result<Vec<(usize, usize, usize, usize)>, Error> {
+    let mut runs : Vec<(usize, usize, usize, usize)> = Vec::new();
+    let (mut row_index, mut column_index) : (usize, usize) = (0, 0);
+    let mut count : Option<usize> = None;
+    let mut prefix : Option<char> = None;
+
+    for tag in body.chars().filter(|tag| !tag.is_whitespace()) {
+        if let Some(digit) = tag.to_digit(10) {
+            count = count.unwrap_or(0).checked_mul(10).and_then(|count| count.checked_add(digit as usize))
+                .filter(|count| *count <= pattern_io::MAX_CELLS);
+            if count.is_none() {
+                return Err(Error::Parse(String::from("run count too large in run length encoding")));
+            }
+            continue;
+        }
+
+        let run : usize = count.take().unwrap_or(1);
+
+        let state : usize = match (prefix.take(), tag) {
+            (None, '!') => break,
+            (None, '$') => {
+                row_index = advance(row_index, run)?;
+                column_index = 0;
+                continue;
+            },
+            (None, 'p'..='y') => {
+                prefix = Some(tag);
+                count = Some(run); // the count comes before the prefix
+                continue;
+            },
+            (None, 'b') | (None, '.') => 0,
+            (None, 'o') => 1,
+            (None, 'A'..='X') => tag as usize - 'A' as usize + 1,
+            (Some(prefix), 'A'..='X') => 24 * (prefix as usize - 'p' as usize + 1) + tag as usize - 'A' as usize + 1,
+            (_, tag) => return Err(Error::Parse(format!("invalid tag {} in run length encoding", tag)))
+        };
+
+        if state > 255 {
+            return Err(Error::Parse(format!("invalid state {} in run length encoding", state)));
+        }
+
+        if state != 0 && run > 0 {
+            runs.push((row_index, column_index, run, state));
+        }
+        column_index = advance(column_index, run)?;
+    }
+
+    return Ok(runs);
+}
+
+// returns `index + run`, if it stays within MAX_CELLS.
+fn advance(index : usize, run : usize) -> Result<usize, Error> {
+    return match index.checked_add(run) {
+        Some(index) if index <= pattern_io::MAX_CELLS => Ok(index),
+        _ => Err(Error::Parse(String::from("pattern too large in run length encoding")))
+    };
+}
+
+// the tag of a state : b / o, or . / A..X / pA..yO.
+fn get_tag(state : usize, multi_state : bool) -> String {
+    return match (state, multi_state) {
+        (0, false) => String::from("b"),
+        (_, false) => String::from("o"),
+        (0, true) => String::from("."),
+        (1..=24, true) => ((b'A' + (state - 1) as u8) as char).to_string(),
+        _ => format!("{}{}", (b'p' + ((state - 25) / 24) as u8) as char, (b'A' + ((state - 25) % 24) as u8) as char)
+    };
+}
+
+// appends a run, merging it into the last one if the tags match.
+fn push_run(runs : &mut Vec<(usize, String)>, count : usize, tag : String) {
+    if let Some((last_count, last_tag)) = runs.last_mut() {
+        if *last_tag == tag {
+            *last_count += count;
+            return;
+        }
+    }
+
+    runs.push((count, tag));
+}
@@ -0,0 +1,213 @@
+// Named patterns : a built-in library of well known Life objects, and a loader for the Life
+// Lexicon (https://conwaylife.com/ref/lexicon/), whose text version lists every term as
+// `:name: description`, with diagrams on tab indented lines of `.` (dead) and `*` or `O`
+// (alive).
+//
+// Names are looked up without case, and with spaces and underscores read as dashes :
+// "Gosper glider gun", "gosper_glider_gun" and "gosper-glider-gun" are the same pattern.
+
+use crate::error::Error;
+use crate::grid::Grid;
+
+use std::collections::BTreeMap;
+use std::fs;
+
+// (name, rows); still lifes, oscillators, spaceships, methuselahs and guns.
+const LIBRARY : [(&str, &str); 21] = [
+    ("block", "OO\nOO"),
+    ("beehive", ".OO.\nO..O\n.OO."),
+    ("loaf", ".OO.\nO..O\n.O.O\n..O."),
+    ("boat", "OO.\nO.O\n.O."),
+    ("tub", ".O.\nO.O\n.O."),
+    ("pond", ".OO.\nO..O\nO..O\n.OO."),
+    ("blinker", "OOO"),
+    ("toad", ".OOO\nOOO."),
+    ("beacon", "OO..\nOO..\n..OO\n..OO"),
+    ("pulsar", "..OOO...OOO..\n.............\nO....O.O....O\nO....O.O....O\nO....O.O....O\n..OOO...OOO..\n\
+        .............\n..OOO...OOO..\nO....O.O....O\nO....O.O....O\nO....O.O....O\n.............\n..OOO...OOO.."),
+    ("pentadecathlon", "..O....O..\nOO.OOOO.OO\n..O....O.."),
+    ("glider", ".O.\n..O\nOOO"),
+    ("lwss", ".O..O\nO....\nO...O\nOOOO."),
+    ("mwss", "...O..\n.O...O\nO.....\nO....O\nOOOOO."),
+    ("hwss", "...OO..\n.O....O\nO......\nO.....O\nOOOOOO."),
+    ("r-pentomino", ".OO\nOO.\n.O."),
+    ("acorn", ".O.....\n...O...\nOO..OOO"),
+    ("diehard", "......O.\nOO......\n.O...OOO"),
+    ("b-heptomino", "O.OO\nOOO.\n.O.."),
+    ("pi-heptomino", "OOO\nO.O\nO.O"),
+    ("gosper-glider-gun", "........................O...........\n\
+        ......................O.O...........\n\
+        ............OO......OO............OO\n\
+        ...........O...O....OO............OO\n\
+        OO........O.....O...OO..............\n\
+        OO........O...O.OO....O.O...........\n\
+        ..........O.....O.......O...........\n\
+        ...........O...O....................\n\
+        ............OO......................"),
+];
+
+/// Returns a pattern of the built-in library.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::patterns;
+///
+/// let glider = patterns::get_pattern("Glider").unwrap();
+/// assert_eq!(glider.get_grid(), &vec![0, 1, 0, 0, 0, 1, 1, 1, 1]);
+///
+/// assert_eq!(patterns::get_pattern("Gosper glider gun").unwrap().get_population(), 36);
+/// assert!(patterns::get_pattern("unknown").is_err());
+/// ```
+pub fn get_pattern(name : &str) -> Result<Grid, Error> {
+    let key : String = get_key(name);
+
+    return match LIBRARY.iter().find(|(library_name, _)| *library_name == key) {
+        Some((_, rows)) => parse_diagram(&rows.lines().collect::<Vec<&str>>()),
+        None => Err(Error::Parse(format!("unknown pattern {}", name)))
+    };
+}
+
+/// Returns the names of the built-in patterns.
+pub fn get_pattern_names() -> Vec<&'static str> {
+    return LIBRARY.iter().map(|(name, _)| *name).collect();
+}
+
+/// Returns `pattern` rotated `quarter_turns` quarter turns clockwise, after mirroring it left
+/// to right if `reflect`.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::patterns;
+///
+/// let glider = patterns::get_pattern("glider").unwrap();
+///
+/// // the glider heading north west
+/// let oriented = patterns::orient(&glider, 2, false);
+/// assert_eq!(oriented.get_grid(), &vec![1, 1, 1, 1, 0, 0, 0, 1, 0]);
+/// ```
+pub fn orient(pattern : &Grid, quarter_turns : usize, reflect : bool) -> Grid {
+    let mut oriented : Grid = if reflect { pattern.reflect() } else { pattern.clone() };
+
+    for _ in 0..(quarter_turns % 4) {
+        oriented = oriented.rotate_clockwise();
+    }
+
+    return oriented;
+}
+
+/// The patterns of a Life Lexicon file, by term.
+#[derive(Clone, Default)]
+pub struct Lexicon {
+    patterns : BTreeMap<String, Grid> // key of the term -> its first diagram
+}
+
+impl Lexicon {
+
+    /// Parses the text version of the Lexicon; terms without a diagram are skipped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::patterns::Lexicon;
+    ///
+    /// let text = ":block: (p1) The most common still life.\n\t**\n\t**\n\
+    ///     :blinker: (p2) The smallest oscillator.\n\t***\n\
+    ///     :Life: A cellular automaton.\n";
+    ///
+    /// let lexicon = Lexicon::parse(text);
+    /// assert_eq!(lexicon.get_names(), vec!["blinker", "block"]);
+    /// assert_eq!(lexicon.get("Block").unwrap().get_population(), 4);
+    /// assert!(lexicon.get("life").is_none());
+    /// ```
+    pub fn parse(text : &str) -> Lexicon {
+        let mut patterns = BTreeMap::<String, Grid>::new();
+
+        let mut term : Option<String> = None;
+        let mut diagram : Vec<&str> = Vec::new();
+
+        for line in text.lines() {
+            if is_diagram_line(line) {
+                diagram.push(line.trim());
+                continue;
+            }
+
+            // the first diagram of a term is its pattern
+            if let (Some(key), false) = (term.as_ref(), diagram.is_empty()) {
+                if !patterns.contains_key(key) {
+                    if let Ok(pattern) = parse_diagram(&diagram) {
+                        patterns.insert(key.clone(), pattern);
+                    }
+                }
+            }
+            diagram.clear();
+
+            if let Some(rest) = line.strip_prefix(':') {
+                if let Some((name, _)) = rest.split_once(':') {
+                    term = Some(get_key(name));
+                }
+            }
+        }
+
+        if let (Some(key), false) = (term, diagram.is_empty()) {
+            if let (false, Ok(pattern)) = (patterns.contains_key(&key), parse_diagram(&diagram)) {
+                patterns.insert(key, pattern);
+            }
+        }
+
+        return Lexicon { patterns : patterns };
+    }
+
+    /// Reads and parses a Lexicon file, e.g., `lexicon.txt`.
+    pub fn read(path : &str) -> Result<Lexicon, Box<dyn std::error::Error>> {
+        return Ok(Lexicon::parse(&fs::read_to_string(path)?));
+    }
+
+    pub fn get(&self, name : &str) -> Option<&Grid> {
+        return self.patterns.get(&get_key(name));
+    }
+
+    /// Returns the names of the terms with a pattern, sorted.
+    pub fn get_names(&self) -> Vec<&str> {
+        return self.patterns.keys().map(|name| name.as_str()).collect();
+    }
+
+    pub fn len(&self) -> usize {
+        return self.patterns.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.patterns.is_empty();
+    }
+}
+
+// lowercase, with spaces and underscores as dashes.
+fn get_key(name : &str) -> String {
+    return name.trim().to_lowercase().replace([' ', '_'], "-");
+}
+
+fn is_diagram_line(line : &str) -> bool {
+    let cells : &str = line.trim();
+
+    return line.starts_with('\t') && !cells.is_empty() && cells.chars().all(|cell| cell == '.' || cell == '*' || cell == 'O');
+}
+
+// rows of `.` and `*` or `O`, padded with dead cells to the longest row.
+fn parse_diagram(rows : &[&str]) -> Result<Grid, Error> {
+    let rows : Vec<&str> = rows.iter().map(|row| row.trim()).collect();
+    let width : usize = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut grid : Grid = Grid::new(width, rows.len())?;
+
+    for (row_index, row) in rows.iter().enumerate() {
+        for (column_index, cell) in row.chars().enumerate() {
+            match cell {
+                '.' => {},
+                '*' | 'O' => grid.set_value(row_index, column_index, 1),
+                _ => return Err(Error::Parse(format!("invalid cell {} in diagram", cell)))
+            }
+        }
+    }
+
+    return Ok(grid);
+}
@@ -0,0 +1,255 @@
+// A rule maps the neighbourhood of a cell to the next state of that cell.
+//
+// The neighbourhood (the "radius") is always passed as nine values, in the same order as
+// the compass used by TCA2D :
+//
+//      0 1 2       NorthWest North NorthEast
+//      3 4 5   =   West      Origin East
+//      6 7 8       SouthWest South SouthEast
+//
+// Inactive directions (e.g., the diagonals of a von Neumann neighbourhood) are passed as 0.
+
+use crate::error::Error;
+
+/// Maps a neighbourhood to the next state of the cell at its centre.
+///
+/// # Arguments
+///
+/// * `radius` - nine values in compass order; `radius[4]` is the cell itself.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::rule::{Rule, OuterTotalistic};
+///
+/// let life = OuterTotalistic::new(224);
+/// let radius = [0, 1, 0, 0, 0, 1, 0, 1, 0]; // dead cell with three neighbours
+/// assert_eq!(life.next_state(&radius), 1);
+/// ```
+pub trait Rule {
+    fn next_state(&self, radius : &[usize]) -> usize;
+
+    /// Returns a short name used in window titles and outputs.
+    fn get_name(&self) -> String {
+        return String::from("custom");
+    }
+
+    /// Returns the outer totalistic code of the rule, if it has one; the bit-sliced backend
+    /// of TCA2D needs it.
+    fn get_outer_totalistic_code(&self) -> Option<usize> {
+        return None;
+    }
+}
+
+impl<R : Rule + ?Sized> Rule for Box<R> {
+    fn next_state(&self, radius : &[usize]) -> usize {
+        return (**self).next_state(radius);
+    }
+
+    fn get_name(&self) -> String {
+        return (**self).get_name();
+    }
+
+    fn get_outer_totalistic_code(&self) -> Option<usize> {
+        return (**self).get_outer_totalistic_code();
+    }
+}
+
+/// Totalistic rule : the next state is f(n), where n is the sum of all nine cells.
+pub struct Totalistic {
+    pub code : usize
+}
+
+impl Totalistic {
+    pub fn new(code : usize) -> Totalistic {
+        return Totalistic { code : code };
+    }
+}
+
+impl Rule for Totalistic {
+    fn next_state(&self, radius : &[usize]) -> usize {
+        let n : usize = radius.iter().sum();
+        return rule_lookup_totalistic(n as u8, self.code);
+    }
+
+    fn get_name(&self) -> String {
+        return format!("totalistic:{}", self.code);
+    }
+
+    /// f(a, n) = f(a + n).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::rule::{Rule, Totalistic};
+    ///
+    /// // f(3) = 1 : f(0, 3) = f(1, 2) = 1, bits 6 and 5
+    /// assert_eq!(Totalistic::new(8).get_outer_totalistic_code(), Some(0b1100000));
+    /// ```
+    fn get_outer_totalistic_code(&self) -> Option<usize> {
+        let mut code : usize = 0;
+
+        for n in 0..9 {
+            for a in 0..2 {
+                code |= rule_lookup_totalistic((n + a) as u8, self.code) << (2 * n + a);
+            }
+        }
+
+        return Some(code);
+    }
+}
+
+/// Outer totalistic rule : the next state is f(a, n), where a is the cell itself and n is
+/// the sum of its neighbours.
+pub struct OuterTotalistic {
+    pub code : usize
+}
+
+impl OuterTotalistic {
+    pub fn new(code : usize) -> OuterTotalistic {
+        return OuterTotalistic { code : code };
+    }
+
+    /// Parses a birth / survival rulestring such as `B3/S23`.
+    ///
+    /// Birth on n neighbours sets f(0, n), survival on n neighbours sets f(1, n). The
+    /// survival / birth notation `23/3` is also accepted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::rule::OuterTotalistic;
+    ///
+    /// assert_eq!(OuterTotalistic::from_rulestring("B3/S23").unwrap().code, 224);
+    /// assert_eq!(OuterTotalistic::from_rulestring("23/3").unwrap().code, 224);
+    /// assert_eq!(OuterTotalistic::new(224).get_rulestring(), "B3/S23");
+    /// ```
+    pub fn from_rulestring(rulestring : &str) -> Result<OuterTotalistic, Error> {
+        let rulestring : String = rulestring.trim().to_uppercase();
+
+        let (birth, survival) : (&str, &str) = if rulestring.starts_with('B') {
+            // B{digits}/S{digits}, the slash is optional
+            let survival_index : usize = rulestring.find('S')
+                .ok_or_else(|| Error::Parse(format!("missing survival in rulestring {}", rulestring)))?;
+            (rulestring[1..survival_index].trim_end_matches('/'), &rulestring[(survival_index + 1)..])
+        } else {
+            // {survival}/{birth}
+            let mut parts = rulestring.split('/');
+            let survival : &str = parts.next().unwrap_or("");
+            let birth : &str = parts.next()
+                .ok_or_else(|| Error::Parse(format!("missing birth in rulestring {}", rulestring)))?;
+            (birth, survival)
+        };
+
+        let mut code : usize = 0;
+
+        for (a, digits) in [(0, birth), (1, survival)].iter() {
+            for digit in digits.chars() {
+                let n : usize = digit.to_digit(10)
+                    .filter(|n| *n <= 8)
+                    .ok_or_else(|| Error::Parse(format!("invalid neighbour count {} in rulestring {}", digit, rulestring)))? as usize;
+                code |= 1 << (2 * n + a);
+            }
+        }
+
+        return Ok(OuterTotalistic::new(code));
+    }
+
+    /// Returns the birth / survival rulestring of the code, e.g. `B3/S23` for 224.
+    pub fn get_rulestring(&self) -> String {
+        let mut birth : String = String::from("B");
+        let mut survival : String = String::from("S");
+
+        for n in 0..9 {
+            if rule_lookup_outer_totalistic(0, n, self.code) == 1 {
+                birth.push_str(&n.to_string());
+            }
+            if rule_lookup_outer_totalistic(1, n, self.code) == 1 {
+                survival.push_str(&n.to_string());
+            }
+        }
+
+        return format!("{}/{}", birth, survival);
+    }
+}
+
+impl Rule for OuterTotalistic {
+    fn next_state(&self, radius : &[usize]) -> usize {
+        let a : u8 = radius[4] as u8;
+        let n : usize = radius.iter().sum();
+        return rule_lookup_outer_totalistic(a, n as u8 - a, self.code);
+    }
+
+    fn get_name(&self) -> String {
+        return format!("outer_totalistic:{}", self.code);
+    }
+
+    fn get_outer_totalistic_code(&self) -> Option<usize> {
+        return Some(self.code);
+    }
+}
+
+/// Rule backed by a closure, for experimental rules defined outside of this crate.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::rule::{Rule, ClosureRule};
+///
+/// // a cell is alive iff its north neighbour was alive
+/// let shift = ClosureRule::new("shift", |radius : &[usize]| radius[1]);
+/// assert_eq!(shift.next_state(&[0, 1, 0, 0, 0, 0, 0, 0, 0]), 1);
+/// ```
+pub struct ClosureRule<F : Fn(&[usize]) -> usize> {
+    name : String,
+    function : F
+}
+
+impl<F : Fn(&[usize]) -> usize> ClosureRule<F> {
+    pub fn new(name : &str, function : F) -> ClosureRule<F> {
+        return ClosureRule {
+            name : name.to_string(),
+            function : function
+        };
+    }
+}
+
+impl<F : Fn(&[usize]) -> usize> Rule for ClosureRule<F> {
+    fn next_state(&self, radius : &[usize]) -> usize {
+        return (self.function)(radius);
+    }
+
+    fn get_name(&self) -> String {
+        return self.name.clone();
+    }
+}
+
+/// Returns the component ~f(a, n) of an outer totalistic code.
+///
+/// # Arguments
+///
+/// * `a` : 0 or 1
+/// * `n` : 0, 1, ..., 8
+///
+/// e.g., (outer) code = 224 -> f(1,2), f(0,3), f(1,3) = 1.
+fn rule_lookup_outer_totalistic(a : u8, n : u8, code : usize) -> usize {
+
+    let shift = 2 * n + a;
+    let mask = 1 << shift;
+
+    // println!("code = {:#020b}", code);
+    // println!("mask = {:#020b}", mask);
+
+    return (mask & code) >> shift;
+}
+
+/// Returns the component f(n) of a totalistic code.
+///
+/// # Arguments
+///
+/// * `n` : 0, 1, ..., 9
+///
+/// e.g., code = 8 -> f(3) = 1.
+fn rule_lookup_totalistic(n : u8, code : usize) -> usize {
+    return (code & (1 << n)) >> n;
+}
@@ -0,0 +1,321 @@
+// Sparse unbounded universe, for any TCA2D rule.
+//
+// The plane is cut in `TILE_SIZE` x `TILE_SIZE` tiles, kept in a map from tile coordinates to
+// cells; only tiles with a nonzero cell are stored. Activity spreads at most one cell per
+// generation, so a step computes the stored tiles and the neighbours they touch (those next to
+// a nonzero cell on their edge, which each tile keeps as a mask), reading the cells around
+// each tile from its neighbours, and drops the tiles that end up empty.
+//
+// The rule must leave empty space empty : state 0 with every neighbour 0 must stay 0.
+
+use crate::error::Error;
+use crate::grid::Grid;
+use crate::rule::Rule;
+
+use std::collections::{HashMap, HashSet};
+
+/// Width and height of a tile.
+pub const TILE_SIZE : usize = 32;
+
+// the (row, column) offsets of the nine cells of a neighbourhood, in compass order.
+const OFFSETS : [(i64, i64); 9] = [(-1, -1), (-1, 0), (-1, 1), (0, -1), (0, 0), (0, 1), (1, -1), (1, 0), (1, 1)];
+
+#[derive(Clone)]
+struct Tile {
+    cells : Vec<usize>,
+    population : usize, // nonzero cells
+    edges : u16 // bit `direction` set if a nonzero cell faces the neighbour at OFFSETS[direction]
+}
+
+/// A two dimensional automaton on an unbounded plane, stored as tiles allocated on demand.
+pub struct SparseUniverse<R : Rule> {
+    rule : R,
+    moore : bool,
+    tiles : HashMap<(i64, i64), Tile>, // (tile row, tile column) -> cells
+    generation : u64
+}
+
+impl<R : Rule> SparseUniverse<R> {
+
+    /// Returns an empty universe; `rule` must keep an empty neighbourhood empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::rule::{OuterTotalistic, Totalistic};
+    /// use gol_rs::sparse::SparseUniverse;
+    ///
+    /// assert!(SparseUniverse::new(OuterTotalistic::new(224), true).is_ok());
+    ///
+    /// // f(0) = 1 : every empty cell is born
+    /// assert!(SparseUniverse::new(Totalistic::new(1), true).is_err());
+    /// ```
+    pub fn new(rule : R, moore : bool) -> Result<SparseUniverse<R>, Error> {
+        if rule.next_state(&[0; 9]) != 0 {
+            return Err(Error::UnsupportedBackend(format!("{} turns empty space alive", rule.get_name())));
+        }
+
+        return Ok(SparseUniverse {
+            rule : rule,
+            moore : moore,
+            tiles : HashMap::new(),
+            generation : 0
+        });
+    }
+
+    pub fn get_rule(&self) -> &R {
+        return &self.rule;
+    }
+
+    pub fn get_generation(&self) -> u64 {
+        return self.generation;
+    }
+
+    /// Returns the number of tiles in memory.
+    pub fn get_tile_count(&self) -> usize {
+        return self.tiles.len();
+    }
+
+    /// Returns the number of nonzero cells.
+    pub fn get_population(&self) -> usize {
+        return self.tiles.values().map(|tile| tile.population).sum();
+    }
+
+    pub fn get_value(&self, row_index : i64, column_index : i64) -> usize {
+        let (key, index) : ((i64, i64), usize) = locate(row_index, column_index);
+
+        return self.tiles.get(&key).map_or(0, |tile| tile.cells[index]);
+    }
+
+    /// Sets a cell, allocating its tile if needed, or dropping it once empty.
+    pub fn set_value(&mut self, row_index : i64, column_index : i64, value : usize) {
+        let (key, index) : ((i64, i64), usize) = locate(row_index, column_index);
+
+        if value == 0 && !self.tiles.contains_key(&key) {
+            return;
+        }
+
+        let tile : &mut Tile = self.tiles.entry(key).or_insert_with(Tile::new);
+        let was_alive : bool = tile.cells[index] != 0;
+
+        tile.population = tile.population + (value != 0) as usize - was_alive as usize;
+        tile.cells[index] = value;
+
+        let edges : u16 = get_edges(index / TILE_SIZE, index % TILE_SIZE);
+        if value != 0 {
+            tile.edges |= edges;
+        } else if was_alive && edges != 1 << 4 {
+            tile.update_edges();
+        }
+
+        if tile.population == 0 {
+            self.tiles.remove(&key);
+        }
+    }
+
+    /// Sets every cell to 0 and drops every tile.
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+    }
+
+    /// Writes the cells of `grid` with its top left cell at (`row_index`, `column_index`).
+    pub fn import_grid(&mut self, grid : &Grid, row_index : i64, column_index : i64) {
+        for grid_row_index in 0..grid.get_height() {
+            for grid_column_index in 0..grid.get_width() {
+                self.set_value(row_index + grid_row_index as i64,
+                    column_index + grid_column_index as i64,
+                    grid.get_value(grid_row_index, grid_column_index));
+            }
+        }
+    }
+
+    /// Returns the `width` x `height` region whose top left cell is (`row_index`,
+    /// `column_index`).
+    pub fn export_grid(&self, row_index : i64, column_index : i64, width : usize, height : usize) -> Result<Grid, Error> {
+        let mut grid : Grid = Grid::new(width, height)?;
+
+        for grid_row_index in 0..height {
+            for grid_column_index in 0..width {
+                grid.set_value(grid_row_index, grid_column_index,
+                    self.get_value(row_index + grid_row_index as i64, column_index + grid_column_index as i64));
+            }
+        }
+
+        return Ok(grid);
+    }
+
+    /// Returns the smallest rectangle holding every nonzero cell, as (min row, min column,
+    /// max row, max column), or None for an empty universe.
+    pub fn get_bounding_box(&self) -> Option<(i64, i64, i64, i64)> {
+        let size : i64 = TILE_SIZE as i64;
+        let mut bounding_box : Option<(i64, i64, i64, i64)> = None;
+
+        for ((tile_row, tile_column), tile) in self.tiles.iter() {
+            for (index, cell) in tile.cells.iter().enumerate() {
+                if *cell == 0 {
+                    continue;
+                }
+
+                let row_index : i64 = tile_row * size + (index / TILE_SIZE) as i64;
+                let column_index : i64 = tile_column * size + (index % TILE_SIZE) as i64;
+
+                bounding_box = Some(match bounding_box {
+                    Some((min_row, min_column, max_row, max_column)) =>
+                        (min_row.min(row_index), min_column.min(column_index), max_row.max(row_index), max_column.max(column_index)),
+                    None => (row_index, column_index, row_index, column_index)
+                });
+            }
+        }
+
+        return bounding_box;
+    }
+
+    /// Advances the universe `generations` generations.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::Grid;
+    /// use gol_rs::rule::OuterTotalistic;
+    /// use gol_rs::sparse::SparseUniverse;
+    ///
+    /// let glider = Grid::from_vec(3, 3, vec![
+    ///     0, 1, 0,
+    ///     0, 0, 1,
+    ///     1, 1, 1]).unwrap();
+    ///
+    /// let mut life = SparseUniverse::new(OuterTotalistic::new(224), true).unwrap();
+    /// life.import_grid(&glider, 0, 0);
+    /// life.step(400); // the glider crosses several tiles
+    ///
+    /// assert_eq!(life.get_population(), 5);
+    /// assert_eq!(life.get_bounding_box(), Some((100, 100, 102, 102)));
+    /// assert_eq!(life.export_grid(100, 100, 3, 3).unwrap().get_grid(), glider.get_grid());
+    /// assert!(life.get_tile_count() <= 4);
+    /// ```
+    pub fn step(&mut self, generations : usize) {
+        for _ in 0..generations {
+            let mut active = HashSet::<(i64, i64)>::new();
+            for ((tile_row, tile_column), tile) in self.tiles.iter() {
+                for (direction, (row_offset, column_offset)) in OFFSETS.iter().enumerate() {
+                    if tile.edges & 1 << direction != 0 {
+                        active.insert((tile_row + row_offset, tile_column + column_offset));
+                    }
+                }
+            }
+
+            let mut tiles = HashMap::<(i64, i64), Tile>::with_capacity(active.len());
+            for key in active {
+                let tile : Tile = self.get_next_tile(key);
+                if tile.population > 0 {
+                    tiles.insert(key, tile);
+                }
+            }
+
+            self.tiles = tiles;
+            self.generation += 1;
+        }
+    }
+
+    // the next generation of the tile at `key`.
+    fn get_next_tile(&self, key : (i64, i64)) -> Tile {
+        let size : i64 = TILE_SIZE as i64;
+        let halo_size : usize = TILE_SIZE + 2;
+
+        // the tile and the cells around it, from its eight neighbours
+        let mut neighbours : [Option<&Tile>; 9] = [None; 9];
+        for (neighbour, (row_offset, column_offset)) in neighbours.iter_mut().zip(OFFSETS.iter()) {
+            *neighbour = self.tiles.get(&(key.0 + row_offset, key.1 + column_offset));
+        }
+
+        let mut halo : Vec<usize> = vec![0; halo_size * halo_size];
+        for halo_row in 0..halo_size {
+            for halo_column in 0..halo_size {
+                let (row_index, column_index) : (i64, i64) = (halo_row as i64 - 1, halo_column as i64 - 1);
+                let neighbour : usize = (3 * (row_index.div_euclid(size) + 1) + column_index.div_euclid(size) + 1) as usize;
+
+                if let Some(tile) = neighbours[neighbour] {
+                    let index : usize = (row_index.rem_euclid(size) * size + column_index.rem_euclid(size)) as usize;
+                    halo[halo_row * halo_size + halo_column] = tile.cells[index];
+                }
+            }
+        }
+
+        let mut next = Tile::new();
+
+        let mut radius : [usize; 9] = [0; 9];
+        for row_index in 0..TILE_SIZE {
+            for column_index in 0..TILE_SIZE {
+                for (direction, (row_offset, column_offset)) in OFFSETS.iter().enumerate() {
+                    // the diagonals are inactive in a von Neumann neighbourhood
+                    let active : bool = self.moore || *row_offset == 0 || *column_offset == 0;
+                    let halo_index : usize = (row_index as i64 + 1 + row_offset) as usize * halo_size + (column_index as i64 + 1 + column_offset) as usize;
+                    radius[direction] = if active { halo[halo_index] } else { 0 };
+                }
+
+                let cell : usize = self.rule.next_state(&radius);
+                if cell != 0 {
+                    next.cells[row_index * TILE_SIZE + column_index] = cell;
+                    next.population += 1;
+                    next.edges |= get_edges(row_index, column_index);
+                }
+            }
+        }
+
+        return next;
+    }
+}
+
+impl Tile {
+
+    fn new() -> Tile {
+        return Tile {
+            cells : vec![0; TILE_SIZE * TILE_SIZE],
+            population : 0,
+            edges : 0
+        };
+    }
+
+    // recomputes `edges` from the cells on the border.
+    fn update_edges(&mut self) {
+        let last : usize = TILE_SIZE - 1;
+        self.edges = 0;
+
+        for index in 0..TILE_SIZE {
+            for (row_index, column_index) in [(0, index), (last, index), (index, 0), (index, last)].iter().copied() {
+                if self.cells[row_index * TILE_SIZE + column_index] != 0 {
+                    self.edges |= get_edges(row_index, column_index);
+                }
+            }
+        }
+
+        if self.population > 0 {
+            self.edges |= 1 << 4;
+        }
+    }
+}
+
+// the neighbours a nonzero cell at (`row_index`, `column_index`) of a tile can reach next
+// generation, as a mask over OFFSETS; every cell reaches its own tile.
+fn get_edges(row_index : usize, column_index : usize) -> u16 {
+    let last : usize = TILE_SIZE - 1;
+    let rows : [bool; 3] = [row_index == 0, true, row_index == last];
+    let columns : [bool; 3] = [column_index == 0, true, column_index == last];
+
+    let mut edges : u16 = 0;
+    for (direction, (row_offset, column_offset)) in OFFSETS.iter().enumerate() {
+        if rows[(row_offset + 1) as usize] && columns[(column_offset + 1) as usize] {
+            edges |= 1 << direction;
+        }
+    }
+
+    return edges;
+}
+
+// the tile holding a cell, and the index of the cell in it.
+fn locate(row_index : i64, column_index : i64) -> ((i64, i64), usize) {
+    let size : i64 = TILE_SIZE as i64;
+    let key : (i64, i64) = (row_index.div_euclid(size), column_index.div_euclid(size));
+
+    return (key, (row_index.rem_euclid(size) * size + column_index.rem_euclid(size)) as usize);
+}
@@ -0,0 +1,374 @@
+// Headless rule-space sweeps for TCA2D.
+//
+// Every (code, kind, neighbourhood, seed) combination is a job. Jobs are shared between worker
+// threads; each finished job is written to the output as one line, as soon as it is done, so
+// that an interrupted sweep can be resumed by skipping the jobs already in the file.
+
+use crate::boundary::BoundaryType;
+use crate::cycle::Cycle;
+use crate::error::Error;
+use crate::grid::{Grid, Symmetry};
+use crate::rule::{Rule, Totalistic, OuterTotalistic};
+use crate::tca2d::{Backend, TCA2D};
+
+use std::collections::HashSet;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RuleKind {
+    Totalistic,
+    OuterTotalistic,
+}
+
+impl RuleKind {
+    pub fn get_name(&self) -> &'static str {
+        return match self {
+            RuleKind::Totalistic => "totalistic",
+            RuleKind::OuterTotalistic => "outer_totalistic",
+        };
+    }
+
+    pub fn get_rule(&self, code : usize) -> Box<dyn Rule + Sync> {
+        return match self {
+            RuleKind::Totalistic => Box::new(Totalistic::new(code)),
+            RuleKind::OuterTotalistic => Box::new(OuterTotalistic::new(code)),
+        };
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Csv,
+    /// one json object per line.
+    Json,
+}
+
+/// Which rules to run, on which soups, for how long.
+pub struct SweepConfiguration {
+    pub codes : Vec<usize>,
+    pub kinds : Vec<RuleKind>,
+    pub neighbourhoods : Vec<bool>, // moore
+    pub seeds : Vec<u64>,
+    pub width : usize,
+    pub height : usize,
+    pub density : f64,
+    pub symmetry : Symmetry,
+    pub generations : usize,
+    pub threads : usize,
+}
+
+/// Statistics of the last generation of one job.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepResult {
+    pub code : usize,
+    pub kind : RuleKind,
+    pub moore : bool,
+    pub seed : u64,
+    pub generations : usize, // number of generations actually run
+    pub population : usize,
+    pub density : f64,
+    pub components : usize,
+    pub holes : usize,
+    pub transient : Option<usize>, // generation the cycle began
+    pub period : Option<usize>,
+}
+
+const CSV_HEADER : &str = "code,kind,moore,seed,generations,population,density,components,holes,transient,period";
+
+impl SweepResult {
+
+    pub fn get_csv_header() -> &'static str {
+        return CSV_HEADER;
+    }
+
+    pub fn get_csv_row(&self) -> String {
+        let optional = |value : Option<usize>| value.map_or(String::new(), |value| value.to_string());
+
+        return format!("{},{},{},{},{},{},{:.6},{},{},{},{}",
+            self.code,
+            self.kind.get_name(),
+            self.moore,
+            self.seed,
+            self.generations,
+            self.population,
+            self.density,
+            self.components,
+            self.holes,
+            optional(self.transient),
+            optional(self.period));
+    }
+
+    pub fn get_json(&self) -> String {
+        let optional = |value : Option<usize>| value.map_or(String::from("null"), |value| value.to_string());
+
+        return format!("{{\"code\":{},\"kind\":\"{}\",\"moore\":{},\"seed\":{},\"generations\":{},\"population\":{},\"density\":{:.6},\"components\":{},\"holes\":{},\"transient\":{},\"period\":{}}}",
+            self.code,
+            self.kind.get_name(),
+            self.moore,
+            self.seed,
+            self.generations,
+            self.population,
+            self.density,
+            self.components,
+            self.holes,
+            optional(self.transient),
+            optional(self.period));
+    }
+}
+
+fn get_key(code : usize, kind : &str, moore : bool, seed : u64) -> String {
+    return format!("{}|{}|{}|{}", code, kind, moore, seed);
+}
+
+// one (code, kind, neighbourhood, seed) combination.
+struct Job {
+    code : usize,
+    kind : RuleKind,
+    moore : bool,
+    seed : u64,
+}
+
+/// Runs `configuration.generations` generations (or until the grid cycles) of one rule on the
+/// soup given by `seed`, with a null boundary.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::grid::Symmetry;
+/// use gol_rs::sweep::{self, RuleKind, SweepConfiguration};
+///
+/// let configuration = SweepConfiguration {
+///     codes : vec![0],
+///     kinds : vec![RuleKind::OuterTotalistic],
+///     neighbourhoods : vec![true],
+///     seeds : vec![1],
+///     width : 32,
+///     height : 32,
+///     density : 0.5,
+///     symmetry : Symmetry::Asymmetric,
+///     generations : 100,
+///     threads : 1,
+/// };
+///
+/// // nothing survives rule 0
+/// let result = sweep::run_rule(&configuration, 0, RuleKind::OuterTotalistic, true, 1).unwrap();
+/// assert_eq!(result.population, 0);
+/// assert_eq!(result.period, Some(1));
+/// ```
+pub fn run_rule(configuration : &SweepConfiguration,
+    code : usize,
+    kind : RuleKind,
+    moore : bool,
+    seed : u64) -> Result<SweepResult, Error> {
+
+    let width : usize = configuration.width;
+    let height : usize = configuration.height;
+
+    let soup : Grid = Grid::random_symmetric(width, height, configuration.density, configuration.symmetry, seed)?;
+    let mut gol = TCA2D::with_backend(kind.get_rule(code), width, height, 1, moore, BoundaryType::Null, soup, Backend::BitSliced)?;
+
+    let cycle : Option<Cycle> = gol.step_until_cycle(configuration.generations);
+    let generation : usize = cycle.map_or(configuration.generations, |cycle| cycle.get_detected());
+
+    let page : &Grid = gol.get_page(0);
+    let (components, holes) : (usize, usize) = page.get_betti_numbers();
+
+    return Ok(SweepResult {
+        code : code,
+        kind : kind,
+        moore : moore,
+        seed : seed,
+        generations : generation,
+        population : page.get_population(),
+        density : page.get_density(),
+        components : components,
+        holes : holes,
+        transient : cycle.map(|cycle| cycle.start),
+        period : cycle.map(|cycle| cycle.period),
+    });
+}
+
+/// Runs every job of `configuration` on `configuration.threads` threads, and writes one line
+/// per job to `output`, in the order the jobs finish.
+///
+/// With `resume`, jobs already in `output` are skipped and new lines are appended; otherwise
+/// `output` is overwritten. Returns the number of jobs run.
+pub fn run_sweep(configuration : &SweepConfiguration,
+    output : &str,
+    format : OutputFormat,
+    resume : bool) -> Result<usize, Box<dyn std::error::Error>> {
+
+    let contents : String = if resume {
+        read_previous(output)?
+    } else {
+        String::new()
+    };
+    let previous : &str = get_complete_lines(&contents);
+    let done : HashSet<String> = get_keys(previous, format);
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resume)
+        .truncate(!resume)
+        .open(output)?;
+
+    if previous.len() < contents.len() {
+        // drops the last line, cut short by an interruption; its job runs again
+        file.set_len(previous.len() as u64)?;
+    }
+
+    if format == OutputFormat::Csv && previous.is_empty() {
+        writeln!(file, "{}", CSV_HEADER)?;
+    }
+
+    return run_jobs(configuration, &done, |result| {
+        let line : String = match format {
+            OutputFormat::Csv => result.get_csv_row(),
+            OutputFormat::Json => result.get_json(),
+        };
+        writeln!(file, "{}", line)?;
+        file.flush()?;
+
+        return Ok(());
+    });
+}
+
+/// Runs every job of `configuration` and returns the results, in the order of the jobs.
+pub fn collect_sweep(configuration : &SweepConfiguration) -> Result<Vec<SweepResult>, Box<dyn std::error::Error>> {
+    let mut results : Vec<SweepResult> = Vec::new();
+
+    run_jobs(configuration, &HashSet::new(), |result| {
+        results.push(result.clone());
+        return Ok(());
+    })?;
+
+    let order = |result : &SweepResult| {
+        (result.code, result.kind == RuleKind::OuterTotalistic, result.moore, result.seed)
+    };
+    results.sort_by_key(order);
+
+    return Ok(results);
+}
+
+// runs every job whose key isn't in `done`; `sink` is called on this thread.
+fn run_jobs<F>(configuration : &SweepConfiguration,
+    done : &HashSet<String>,
+    mut sink : F) -> Result<usize, Box<dyn std::error::Error>>
+    where F : FnMut(&SweepResult) -> Result<(), Box<dyn std::error::Error>> {
+
+    let mut jobs : Vec<Job> = Vec::new();
+    for code in &configuration.codes {
+        for kind in &configuration.kinds {
+            for moore in &configuration.neighbourhoods {
+                for seed in &configuration.seeds {
+                    if !done.contains(&get_key(*code, kind.get_name(), *moore, *seed)) {
+                        jobs.push(Job { code : *code, kind : *kind, moore : *moore, seed : *seed });
+                    }
+                }
+            }
+        }
+    }
+
+    let next_job = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel::<Result<SweepResult, Error>>();
+    let threads : usize = configuration.threads.max(1).min(jobs.len().max(1));
+
+    return thread::scope(|scope| {
+        for _ in 0..threads {
+            let sender = sender.clone();
+            let jobs = &jobs;
+            let next_job = &next_job;
+
+            scope.spawn(move || {
+                loop {
+                    let job_index : usize = next_job.fetch_add(1, Ordering::Relaxed);
+                    if job_index >= jobs.len() {
+                        break;
+                    }
+
+                    let job : &Job = &jobs[job_index];
+                    let result = run_rule(configuration, job.code, job.kind, job.moore, job.seed);
+
+                    if sender.send(result).is_err() {
+                        break; // the sink failed; stop
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        let mut count : usize = 0;
+        for result in receiver {
+            let result : SweepResult = result?;
+            sink(&result)?;
+            count += 1;
+        }
+
+        return Ok(count);
+    });
+}
+
+// contents of a previous run; a missing file is empty.
+fn read_previous(output : &str) -> Result<String, std::io::Error> {
+    return match std::fs::read_to_string(output) {
+        Ok(contents) => Ok(contents),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e),
+    };
+}
+
+// the lines of `contents` up to its last '\n'; anything after it was cut short.
+fn get_complete_lines(contents : &str) -> &str {
+    return contents.rfind('\n').map_or("", |end| &contents[..=end]);
+}
+
+// keys of the jobs already written by a previous run.
+fn get_keys(contents : &str, format : OutputFormat) -> HashSet<String> {
+    let mut keys = HashSet::<String>::new();
+
+    for line in contents.lines() {
+        if line.is_empty() || line == CSV_HEADER {
+            continue;
+        }
+
+        // a malformed line doesn't count
+        let key : Option<String> = match format {
+            OutputFormat::Csv => {
+                let fields : Vec<&str> = line.split(',').collect();
+                if fields.len() == CSV_HEADER.split(',').count() {
+                    Some(format!("{}|{}|{}|{}", fields[0], fields[1], fields[2], fields[3]))
+                } else {
+                    None
+                }
+            },
+            OutputFormat::Json => {
+                if line.ends_with('}') {
+                    let field = |name : &str| get_json_field(line, name).unwrap_or_default();
+                    Some(format!("{}|{}|{}|{}", field("code"), field("kind"), field("moore"), field("seed")))
+                } else {
+                    None
+                }
+            },
+        };
+
+        if let Some(key) = key {
+            keys.insert(key);
+        }
+    }
+
+    return keys;
+}
+
+// returns the raw value of `"name":value` in a flat json object, without quotes.
+fn get_json_field(line : &str, name : &str) -> Option<String> {
+    let pattern : String = format!("\"{}\":", name);
+    let start : usize = line.find(&pattern)? + pattern.len();
+    let end : usize = line[start..].find([',', '}']).map(|end| start + end)?;
+
+    return Some(line[start..end].trim_matches('"').to_string());
+}
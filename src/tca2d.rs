@@ -1,41 +1,194 @@
 use crate::boundary::{Boundary, BoundaryType};
 use crate::compass_direction::CompassDirection;
+use crate::cycle::{Cycle, CycleDetector};
+use crate::error::Error;
 use crate::grid::Grid;
+use crate::pattern_io::Pattern;
+use crate::patterns;
+use crate::rule::Rule;
+use crate::tca2d_packed::{self, PackedPage};
+use crate::viewer;
 
 extern crate minifb;
 use minifb::{Key, WindowOptions, Window, Scale};
 
 use std::fmt;
-use std::fmt::Write as FmtWrite;
-
+use std::mem;
+use std::str::FromStr;
+use std::sync::{Barrier, RwLock};
 use std::thread;
-use std::time::{Duration}; // timing
 
+/// How the generations of a TCA2D are computed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backend {
+    /// one cell at a time, through `Rule::next_state`; runs any rule on any boundary.
+    Reference,
+    /// 64 cells at a time (see `tca2d_packed`); runs 2 state rules that have an outer
+    /// totalistic code, on null and torus boundaries.
+    BitSliced,
+}
+
+impl FromStr for Backend {
+    type Err = Error;
+
+    /// Parses `reference` or `bit-sliced`.
+    fn from_str(backend : &str) -> Result<Backend, Error> {
+        return match backend.to_lowercase().as_str() {
+            "reference" => Ok(Backend::Reference),
+            "bit-sliced" | "bitsliced" => Ok(Backend::BitSliced),
+            _ => Err(Error::Parse(format!("unknown backend {}", backend)))
+        };
+    }
+}
+
+/// How `TCA2D::insert_at` combines an inserted cell with the cell it covers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlendMode {
+    /// the inserted cell replaces the page cell.
+    Overwrite,
+    /// bitwise or of both states; for two states, a cell is alive if either is.
+    Or,
+    /// bitwise xor of both states; inserting the same cells twice restores the page.
+    Xor,
+    /// nonzero inserted cells replace the page cell, zeros leave it as it is.
+    IgnoreZeros,
+}
+
+impl FromStr for BlendMode {
+    type Err = Error;
+
+    /// Parses `overwrite`, `or`, `xor` or `ignore-zeros`.
+    fn from_str(blend : &str) -> Result<BlendMode, Error> {
+        return match blend.to_lowercase().as_str() {
+            "overwrite" => Ok(BlendMode::Overwrite),
+            "or" => Ok(BlendMode::Or),
+            "xor" => Ok(BlendMode::Xor),
+            "ignore-zeros" | "ignorezeros" => Ok(BlendMode::IgnoreZeros),
+            _ => Err(Error::Parse(format!("unknown blend mode {}", blend)))
+        };
+    }
+}
+
+/// What `TCA2D::insert_at` placed.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Placement {
+    pub written : usize, // cells written to the page, zeros skipped by IgnoreZeros excepted
+    pub wrapped : usize, // of which, cells written across an edge
+    pub clipped : usize, // cells dropped past a null edge, counted like `written`
+    pub changed : usize // page cells whose value changed
+}
 
-// todo : moore and outer_totalistic don't need to be part of the class;
-//      make part of the methods that use them.
-pub struct TCA2D {
-    rule : usize,
+/// Two dimensional cellular automaton : a stack of `depth` pages, each a `width` x `height`
+/// grid, where page `i` is the generation following page `i - 1`.
+///
+/// Generations are written into a preallocated scratch page which is then swapped with the
+/// page it replaces, so stepping never allocates a page. The bit-sliced backend steps between
+/// two preallocated packed pages, and unpacks only the generations kept in the stack.
+// todo : moore doesn't need to be part of the class;
+//      make part of the methods that use it.
+pub struct TCA2D<R : Rule> {
+    rule : R,
     width : usize,
     height : usize,
     depth : usize,
-    moore : bool, // only needed to generate compass
-    outer_totalistic : bool, // no : is totalistic.
+    moore : bool, // needed to generate compass, and by the bit-sliced backend
     compass : Vec<CompassDirection>, // "constant"
     boundary : Boundary, // type to use for calculations
-    universe : Vec<Grid>
+    universe : Vec<Grid>,
+    scratch : Grid, // the next generation is written here, then swapped into the universe
+    backend : Backend,
+    packed : Option<(PackedPage, PackedPage)> // bit-sliced : the current generation and the next
 }
 
-impl TCA2D { // totalistic cellular automata : 2 dimensional
+impl<R : Rule> TCA2D<R> { // totalistic cellular automata : 2 dimensional
 
-    pub fn new(rule : usize,
+    /// Returns a new automaton whose first page is `initial_configuration`.
+    ///
+    /// # Arguments
+    ///
+    /// * `rule` - maps each neighbourhood to the next state.
+    /// * `depth` - number of pages kept in memory; at least 1.
+    /// * `moore` - use the Moore (eight neighbours) or von Neumann (four neighbours) neighbourhood.
+    /// * `initial_configuration` - a `width` x `height` grid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{Grid, TCA2D, BoundaryType};
+    /// use gol_rs::rule::OuterTotalistic;
+    ///
+    /// let mut blinker = Grid::new(5, 5).unwrap();
+    /// for column_index in 1..4 {
+    ///     blinker.set_value(2, column_index, 1);
+    /// }
+    ///
+    /// let mut gol = TCA2D::new(OuterTotalistic::new(224), 5, 5, 2, true, BoundaryType::Null, blinker).unwrap();
+    /// gol.generate();
+    /// assert_eq!(gol.get_value(1, 1, 2), 1);
+    /// assert_eq!(gol.get_value(1, 2, 1), 0);
+    /// ```
+    pub fn new(rule : R,
         width : usize,
         height : usize,
         depth : usize,
-        outer_totalistic : bool,
         moore : bool,
         boundary_type : BoundaryType,
-        initial_configuration : Grid) -> TCA2D {
+        initial_configuration : Grid) -> Result<TCA2D<R>, Error> {
+
+        return TCA2D::with_backend(rule, width, height, depth, moore, boundary_type, initial_configuration, Backend::Reference);
+    }
+
+    /// Returns a new automaton, like `new`, whose generations are computed by `backend`.
+    ///
+    /// The bit-sliced backend gives the same generations as the reference one, faster; it
+    /// returns an error for rules, boundaries or configurations it can't run.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, Grid, TCA2D};
+    /// use gol_rs::rule::{ClosureRule, OuterTotalistic};
+    /// use gol_rs::tca2d::Backend;
+    ///
+    /// let soup = Grid::random(100, 70, 0.5, 1).unwrap();
+    ///
+    /// let mut reference = TCA2D::new(OuterTotalistic::new(224), 100, 70, 20, true, BoundaryType::Torus, soup.clone()).unwrap();
+    /// let mut bit_sliced = TCA2D::with_backend(OuterTotalistic::new(224), 100, 70, 20, true, BoundaryType::Torus,
+    ///     soup.clone(), Backend::BitSliced).unwrap();
+    /// reference.generate();
+    /// bit_sliced.generate();
+    /// assert_eq!(reference.get_page(19).get_grid(), bit_sliced.get_page(19).get_grid());
+    ///
+    /// let shift = ClosureRule::new("shift", |radius : &[usize]| radius[1]);
+    /// assert!(TCA2D::with_backend(shift, 100, 70, 1, true, BoundaryType::Null, soup, Backend::BitSliced).is_err());
+    /// ```
+    pub fn with_backend(rule : R,
+        width : usize,
+        height : usize,
+        depth : usize,
+        moore : bool,
+        boundary_type : BoundaryType,
+        initial_configuration : Grid,
+        backend : Backend) -> Result<TCA2D<R>, Error> {
+
+        if width == 0 || height == 0 {
+            return Err(Error::InvalidDimensions { width : width, height : height });
+        }
+
+        if depth == 0 {
+            return Err(Error::InvalidDepth(depth));
+        }
+
+        if initial_configuration.get_width() != width || initial_configuration.get_height() != height {
+            return Err(Error::ConfigurationMismatch {
+                expected : width * height,
+                found : initial_configuration.get_width() * initial_configuration.get_height()
+            });
+        }
+
+        if backend == Backend::BitSliced {
+            tca2d_packed::check(rule.get_outer_totalistic_code(), boundary_type, &initial_configuration)?;
+        }
 
         // initialize universe with initial configuration
         let mut universe : Vec<Grid> = Vec::<Grid>::new();
@@ -44,12 +197,18 @@ impl TCA2D { // totalistic cellular automata : 2 dimensional
 
         universe.push(initial_configuration); // pass by reference
 
-        for i in 1..depth {
-            let page : Grid = Grid::new(width, height);
+        for _ in 1..depth {
+            let page : Grid = Grid::empty(width, height);
 
             universe.push(page);
         }
 
+        let scratch : Grid = Grid::empty(width, height);
+        let packed : Option<(PackedPage, PackedPage)> = match backend {
+            Backend::BitSliced => Some((PackedPage::new(width, height), PackedPage::new(width, height))),
+            Backend::Reference => None
+        };
+
         // object to use for calculations!
         let boundary = Boundary::new(width, height, boundary_type);
 
@@ -69,17 +228,19 @@ impl TCA2D { // totalistic cellular automata : 2 dimensional
 
         // todo : I can implement extended von_neumann here (in future)
 
-        return TCA2D {
+        return Ok(TCA2D {
             rule : rule,
             width : width,
             height : height,
             depth : depth,
-            moore : moore, // delete
-            outer_totalistic : outer_totalistic,
+            moore : moore,
             compass : compass,
             boundary : boundary,
-            universe : universe
-        };
+            universe : universe,
+            scratch : scratch,
+            backend : backend,
+            packed : packed
+        });
     }
 
     pub fn get_value(&self, page_index : usize, row_index : usize, column_index : usize) -> usize {
@@ -90,166 +251,478 @@ impl TCA2D { // totalistic cellular automata : 2 dimensional
         self.universe[page_index].set_value(row_index, column_index, value);
     }
 
-    //
-    fn get_radius_at_index(&self, page_index : usize, row_index : usize, column_index : usize) -> Vec<usize> {
-        // this returns the *actual* values at the valid grid coordinates.
-        // this will be called for every pixel at every page, so it needs to be efficient.
+    pub fn get_page(&self, page_index : usize) -> &Grid {
+        return &self.universe[page_index];
+    }
 
+    /// Returns page `page_index` as a pattern, with the rulestring of the rule if it has an
+    /// outer totalistic code; see `pattern_io`.
+    pub fn get_pattern(&self, page_index : usize) -> Pattern {
+        return Pattern::with_rule(self.universe[page_index].clone(), &self.rule);
+    }
 
-        // based on boundary_type
-        return match self.boundary.boundary_type {
-            BoundaryType::Null => self.get_null_radius_at_index(page_index, row_index, column_index),
-            _ => Vec::<usize>::new()
-        };
+    /// Replaces page `page_index`; `page` must be `width` x `height`.
+    pub fn set_page(&mut self, page_index : usize, page : Grid) -> Result<(), Error> {
+        if page.get_width() != self.width || page.get_height() != self.height {
+            return Err(Error::ConfigurationMismatch {
+                expected : self.width * self.height,
+                found : page.get_width() * page.get_height()
+            });
+        }
+
+        self.universe[page_index] = page;
 
+        return Ok(());
     }
 
-    fn get_null_radius_at_index(&self,
-        page_index : usize,
-        row_index : usize,
-        column_index : usize) -> Vec<usize> {
-        // should return a vector with nine elements.
+    pub fn get_width(&self) -> usize {
+        return self.width;
+    }
+
+    pub fn get_height(&self) -> usize {
+        return self.height;
+    }
+
+    pub fn get_depth(&self) -> usize {
+        return self.depth;
+    }
+
+    pub fn get_rule(&self) -> &R {
+        return &self.rule;
+    }
+
+    /// Replaces the rule, e.g. with the one a pattern file names; the bit-sliced backend
+    /// returns an error for rules it can't run.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, Grid, TCA2D};
+    /// use gol_rs::pattern_io::rle;
+    /// use gol_rs::rule::OuterTotalistic;
+    ///
+    /// // a HighLife replicator
+    /// let pattern = rle::parse("x = 5, y = 5, rule = B36/S23\n2b3o$bo2bo$o3bo$o2bob$3o!").unwrap();
+    ///
+    /// let mut gol = TCA2D::new(OuterTotalistic::new(224), 5, 5, 1, true, BoundaryType::Null, pattern.cells.clone()).unwrap();
+    /// gol.set_rule(pattern.get_outer_totalistic().unwrap()).unwrap();
+    /// assert_eq!(gol.get_rule().get_rulestring(), "B36/S23");
+    /// ```
+    pub fn set_rule(&mut self, rule : R) -> Result<(), Error> {
+        if self.backend == Backend::BitSliced {
+            tca2d_packed::check(rule.get_outer_totalistic_code(), self.boundary.boundary_type, &self.universe[0])?;
+        }
+
+        self.rule = rule;
 
-        let mut radius : Vec<usize> = vec![0; 9];
+        return Ok(());
+    }
+
+    pub fn get_backend(&self) -> Backend {
+        return self.backend;
+    }
+
+    //
+    fn get_radius_at_index(&self, page : &Grid, row_index : usize, column_index : usize) -> [usize; 9] {
+        // this returns the *actual* values at the valid grid coordinates.
+        // this will be called for every pixel at every page, so it needs to be efficient.
+
+        let mut radius : [usize; 9] = [0; 9];
 
         // note : direction knows moore or von_neumann type!
         for (index, direction) in self.compass.iter().enumerate() {
-            // get valid indices
-            // if negative, value is zero
-            // if nonnegative, access universe
-
-            let mut direction_row_index : isize = -1;
-            let mut direction_column_index : isize = -1;
-
-            if direction.active {
-                direction_row_index =
-                    self.boundary.get_new_row_index(row_index, &direction);
-                direction_column_index =
-                    self.boundary.get_new_column_index(column_index, &direction);
-            } else {
-                // leave row_index, column_index = -1
+            if !direction.active {
+                // cell not active
+                continue;
             }
 
-            // println!("dri:{}, dci:{}", direction_row_index, direction_column_index);
-
-            // todo : maybe rewrite to >= 0?
-            if direction_row_index != -1 && direction_column_index != -1 {
-                // new calculated indices are valid
-                // get the value in universe at the given page
-                radius[index] = self.get_value(
-                    page_index - 1,
-                    direction_row_index as usize,
-                    direction_column_index as usize);
-            } else {
-                // calculated indices are not valid, or cell not active
-                radius[index] = 0;
-            }
+            // the boundary knows where the neighbour is, or what lies outside the grid
+            radius[index] = match self.boundary.get_neighbour(row_index, column_index, direction) {
+                Some((direction_row_index, direction_column_index)) => page.get_value(
+                    direction_row_index,
+                    direction_column_index),
+                None => self.boundary.get_outside_value()
+            };
         }
 
         return radius;
     }
 
+    /// Returns the generation following page `page_index - 1`.
     pub fn get_next_page(&self, page_index : usize) -> Grid {
-
-        // assumes page_index > 0
         // does not increase the page or set the value; you must do that yourself.
+        let mut next_page : Grid = Grid::empty(self.width, self.height);
+        self.write_next_page(page_index, &mut next_page);
 
-        let mut next_page : Grid = Grid::new(self.width, self.height);
+        return next_page;
+    }
 
-        // here is where I need to know totalistic vs outer_totalistic!
-        for row_index in 0..self.height {
-            for column_index in 0..self.width {
-                let mut cell : usize = 0;
+    // writes the generation following page `page_index - 1` into `next_page`.
+    fn write_next_page(&self, page_index : usize, next_page : &mut Grid) {
+        // assumes page_index > 0
 
-                // get radius
-                let radius : Vec<usize> =
-                    self.get_radius_at_index(page_index, row_index, column_index);
+        if let (Backend::BitSliced, Some(code)) = (self.backend, self.rule.get_outer_totalistic_code()) {
+            // only for get_next_page, which has no access to the packed pages; cells other
+            // than 0 count as 1
+            PackedPage::from_grid(&self.universe[page_index - 1])
+                .step(code, self.moore, self.boundary.boundary_type)
+                .write_to(next_page);
 
-                if self.outer_totalistic { // outer_totalistic
-                    let a : u8 = radius[4] as u8;
-                    let n : usize = radius.iter().sum();
+            return;
+        }
 
-                    cell = TCA2D::rule_lookup_outer_totalistic(a, n as u8 - a, self.rule);
-                } else { // totalistic
-                    // totalistic : n = sum of all nine cells
-                    let n : usize = radius.iter().sum();
-                    cell = TCA2D::rule_lookup_totalistic(n as u8, self.rule);
-                }
+        self.write_next_band(&self.universe[page_index - 1], 0, next_page.get_cells_mut());
+    }
 
-                // set value
-                next_page.set_value(row_index, column_index, cell);
-            }
+    // writes the rows of the generation following `page`, from `first_row` on, into `band`.
+    fn write_next_band(&self, page : &Grid, first_row : usize, band : &mut [usize]) {
+        for (index, cell) in band.iter_mut().enumerate() {
+            let radius : [usize; 9] = self.get_radius_at_index(page, first_row + index / self.width, index % self.width);
+            *cell = self.rule.next_state(&radius);
         }
+    }
 
-        return next_page;
+    // takes the scratch page, to write a generation into it.
+    fn take_scratch(&mut self) -> Grid {
+        return mem::replace(&mut self.scratch, Grid::empty(0, 0));
     }
 
+    /// Overwrites page `page_index` with the generation following page `page_index - 1`.
     pub fn increase_generation(&mut self, page_index : usize) {
-        let next_generation : Grid = self.get_next_page(page_index);
-
-        for row_index in 0..self.height {
-            for column_index in 0..self.width {
-                self.set_value(page_index,
-                    row_index,
-                    column_index,
-                    next_generation.get_value(row_index, column_index));
-            }
+        if self.packed.is_some() {
+            self.generate_packed(page_index - 1, page_index..(page_index + 1));
+            return;
+        }
+
+        let mut next_generation : Grid = self.take_scratch();
+        self.write_next_page(page_index, &mut next_generation);
+
+        self.scratch = mem::replace(&mut self.universe[page_index], next_generation);
+    }
+
+    // drops page 0, moves every other page down one and makes `next_generation` the last page.
+    fn push_page(&mut self, next_generation : Grid) {
+        self.universe.rotate_left(1);
+        self.scratch = mem::replace(&mut self.universe[self.depth - 1], next_generation);
+    }
+
+    /// Advances the automaton `generations` generations : each one drops page 0, moves every
+    /// other page down one and computes the last page from the one before it. With a depth
+    /// of 1, page 0 is evolved in place.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, Grid, TCA2D};
+    /// use gol_rs::rule::OuterTotalistic;
+    ///
+    /// let soup = Grid::random(30, 20, 0.5, 7).unwrap();
+    /// let mut stack = TCA2D::new(OuterTotalistic::new(224), 30, 20, 11, true, BoundaryType::Torus, soup.clone()).unwrap();
+    /// stack.generate();
+    ///
+    /// let mut gol = TCA2D::new(OuterTotalistic::new(224), 30, 20, 1, true, BoundaryType::Torus, soup).unwrap();
+    /// gol.step(10);
+    /// assert_eq!(gol.get_page(0).get_grid(), stack.get_page(10).get_grid());
+    ///
+    /// stack.step(3);
+    /// gol.step(3);
+    /// assert_eq!(gol.get_page(0).get_grid(), stack.get_page(10).get_grid());
+    /// ```
+    pub fn step(&mut self, generations : usize) {
+        if self.packed.is_some() {
+            self.step_packed(generations);
+            return;
+        }
+
+        for _ in 0..generations {
+            let mut next_generation : Grid = self.take_scratch();
+            self.write_next_page(self.depth, &mut next_generation);
+            self.push_page(next_generation);
         }
     }
 
-    // This is used to insert a subgrid at a given page
+    /// Writes `subgrid` into a page with its top left cell at (`row_index`, `column_index`),
+    /// combining each cell with the one it covers according to `blend`, and returns what was
+    /// placed.
+    ///
+    /// Cells past an edge are handled according to the boundary : they wrap around the edges
+    /// that are glued together (torus, cylinder, moebius, klein), and are clipped past null
+    /// edges. Past constant and reflective edges they are an error, as are a page index past
+    /// the depth, a subgrid larger than the page along a wrapping edge and, on the bit-sliced
+    /// backend, states above 1; nothing is written then.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, Grid, TCA2D};
+    /// use gol_rs::rule::OuterTotalistic;
+    /// use gol_rs::tca2d::BlendMode;
+    ///
+    /// let block = Grid::from_vec(2, 2, vec![1, 1, 1, 1]).unwrap();
+    ///
+    /// // half the block falls past the right edge : wrapped on a torus, clipped on a null boundary
+    /// let mut torus = TCA2D::new(OuterTotalistic::new(224), 8, 8, 1, true, BoundaryType::Torus, Grid::new(8, 8).unwrap()).unwrap();
+    /// let placement = torus.insert_at(0, 3, 7, &block, BlendMode::Overwrite).unwrap();
+    /// assert_eq!((placement.written, placement.wrapped, placement.clipped), (4, 2, 0));
+    /// assert_eq!(torus.get_value(0, 3, 0), 1);
+    ///
+    /// let mut null = TCA2D::new(OuterTotalistic::new(224), 8, 8, 1, true, BoundaryType::Null, Grid::new(8, 8).unwrap()).unwrap();
+    /// let placement = null.insert_at(0, 3, 7, &block, BlendMode::Overwrite).unwrap();
+    /// assert_eq!((placement.written, placement.wrapped, placement.clipped), (2, 0, 2));
+    ///
+    /// // XOR twice restores the page
+    /// null.insert_at(0, 3, 7, &block, BlendMode::Xor).unwrap();
+    /// assert_eq!(null.get_page(0).get_population(), 0);
+    /// ```
     pub fn insert_at(&mut self,
         page_index : usize,
-        row_index : usize,
-        column_index : usize,
-        width : usize,
-        height : usize,
-        subgrid : Vec<usize>) {
-        // inserts `subgrid` at (page_index, row_index, column_index) of size (width, height)
+        row_index : isize,
+        column_index : isize,
+        subgrid : &Grid,
+        blend : BlendMode) -> Result<Placement, Error> {
 
-        // assuming subgrid is size width * height
+        if page_index >= self.depth {
+            return Err(Error::InvalidPage { page_index : page_index, depth : self.depth });
+        }
 
-        // check if the subgrid fits at the given location
-       if width + column_index <= self.width - 1 && height + row_index <= self.height {
+        if self.backend == Backend::BitSliced {
+            tca2d_packed::check(self.rule.get_outer_totalistic_code(), self.boundary.boundary_type, subgrid)?;
+        }
 
-           // insertion code
-           for sub_row_index in 0..height {
-               for sub_column_index in 0..width {
+        let wraps_rows : bool = self.boundary.boundary_type == BoundaryType::Torus || self.boundary.boundary_type == BoundaryType::Klein;
+        let wraps_columns : bool = wraps_rows || self.boundary.boundary_type == BoundaryType::Cylinder || self.boundary.boundary_type == BoundaryType::Moebius;
+        if (wraps_rows && subgrid.get_height() > self.height) || (wraps_columns && subgrid.get_width() > self.width) {
+            return Err(Error::InvalidDimensions { width : subgrid.get_width(), height : subgrid.get_height() });
+        }
+
+        // where each cell goes, before writing any
+        let mut placement = Placement::default();
+        let mut targets : Vec<(usize, usize, usize)> = Vec::with_capacity(subgrid.get_width() * subgrid.get_height());
 
-                   // self.universe[page_index][(height + row_index + sub_row_index) * self.width +
-                   //      (width + column_index + sub_column_index)] =
-                   //      subgrid[sub_row_index * width + sub_column_index];
+        for sub_row_index in 0..subgrid.get_height() {
+            for sub_column_index in 0..subgrid.get_width() {
+                let value : usize = subgrid.get_value(sub_row_index, sub_column_index);
+                if blend == BlendMode::IgnoreZeros && value == 0 {
+                    continue;
+                }
 
-                    // todo : check if this is works as desired :
-                    self.set_value(page_index,
-                        row_index + sub_row_index,
-                        column_index + sub_column_index,
-                        subgrid[sub_row_index * width + sub_column_index]);
-               }
-           }
-       }
-       // maybe return boolean : worked
+                let (row, column) : (isize, isize) = (row_index + sub_row_index as isize, column_index + sub_column_index as isize);
+                let inside : bool = row >= 0 && column >= 0 && row < self.height as isize && column < self.width as isize;
+
+                let target : Option<(usize, usize)> = match self.boundary.boundary_type {
+                    _ if inside => Some((row as usize, column as usize)),
+                    BoundaryType::Constant(_) | BoundaryType::Reflective =>
+                        return Err(Error::OutOfBounds { row_index : row, column_index : column }),
+                    _ => self.boundary.resolve(row, column)
+                };
+
+                match target {
+                    Some((target_row, target_column)) => {
+                        targets.push((target_row, target_column, value));
+                        placement.wrapped += !inside as usize;
+                    },
+                    None => placement.clipped += 1
+                }
+            }
+        }
+
+        let page : &mut Grid = &mut self.universe[page_index];
+        for (row, column, value) in targets {
+            let old : usize = page.get_value(row, column);
+            let new : usize = match blend {
+                BlendMode::Overwrite | BlendMode::IgnoreZeros => value,
+                BlendMode::Or => old | value,
+                BlendMode::Xor => old ^ value
+            };
+
+            page.set_value(row, column, new);
+            placement.written += 1;
+            placement.changed += (new != old) as usize;
+        }
+
+        return Ok(placement);
     }
 
-    pub fn insert_page(&mut self,
+    /// Inserts `pattern` like `insert_at`, rotated `quarter_turns` quarter turns clockwise
+    /// after mirroring it if `reflect`; see `patterns::orient`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, Grid, TCA2D};
+    /// use gol_rs::patterns;
+    /// use gol_rs::rule::OuterTotalistic;
+    /// use gol_rs::tca2d::BlendMode;
+    ///
+    /// let mut gol = TCA2D::new(OuterTotalistic::new(224), 16, 16, 1, true, BoundaryType::Torus,
+    ///     Grid::new(16, 16).unwrap()).unwrap();
+    ///
+    /// // a glider heading north west, back where it started 4 generations later, one cell up and left
+    /// let glider = patterns::get_pattern("glider").unwrap();
+    /// gol.insert_pattern(0, 8, 8, &glider, 2, false, BlendMode::Or).unwrap();
+    /// let expected : Vec<usize> = gol.get_page(0).get_grid().clone();
+    ///
+    /// gol.step(4);
+    /// assert_eq!(gol.get_value(0, 7, 7), 1);
+    /// assert_eq!(gol.get_page(0).get_population(), 5);
+    /// assert_eq!(gol.get_page(0).get_grid()[..256 - 17], expected[17..]);
+    /// ```
+    pub fn insert_pattern(&mut self,
         page_index : usize,
-        row_index : usize,
-        column_index : usize,
-        width : usize,
-        height : usize,
-        subgrid : Vec<usize>) {
-        // ideally just call insert_at(self.width, self.height, )
-        self.insert_at(page_index, row_index, column_index, width, height, subgrid);
-
+        row_index : isize,
+        column_index : isize,
+        pattern : &Grid,
+        quarter_turns : usize,
+        reflect : bool,
+        blend : BlendMode) -> Result<Placement, Error> {
+
+        return self.insert_at(page_index, row_index, column_index, &patterns::orient(pattern, quarter_turns, reflect), blend);
     }
 
+    /// Fills every page after the first.
     // todo : rename to generate_finite?
     pub fn generate(&mut self) {
+        if self.packed.is_some() {
+            self.generate_packed(0, 1..self.depth);
+            return;
+        }
+
         for page_index in 1..self.depth {
             self.increase_generation(page_index);
         }
     }
 
+    // bit-sliced : packs page `source_index`, and unpacks each following generation into
+    // the pages of `page_indices` in turn.
+    fn generate_packed(&mut self, source_index : usize, page_indices : std::ops::Range<usize>) {
+        let code : usize = self.rule.get_outer_totalistic_code().unwrap(); // checked by with_backend and set_rule
+        let (mut current, mut next) : (PackedPage, PackedPage) = self.packed.take().unwrap();
+
+        current.read_from(&self.universe[source_index]);
+        for page_index in page_indices {
+            current.step_into(code, self.moore, self.boundary.boundary_type, &mut next);
+            mem::swap(&mut current, &mut next);
+            current.write_to(&mut self.universe[page_index]);
+        }
+
+        self.packed = Some((current, next));
+    }
+
+    // bit-sliced : steps from the last page between the two packed pages, unpacking only the
+    // generations still in the stack at the end.
+    fn step_packed(&mut self, generations : usize) {
+        let code : usize = self.rule.get_outer_totalistic_code().unwrap();
+        let (mut current, mut next) : (PackedPage, PackedPage) = self.packed.take().unwrap();
+
+        current.read_from(&self.universe[self.depth - 1]);
+        for generation in 0..generations {
+            current.step_into(code, self.moore, self.boundary.boundary_type, &mut next);
+            mem::swap(&mut current, &mut next);
+
+            if generations - generation <= self.depth {
+                let mut next_generation : Grid = self.take_scratch();
+                current.write_to(&mut next_generation);
+                self.push_page(next_generation);
+            }
+        }
+
+        self.packed = Some((current, next));
+    }
+
+    /// Returns the first cycle among the pages, if any.
+    pub fn find_cycle(&self) -> Option<Cycle> {
+        let mut detector = CycleDetector::new();
+
+        for (page_index, page) in self.universe.iter().enumerate() {
+            if let Some(cycle) = detector.push(page_index, page.get_grid()) {
+                return Some(cycle);
+            }
+        }
+
+        return None;
+    }
+
+    /// Fills the pages like `generate`, but stops computing as soon as the automaton enters a
+    /// cycle, and fills the remaining pages by repeating it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, Grid, TCA2D};
+    /// use gol_rs::rule::OuterTotalistic;
+    ///
+    /// let mut blinker = Grid::new(5, 5).unwrap();
+    /// for column_index in 1..4 {
+    ///     blinker.set_value(2, column_index, 1);
+    /// }
+    ///
+    /// let mut gol = TCA2D::new(OuterTotalistic::new(224), 5, 5, 100, true, BoundaryType::Klein, blinker.clone()).unwrap();
+    /// let cycle = gol.generate_until_cycle().unwrap();
+    /// assert_eq!((cycle.start, cycle.period), (0, 2));
+    ///
+    /// assert_eq!(gol.find_cycle(), Some(cycle));
+    /// assert_eq!(gol.get_page(98).get_grid(), blinker.get_grid());
+    /// ```
+    pub fn generate_until_cycle(&mut self) -> Option<Cycle> {
+        let mut detector = CycleDetector::new();
+        detector.push(0, self.universe[0].get_grid());
+
+        for page_index in 1..self.depth {
+            self.increase_generation(page_index);
+
+            if let Some(cycle) = detector.push(page_index, self.universe[page_index].get_grid()) {
+                // every later page repeats the one a period before
+                for next_page_index in (page_index + 1)..self.depth {
+                    let (before, after) : (&mut [Grid], &mut [Grid]) = self.universe.split_at_mut(next_page_index);
+                    after[0].copy_from(&before[next_page_index - cycle.period]);
+                }
+
+                return Some(cycle);
+            }
+        }
+
+        return None;
+    }
+
+    /// Steps the automaton (see `step`) until its last page enters a cycle, or for at most
+    /// `max_generations`. Generations are counted from the current last page, generation 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, Grid, TCA2D};
+    /// use gol_rs::rule::OuterTotalistic;
+    ///
+    /// // a glider on a 6 x 6 torus comes back after 24 generations
+    /// let glider = Grid::from_vec(6, 6, vec![
+    ///     0, 1, 0, 0, 0, 0,
+    ///     0, 0, 1, 0, 0, 0,
+    ///     1, 1, 1, 0, 0, 0,
+    ///     0, 0, 0, 0, 0, 0,
+    ///     0, 0, 0, 0, 0, 0,
+    ///     0, 0, 0, 0, 0, 0]).unwrap();
+    ///
+    /// let mut gol = TCA2D::new(OuterTotalistic::new(224), 6, 6, 1, true, BoundaryType::Torus, glider).unwrap();
+    /// assert_eq!(gol.step_until_cycle(10), None);
+    ///
+    /// let cycle = gol.step_until_cycle(100).unwrap();
+    /// assert_eq!((cycle.start, cycle.period), (0, 24));
+    /// ```
+    pub fn step_until_cycle(&mut self, max_generations : usize) -> Option<Cycle> {
+        let mut detector = CycleDetector::new();
+        detector.push(0, self.universe[self.depth - 1].get_grid());
+
+        for generation in 1..=max_generations {
+            self.step(1);
+
+            if let Some(cycle) = detector.push(generation, self.universe[self.depth - 1].get_grid()) {
+                return Some(cycle);
+            }
+        }
+
+        return None;
+    }
+
     pub fn generate_display(&mut self) {
         for page_index in 1..self.depth {
             self.increase_generation(page_index);
@@ -288,151 +761,207 @@ impl TCA2D { // totalistic cellular automata : 2 dimensional
         }
     }
 
-    pub fn display_infinite(&mut self) {
-        // assumes depth == 1
-
-        let mut buffer: Vec<u32> = vec![0; self.width * self.height];
-
-        let mut title = format!("code:{}|width:{}|height:{}|generation:{}",
-            &self.rule.to_string(),
-            &self.width.to_string(),
-            &self.height.to_string(),
-            0);
-
-        let mut window = Window::new("Test - ESC to exit",
-                                     self.width,
-                                     self.height,
-                                     WindowOptions {
-                                        scale: Scale::X2,
-                                        ..WindowOptions::default()
-                                     }).unwrap_or_else(|e| {
-            panic!("{}", e);
-        });
-
-        // todo : add code to generate a gif of what is being shown to user
-
-        let mut generation : usize = 0;
-
-        while window.is_open() && !window.is_key_down(Key::Escape) {
-
-            let connected_components : Grid  = self.universe[0].get_connected_components_grid();
-            // println!("{}", connected_components);
-
-            // println!("#components:{}, generation:{}", connected_components.get_number_of_components(), generation);
+    /// Opens a window and evolves the first page until Escape is pressed, with the controls
+    /// of `viewer`; `soup` makes the configuration R restarts from, given a seed.
+    pub fn display_infinite<F>(&mut self, soup : F, seed : u64) -> Result<(), Box<dyn std::error::Error>>
+        where R : Sync, F : FnMut(u64) -> Result<Grid, Error> {
+        return viewer::run(self, soup, seed);
+    }
 
-            // update buffer
-            for row_index in 0..self.height {
-                for column_index in 0..self.width {
-                    let cell : u32 = connected_components.get_value(row_index, column_index) as u32;
+    /// Returns the page at a given index as a string
+    fn get_page_str(&self, page_index : usize) -> String {
+        let mut page_str = String::new();
 
-                    buffer[row_index * self.width + column_index] =
-                        if cell != 0 {
-                            cell * 1000
-                        } else {
-                            0x00
-                        };
-                }
+        for (index, cell) in (self.universe[page_index].get_grid()).iter().enumerate() {
+            page_str.push_str(&cell.to_string());
+            if index % self.width == self.width - 1 {
+                page_str.push('\n');
             }
-
-            // update universe
-            let mut next_generation : Grid = self.get_next_page(1);
-            self.universe[0] = next_generation;
-
-            // update generation
-            generation = generation + 1;
-            title = format!("code:{}|moore:{}|totalistic:{}|width:{}|height:{}|generation:{}|#components:{}",
-                &self.rule.to_string(),
-                &self.moore,
-                &self.outer_totalistic,
-                &self.width.to_string(),
-                &self.height.to_string(),
-                generation.to_string(),
-                connected_components.get_number_of_components());
-
-            window.set_title(&title);
-            window.update_with_buffer_size(&buffer, self.width, self.height).unwrap();
-
-            // while(!window.is_key_down(Key::W)) {}
-            let sleep_time = Duration::from_millis(100);
-            // thread::sleep(sleep_time);   c
         }
 
-
+        return page_str;
     }
 
-    /// Returns the component ~f(a, n) of an outer totalistic code.
+    /// Restarts from `initial_configuration`, which must be `width` x `height` : it is copied
+    /// into page 0 and every other page is cleared, without reallocating the universe.
     ///
-    /// # Arguments
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, Grid, TCA2D};
+    /// use gol_rs::rule::OuterTotalistic;
+    ///
+    /// let soup = Grid::random(30, 20, 0.5, 7).unwrap();
+    /// let mut gol = TCA2D::new(OuterTotalistic::new(224), 30, 20, 4, true, BoundaryType::Null, soup.clone()).unwrap();
+    /// gol.generate();
+    /// let first_run : Grid = gol.get_page(3).clone();
     ///
-    /// * `a` : 0 or 1
-    /// * `n` : 0, 1, ..., 8
+    /// gol.reset(&Grid::random(30, 20, 0.5, 8).unwrap()).unwrap();
+    /// assert_eq!(gol.get_page(3).get_population(), 0);
     ///
-    /// e.g., (outer) code = 224 -> f(1,2), f(0,3), f(1,3) = 1.
-    fn rule_lookup_outer_totalistic(a : u8, n : u8, code : usize) -> usize {
+    /// gol.reset(&soup).unwrap();
+    /// gol.generate();
+    /// assert_eq!(gol.get_page(3).get_grid(), first_run.get_grid());
+    ///
+    /// assert!(gol.reset(&Grid::new(20, 30).unwrap()).is_err());
+    /// ```
+    pub fn reset(&mut self, initial_configuration : &Grid) -> Result<(), Error> {
+        if initial_configuration.get_width() != self.width || initial_configuration.get_height() != self.height {
+            return Err(Error::ConfigurationMismatch {
+                expected : self.width * self.height,
+                found : initial_configuration.get_width() * initial_configuration.get_height()
+            });
+        }
 
-        let shift = 2 * n + a;
-        let mask = 1 << shift;
+        if self.backend == Backend::BitSliced {
+            tca2d_packed::check(self.rule.get_outer_totalistic_code(), self.boundary.boundary_type, initial_configuration)?;
+        }
 
-        // println!("code = {:#020b}", code);
-        // println!("mask = {:#020b}", mask);
+        self.universe[0].copy_from(initial_configuration);
+        for page in self.universe.iter_mut().skip(1) {
+            page.clear();
+        }
 
-        return (mask & code) >> shift;
+        return Ok(());
     }
 
-    // Returns the component f(n) of a totalistic code.
+}
+
+impl<R : Rule + Sync> TCA2D<R> {
+
+    /// Returns the generation following page `page_index - 1`, like `get_next_page`, with the
+    /// rows split in bands computed on `threads` threads (0 : one per core).
     ///
-    /// # Arguments
+    /// Each band reads the whole previous page, so the neighbours across the edges of a band
+    /// and across every boundary are the same as in `get_next_page`, and so is the result.
     ///
-    /// * `n` : 0, 1, ..., 9
+    /// # Example
     ///
-    /// e.g., code =
-    fn rule_lookup_totalistic(n : u8, code : usize) -> usize {
-        return (code & (1 << n)) >> n;
-    }
+    /// ```
+    /// use gol_rs::{BoundaryType, Grid, TCA2D};
+    /// use gol_rs::rule::OuterTotalistic;
+    ///
+    /// let soup = Grid::random(50, 37, 0.5, 1).unwrap();
+    /// let gol = TCA2D::new(OuterTotalistic::new(224), 50, 37, 2, true, BoundaryType::Klein, soup).unwrap();
+    ///
+    /// let serial = gol.get_next_page(1);
+    /// for threads in 0..8 {
+    ///     assert_eq!(gol.get_next_page_parallel(1, threads).get_grid(), serial.get_grid());
+    /// }
+    /// ```
+    pub fn get_next_page_parallel(&self, page_index : usize, threads : usize) -> Grid {
+        if get_threads(threads).min(self.height) <= 1 || self.backend == Backend::BitSliced {
+            return self.get_next_page(page_index);
+        }
 
-    fn save_page_as_png(&self, page_index : usize) {
+        let pages : [Grid; 2] = [self.universe[page_index - 1].clone(), Grid::empty(self.width, self.height)];
+        let [_, next_page] = self.run_parallel(pages, 1, threads, |_, _| {});
 
+        return next_page;
     }
 
-    fn save_universe_as_png(&self) {
+    /// Fills every page after the first, like `generate`, on `threads` threads (0 : one per
+    /// core).
+    ///
+    /// The threads are started once, and each computes the same band of rows of every page.
+    pub fn generate_parallel(&mut self, threads : usize) {
+        // the bit-sliced backend is fast enough on its own
+        if get_threads(threads).min(self.height) <= 1 || self.packed.is_some() {
+            self.generate();
+            return;
+        }
 
-    }
+        let mut universe : Vec<Grid> = mem::take(&mut self.universe);
+        let pages : [Grid; 2] = [universe[0].clone(), self.take_scratch()];
 
-    fn save_page_as_csv(&self, page_index : usize) {
+        let [scratch, _] = self.run_parallel(pages, self.depth - 1, threads, |generation, page| {
+            universe[generation].copy_from(page);
+        });
 
+        self.universe = universe;
+        self.scratch = scratch;
     }
 
-    fn save_universe_as_csv(&self) {
+    /// Advances the automaton `generations` generations, like `step`, on `threads` threads
+    /// (0 : one per core).
+    ///
+    /// The threads are started once, and each computes the same band of rows of every
+    /// generation; only the generations still in the stack at the end are copied into it.
+    pub fn step_parallel(&mut self, generations : usize, threads : usize) {
+        if get_threads(threads).min(self.height) <= 1 || self.packed.is_some() || generations == 0 {
+            self.step(generations);
+            return;
+        }
 
-    }
+        let depth : usize = self.depth;
+        let mut universe : Vec<Grid> = mem::take(&mut self.universe);
+        let pages : [Grid; 2] = [universe[depth - 1].clone(), self.take_scratch()];
 
-    fn save_universe_as_gif(&self) {
+        let [scratch, _] = self.run_parallel(pages, generations, threads, |generation, page| {
+            if generations - generation < depth {
+                universe.rotate_left(1);
+                universe[depth - 1].copy_from(page);
+            }
+        });
 
+        self.universe = universe;
+        self.scratch = scratch;
     }
 
-    /// Returns the page at a given index as a string
-    fn get_page(&self, page_index : usize) -> String {
-        let mut page_str = String::new();
+    // computes `generations` generations from `pages[0]`, on `threads` threads kept for the
+    // whole run : each thread writes its band of rows of the next generation into one page
+    // while reading the previous generation from the other, then waits at a barrier for the
+    // others. Between barriers, `keep(generation, page)` is called on this thread with each
+    // generation (from 1). Returns the two pages.
+    fn run_parallel<F>(&self, pages : [Grid; 2], generations : usize, threads : usize, mut keep : F) -> [Grid; 2]
+        where F : FnMut(usize, &Grid) {
+
+        let band_height : usize = self.height.div_ceil(get_threads(threads).min(self.height));
+        let bands : usize = self.height.div_ceil(band_height);
+
+        let pages : [RwLock<Grid>; 2] = pages.map(RwLock::new);
+        let barrier = Barrier::new(bands + 1);
+
+        thread::scope(|scope| {
+            for band_index in 0..bands {
+                let (pages, barrier) : (&[RwLock<Grid>; 2], &Barrier) = (&pages, &barrier);
+
+                scope.spawn(move || {
+                    let first_row : usize = band_index * band_height;
+                    let mut band : Vec<usize> = vec![0; band_height.min(self.height - first_row) * self.width];
+                    let start : usize = first_row * self.width;
+
+                    for generation in 0..generations {
+                        self.write_next_band(&pages[generation % 2].read().unwrap(), first_row, &mut band);
+                        pages[(generation + 1) % 2].write().unwrap().get_cells_mut()[start..start + band.len()].copy_from_slice(&band);
+
+                        // the next generation writes into the page read now
+                        barrier.wait();
+                    }
+                });
+            }
 
-        for (index, cell) in (self.universe[page_index].get_grid()).iter().enumerate() {
-            page_str.push_str(&cell.to_string());
-            if index % self.width as usize == (self.width - 1) as usize {
-                page_str.push('\n');
+            // while the threads write generation + 1 into the other page
+            for generation in 1..=generations {
+                barrier.wait();
+                keep(generation, &pages[generation % 2].read().unwrap());
             }
-        }
+        });
 
-        return page_str;
+        return pages.map(|page| page.into_inner().unwrap());
     }
+}
 
-    pub fn reset(&mut self) {
-        // wipe everything except first page
-        // alternatively, have a reset method that puts in a new initial_configuration
-        //      save from having to reallocate memory for the entire universe
+// 0 : one thread per core.
+fn get_threads(threads : usize) -> usize {
+    if threads == 0 {
+        return thread::available_parallelism().map_or(1, |threads| threads.get());
     }
 
+    return threads;
 }
 
-impl fmt::Display for TCA2D {
+impl<R : Rule> fmt::Display for TCA2D<R> {
     // This trait requires `fmt` with this exact signature.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // Write strictly the first element into the supplied output
@@ -443,7 +972,7 @@ impl fmt::Display for TCA2D {
         let mut universe_str : String = String::new();
         for page_index in 0..self.universe.len() {
             universe_str.push_str(&format!("page : {}\n", page_index));
-            universe_str.push_str(&self.get_page(page_index));
+            universe_str.push_str(&self.get_page_str(page_index));
             universe_str.push('\n');
         }
 
@@ -0,0 +1,233 @@
+// Bit-sliced two dimensional automata, for 2 state outer totalistic rules.
+//
+// Each row of a page is stored 64 cells to a word, as in `eca_packed`. The neighbours of every
+// cell of a word in one direction are a shifted copy of a row, so the number of live
+// neighbours of 64 cells is added up at once, in four bit planes (counts go up to 8), by a
+// chain of half adders. The next state is then the sum of the minterms f(a, n) = 1 of the code :
+// (a or !a) & (count == n).
+
+use crate::boundary::BoundaryType;
+use crate::error::Error;
+use crate::grid::Grid;
+
+/// Returns whether the bit-sliced backend can run an outer totalistic `code` (if any) on
+/// `boundary_type`; only null and torus boundaries are supported.
+pub fn is_supported(code : Option<usize>, boundary_type : BoundaryType) -> bool {
+    return code.is_some() && (boundary_type == BoundaryType::Null || boundary_type == BoundaryType::Torus);
+}
+
+/// Checks that the backend can run `code` on `boundary_type`, from a 2 state `page`.
+pub fn check(code : Option<usize>, boundary_type : BoundaryType, page : &Grid) -> Result<(), Error> {
+    if code.is_none() {
+        return Err(Error::UnsupportedBackend(String::from("the rule has no outer totalistic code")));
+    }
+
+    if !is_supported(code, boundary_type) {
+        return Err(Error::UnsupportedBackend(format!("{:?} boundary; only null and torus are supported", boundary_type)));
+    }
+
+    if let Some(state) = page.get_grid().iter().find(|cell| **cell > 1) {
+        return Err(Error::InvalidState { state : *state, states : 2 });
+    }
+
+    return Ok(());
+}
+
+/// A page stored as rows of u64 words.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackedPage {
+    width : usize,
+    height : usize,
+    words_per_row : usize,
+    words : Vec<u64> // bits past the width are always 0
+}
+
+impl PackedPage {
+
+    /// Returns an empty `width` x `height` page.
+    pub fn new(width : usize, height : usize) -> PackedPage {
+        let words_per_row : usize = width.div_ceil(64);
+
+        return PackedPage {
+            width : width,
+            height : height,
+            words_per_row : words_per_row,
+            words : vec![0; words_per_row * height]
+        };
+    }
+
+    /// Packs `grid`; cells other than 0 count as 1.
+    pub fn from_grid(grid : &Grid) -> PackedPage {
+        let mut page : PackedPage = PackedPage::new(grid.get_width(), grid.get_height());
+        page.read_from(grid);
+
+        return page;
+    }
+
+    /// Packs `grid`, which must have the same dimensions, in place; cells other than 0 count
+    /// as 1.
+    pub fn read_from(&mut self, grid : &Grid) {
+        let rows = grid.get_grid().chunks(self.width);
+
+        for (words, row) in self.words.chunks_mut(self.words_per_row).zip(rows) {
+            for (word, cells) in words.iter_mut().zip(row.chunks(64)) {
+                *word = cells.iter().enumerate().fold(0, |word, (bit, cell)| word | ((*cell != 0) as u64) << bit);
+            }
+        }
+    }
+
+    /// Writes the cells into `grid`, which must have the same dimensions.
+    pub fn write_to(&self, grid : &mut Grid) {
+        let width : usize = self.width;
+        let rows = grid.get_cells_mut().chunks_mut(width);
+
+        for (words, row) in self.words.chunks(self.words_per_row).zip(rows) {
+            for (word, cells) in words.iter().zip(row.chunks_mut(64)) {
+                for (bit, cell) in cells.iter_mut().enumerate() {
+                    *cell = ((word >> bit) & 1) as usize;
+                }
+            }
+        }
+    }
+
+    pub fn get_value(&self, row_index : usize, column_index : usize) -> usize {
+        let word : u64 = self.words[row_index * self.words_per_row + column_index / 64];
+
+        return ((word >> (column_index % 64)) & 1) as usize;
+    }
+
+    pub fn get_population(&self) -> usize {
+        return self.words.iter().map(|word| word.count_ones() as usize).sum();
+    }
+
+    /// Returns the next generation under the outer totalistic `code`, where bit 2n + a is
+    /// f(a, n).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::{BoundaryType, Grid};
+    /// use gol_rs::tca2d_packed::PackedPage;
+    ///
+    /// let blinker = Grid::from_vec(5, 5, vec![
+    ///     0, 0, 0, 0, 0,
+    ///     0, 0, 0, 0, 0,
+    ///     0, 1, 1, 1, 0,
+    ///     0, 0, 0, 0, 0,
+    ///     0, 0, 0, 0, 0]).unwrap();
+    ///
+    /// let next = PackedPage::from_grid(&blinker).step(224, true, BoundaryType::Null);
+    /// assert_eq!(next.get_value(1, 2), 1);
+    /// assert_eq!(next.get_value(2, 1), 0);
+    /// assert_eq!(next.get_population(), 3);
+    /// ```
+    pub fn step(&self, code : usize, moore : bool, boundary_type : BoundaryType) -> PackedPage {
+        let mut next : PackedPage = PackedPage::new(self.width, self.height);
+        self.step_into(code, moore, boundary_type, &mut next);
+
+        return next;
+    }
+
+    /// Writes the next generation, like `step`, into `next`, which must have the same
+    /// dimensions; nothing is allocated.
+    pub fn step_into(&self, code : usize, moore : bool, boundary_type : BoundaryType, next : &mut PackedPage) {
+        let torus : bool = boundary_type == BoundaryType::Torus;
+        let length : usize = self.words_per_row;
+        let last_bit : usize = (self.width - 1) % 64;
+        let last_mask : u64 = if last_bit == 63 { !0 } else { (1 << (last_bit + 1)) - 1 };
+
+        // rows above and below, None outside a null boundary
+        let get_neighbour_row = |row_index : usize, offset : isize| -> Option<usize> {
+            let neighbour : isize = row_index as isize + offset;
+            if neighbour >= 0 && neighbour < self.height as isize {
+                return Some(neighbour as usize);
+            }
+            if torus {
+                return Some(neighbour.rem_euclid(self.height as isize) as usize);
+            }
+            return None;
+        };
+
+        // word `index` of a row, shifted so that bit i holds the west (east) neighbour of
+        // cell i; 0 outside a null boundary
+        let get_words = |row_index : Option<usize>, index : usize| -> (u64, u64, u64) {
+            let row : &[u64] = match row_index {
+                Some(row_index) => &self.words[row_index * length..(row_index + 1) * length],
+                None => return (0, 0, 0)
+            };
+
+            let west_carry : u64 = match (index, torus) {
+                (0, true) => (row[length - 1] >> last_bit) & 1,
+                (0, false) => 0,
+                _ => row[index - 1] >> 63
+            };
+            let east_carry : u64 = match (index + 1 == length, torus) {
+                (true, true) => (row[0] & 1) << last_bit,
+                (true, false) => 0,
+                _ => row[index + 1] << 63
+            };
+
+            return ((row[index] << 1) | west_carry, row[index], (row[index] >> 1) | east_carry);
+        };
+
+        for row_index in 0..self.height {
+            let above : Option<usize> = get_neighbour_row(row_index, -1);
+            let below : Option<usize> = get_neighbour_row(row_index, 1);
+
+            for index in 0..length {
+                let (north_west, north, north_east) : (u64, u64, u64) = get_words(above, index);
+                let (west, origin, east) : (u64, u64, u64) = get_words(Some(row_index), index);
+                let (south_west, south, south_east) : (u64, u64, u64) = get_words(below, index);
+
+                let mut count : [u64; 4] = [0; 4];
+                add(&mut count, north);
+                add(&mut count, west);
+                add(&mut count, east);
+                add(&mut count, south);
+                if moore {
+                    add(&mut count, north_west);
+                    add(&mut count, north_east);
+                    add(&mut count, south_west);
+                    add(&mut count, south_east);
+                }
+
+                next.words[row_index * length + index] = apply_code(code, origin, &count);
+            }
+
+            next.words[row_index * length + length - 1] &= last_mask;
+        }
+    }
+}
+
+// adds the bit plane `bits` to the four bit counter, one half adder per plane.
+fn add(count : &mut [u64; 4], bits : u64) {
+    let mut carry : u64 = bits;
+
+    for plane in count.iter_mut() {
+        let next_carry : u64 = *plane & carry;
+        *plane ^= carry;
+        carry = next_carry;
+    }
+}
+
+// the next state of 64 cells at once, from their own bits and their neighbour counts.
+fn apply_code(code : usize, origin : u64, count : &[u64; 4]) -> u64 {
+    let mut next : u64 = 0;
+
+    for n in 0..9 {
+        // bits of count == n
+        let mut equal : u64 = !0;
+        for (bit, plane) in count.iter().enumerate() {
+            equal &= if (n >> bit) & 1 == 1 { *plane } else { !*plane };
+        }
+
+        if (code >> (2 * n)) & 1 == 1 {
+            next |= !origin & equal; // birth
+        }
+        if (code >> (2 * n + 1)) & 1 == 1 {
+            next |= origin & equal; // survival
+        }
+    }
+
+    return next;
+}
@@ -0,0 +1,661 @@
+// Interactive viewer for the first page of a TCA2D, in a minifb window :
+//
+//      Space           pause / resume
+//      N               one generation (while paused)
+//      =, -            faster / slower
+//      R               restart from a new random soup
+//      C               raw cells / connected components
+//      S               save a screenshot, generation-N.png
+//      P               save the page, generation-N.rle
+//      Escape          drop the selection, or quit
+//
+//      left mouse      draw               right mouse     erase
+//      shift + left    select             middle mouse    pan
+//      scroll, [, ]    zoom               arrows          pan
+//      Home            fit the page
+//
+//      Ctrl+C, Ctrl+X  copy / cut the selection
+//      Ctrl+V          paste with its top left cell under the mouse
+//      O               rotate the selection clockwise, in place
+//      Delete          clear the selection
+//
+// Speeds run from one generation a second to 64 generations a frame; at every speed the
+// window is redrawn, and the keys and mouse read, at least every FRAME ms. The window has its
+// own pixels : `Viewport` maps them to cells, `zoom` pixels a side, and can show part of a
+// page larger than the screen. The state of the viewer other than the automaton is kept in
+// `ViewerState`, `Viewport` and `Selection`, so they can be checked without a window.
+
+use crate::error::Error;
+use crate::grid::Grid;
+use crate::pattern_io;
+use crate::rule::Rule;
+use crate::tca2d::{BlendMode, TCA2D};
+
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// (generations per step, least milliseconds between steps), slowest first; with 0 ms, a
+/// step every frame.
+pub const SPEEDS : [(usize, u64); 13] = [(1, 1000), (1, 500), (1, 250), (1, 100), (1, 50), (1, 20), (1, 0),
+    (2, 0), (4, 0), (8, 0), (16, 0), (32, 0), (64, 0)];
+
+// as fast as display_infinite has always run : one generation a frame.
+const DEFAULT_SPEED : usize = 6;
+
+// the longest a frame waits for the next step, in milliseconds (60 frames a second).
+const FRAME : u64 = 16;
+
+/// How cells are coloured.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Colouring {
+    /// dead cells black, live cells white, other states from a palette.
+    Raw,
+    /// every connected component of live cells in its own colour.
+    Components,
+}
+
+/// What a key does.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    TogglePause,
+    Step,
+    Faster,
+    Slower,
+    Restart,
+    ToggleColouring,
+    SaveScreenshot,
+    SavePattern,
+    ZoomIn,
+    ZoomOut,
+    /// by an eighth of the window, in rows and columns.
+    Pan(isize, isize),
+    Fit,
+    Copy,
+    Cut,
+    Paste,
+    Rotate,
+    Clear,
+    Quit,
+}
+
+/// Returns the command bound to `key`, with or without Ctrl held, if any.
+pub fn get_command(key : Key, control : bool) -> Option<Command> {
+    if control {
+        return match key {
+            Key::C => Some(Command::Copy),
+            Key::X => Some(Command::Cut),
+            Key::V => Some(Command::Paste),
+            _ => None
+        };
+    }
+
+    return match key {
+        Key::Space => Some(Command::TogglePause),
+        Key::N => Some(Command::Step),
+        Key::Equal | Key::NumPadPlus => Some(Command::Faster),
+        Key::Minus | Key::NumPadMinus => Some(Command::Slower),
+        Key::R => Some(Command::Restart),
+        Key::C => Some(Command::ToggleColouring),
+        Key::S => Some(Command::SaveScreenshot),
+        Key::P => Some(Command::SavePattern),
+        Key::RightBracket => Some(Command::ZoomIn),
+        Key::LeftBracket => Some(Command::ZoomOut),
+        Key::Up => Some(Command::Pan(-1, 0)),
+        Key::Down => Some(Command::Pan(1, 0)),
+        Key::Left => Some(Command::Pan(0, -1)),
+        Key::Right => Some(Command::Pan(0, 1)),
+        Key::Home => Some(Command::Fit),
+        Key::O => Some(Command::Rotate),
+        Key::Delete | Key::Backspace => Some(Command::Clear),
+        Key::Escape => Some(Command::Quit),
+        _ => None
+    };
+}
+
+/// Pause, speed and colouring of the viewer.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::viewer::{Colouring, Command, ViewerState};
+/// use std::time::{Duration, Instant};
+///
+/// let start = Instant::now();
+/// let mut state = ViewerState::new();
+/// assert_eq!(state.get_generations(start), 1);
+///
+/// state.apply(Command::TogglePause);
+/// assert_eq!(state.get_generations(start), 0);
+///
+/// // one generation, then paused again
+/// state.apply(Command::Step);
+/// assert_eq!(state.get_generations(start), 1);
+/// assert_eq!(state.get_generations(start), 0);
+///
+/// state.apply(Command::TogglePause);
+/// state.apply(Command::Faster);
+/// assert_eq!(state.get_generations(start), 2);
+///
+/// // slowest : a generation a second, while frames go on
+/// for _ in 0..10 {
+///     state.apply(Command::Slower);
+/// }
+/// assert_eq!(state.get_generations(start + Duration::from_millis(500)), 0);
+/// assert_eq!(state.get_generations(start + Duration::from_millis(1000)), 1);
+/// assert_eq!(state.get_generations(start + Duration::from_millis(1500)), 0);
+///
+/// state.apply(Command::ToggleColouring);
+/// assert_eq!(state.colouring, Colouring::Raw);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewerState {
+    pub paused : bool,
+    pub speed : usize, // index into SPEEDS
+    pub colouring : Colouring,
+    steps : usize, // single generations asked for while paused
+    last_step : Option<Instant>, // when generations last ran
+}
+
+impl ViewerState {
+
+    pub fn new() -> ViewerState {
+        return ViewerState {
+            paused : false,
+            speed : DEFAULT_SPEED,
+            colouring : Colouring::Components,
+            steps : 0,
+            last_step : None
+        };
+    }
+
+    /// Applies the commands that only change the state; the others are left to the viewer.
+    pub fn apply(&mut self, command : Command) {
+        match command {
+            Command::TogglePause => self.paused = !self.paused,
+            Command::Step if self.paused => self.steps += 1,
+            Command::Faster => self.speed = (self.speed + 1).min(SPEEDS.len() - 1),
+            Command::Slower => self.speed = self.speed.saturating_sub(1),
+            Command::ToggleColouring => self.colouring = match self.colouring {
+                Colouring::Raw => Colouring::Components,
+                Colouring::Components => Colouring::Raw
+            },
+            _ => {}
+        }
+    }
+
+    /// Returns the number of generations to run in the frame drawn at `now` : the single
+    /// steps while paused, otherwise a step once its time since the last one has passed.
+    pub fn get_generations(&mut self, now : Instant) -> usize {
+        if self.paused {
+            return std::mem::take(&mut self.steps);
+        }
+
+        let (generations, delay) : (usize, u64) = SPEEDS[self.speed];
+        if self.last_step.is_some_and(|last_step| now.saturating_duration_since(last_step) < Duration::from_millis(delay)) {
+            return 0;
+        }
+
+        self.last_step = Some(now);
+
+        return generations;
+    }
+}
+
+impl Default for ViewerState {
+    fn default() -> ViewerState {
+        return ViewerState::new();
+    }
+}
+
+/// A rectangle of cells, inside a page.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Selection {
+    pub row_index : usize,
+    pub column_index : usize,
+    pub width : usize,
+    pub height : usize,
+}
+
+impl Selection {
+    /// Returns the rectangle with opposite corners `from` and `to`, both included, clamped to a
+    /// `page_width` x `page_height` page.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use gol_rs::viewer::Selection;
+    ///
+    /// let selection = Selection::from_corners((5, 1), (-3, 12), 10, 8);
+    /// assert_eq!(selection, Selection { row_index : 0, column_index : 1, width : 9, height : 6 });
+    /// ```
+    pub fn from_corners(from : (isize, isize), to : (isize, isize), page_width : usize, page_height : usize) -> Selection {
+        let clamp = |index : isize, length : usize| index.clamp(0, length as isize - 1) as usize;
+        let (first_row, last_row) : (usize, usize) = (clamp(from.0, page_height), clamp(to.0, page_height));
+        let (first_column, last_column) : (usize, usize) = (clamp(from.1, page_width), clamp(to.1, page_width));
+
+        return Selection {
+            row_index : first_row.min(last_row),
+            column_index : first_column.min(last_column),
+            width : first_column.abs_diff(last_column) + 1,
+            height : first_row.abs_diff(last_row) + 1
+        };
+    }
+}
+
+/// Largest number of pixels a side of a cell.
+pub const MAX_ZOOM : usize = 32;
+
+// window sizes, in pixels : twice the page, within these bounds.
+const MIN_WINDOW : usize = 256;
+const MAX_WINDOW_WIDTH : usize = 1024;
+const MAX_WINDOW_HEIGHT : usize = 768;
+
+/// The part of a page shown in the window.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::viewer::Viewport;
+///
+/// // a 200 x 130 page fits a 400 x 260 window at 2 pixels a cell
+/// let mut viewport = Viewport::new(200, 130);
+/// assert_eq!((viewport.width, viewport.height, viewport.zoom), (400, 260, 2));
+/// assert_eq!(viewport.get_cell(11.0, 5.0), (2, 5));
+///
+/// // the cell under the mouse stays there
+/// viewport.zoom_at(11.0, 5.0, 8);
+/// assert_eq!(viewport.get_cell(11.0, 5.0), (2, 5));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Viewport {
+    pub width : usize, // of the window, in pixels
+    pub height : usize,
+    pub zoom : usize, // pixels a side of a cell, a power of two
+    pub row_index : isize, // of the cell at the top left pixel; negative past the top edge
+    pub column_index : isize,
+    page_width : usize,
+    page_height : usize,
+}
+
+impl Viewport {
+
+    /// Returns a viewport on a `page_width` x `page_height` page, fitted to it.
+    pub fn new(page_width : usize, page_height : usize) -> Viewport {
+        let mut viewport = Viewport {
+            width : (page_width * 2).clamp(MIN_WINDOW, MAX_WINDOW_WIDTH),
+            height : (page_height * 2).clamp(MIN_WINDOW, MAX_WINDOW_HEIGHT),
+            zoom : 1,
+            row_index : 0,
+            column_index : 0,
+            page_width : page_width,
+            page_height : page_height
+        };
+        viewport.fit();
+
+        return viewport;
+    }
+
+    /// Zooms as far in as the whole page fits, or as far out as possible, and centres the page.
+    pub fn fit(&mut self) {
+        let zoom : usize = (self.width / self.page_width).min(self.height / self.page_height).clamp(1, MAX_ZOOM);
+        self.zoom = 1 << (usize::BITS - 1 - zoom.leading_zeros()); // rounded down to a power of two
+
+        self.row_index = (self.page_height as isize - self.get_rows() as isize) / 2;
+        self.column_index = (self.page_width as isize - self.get_columns() as isize) / 2;
+        self.clamp();
+    }
+
+    /// Returns the (row, column) of the cell under pixel (`x`, `y`), which may be past an edge
+    /// of the page.
+    pub fn get_cell(&self, x : f32, y : f32) -> (isize, isize) {
+        return (self.row_index + (y.max(0.0) as usize / self.zoom) as isize,
+            self.column_index + (x.max(0.0) as usize / self.zoom) as isize);
+    }
+
+    /// Sets the zoom, between 1 and `MAX_ZOOM` and rounded down to a power of two, keeping the
+    /// cell under pixel (`x`, `y`) there.
+    pub fn zoom_at(&mut self, x : f32, y : f32, zoom : usize) {
+        let (row_index, column_index) : (isize, isize) = self.get_cell(x, y);
+
+        let zoom : usize = zoom.clamp(1, MAX_ZOOM);
+        self.zoom = 1 << (usize::BITS - 1 - zoom.leading_zeros());
+
+        self.row_index = row_index - (y.max(0.0) as usize / self.zoom) as isize;
+        self.column_index = column_index - (x.max(0.0) as usize / self.zoom) as isize;
+        self.clamp();
+    }
+
+    /// Moves the top left pixel to cell (`row_index`, `column_index`), keeping part of the
+    /// page in the window.
+    pub fn move_to(&mut self, row_index : isize, column_index : isize) {
+        self.row_index = row_index;
+        self.column_index = column_index;
+        self.clamp();
+    }
+
+    /// Applies the zoom, pan and fit commands; the others are left to the viewer.
+    pub fn apply(&mut self, command : Command) {
+        let (x, y) : (f32, f32) = (self.width as f32 / 2.0, self.height as f32 / 2.0);
+
+        match command {
+            Command::ZoomIn => self.zoom_at(x, y, self.zoom * 2),
+            Command::ZoomOut => self.zoom_at(x, y, self.zoom / 2),
+            Command::Pan(rows, columns) => self.move_to(
+                self.row_index + rows * (self.get_rows() as isize / 8).max(1),
+                self.column_index + columns * (self.get_columns() as isize / 8).max(1)),
+            Command::Fit => self.fit(),
+            _ => {}
+        }
+    }
+
+    /// Fills `buffer`, `width` x `height` pixels, with `cells` (a page, or its connected
+    /// components) and the outline of `selection`; past the edges of the page is grey.
+    pub fn render(&self, cells : &Grid, colouring : Colouring, selection : Option<Selection>, buffer : &mut [u32]) {
+        for (y, pixels) in buffer.chunks_mut(self.width).enumerate() {
+            let row_index : isize = self.row_index + (y / self.zoom) as isize;
+
+            for (x, pixel) in pixels.iter_mut().enumerate() {
+                let column_index : isize = self.column_index + (x / self.zoom) as isize;
+
+                *pixel = if row_index < 0 || column_index < 0 || row_index >= self.page_height as isize || column_index >= self.page_width as isize {
+                    EDGE_COLOUR
+                } else {
+                    get_colour(cells.get_value(row_index as usize, column_index as usize), colouring)
+                };
+            }
+        }
+
+        if let Some(selection) = selection {
+            // in pixels, both included
+            let top : isize = (selection.row_index as isize - self.row_index) * self.zoom as isize;
+            let left : isize = (selection.column_index as isize - self.column_index) * self.zoom as isize;
+            let bottom : isize = top + (selection.height * self.zoom) as isize - 1;
+            let right : isize = left + (selection.width * self.zoom) as isize - 1;
+
+            for y in top.max(0)..=bottom.min(self.height as isize - 1) {
+                for x in left.max(0)..=right.min(self.width as isize - 1) {
+                    if y == top || y == bottom || x == left || x == right {
+                        buffer[y as usize * self.width + x as usize] = SELECTION_COLOUR;
+                    }
+                }
+            }
+        }
+    }
+
+    // rows and columns of cells in the window, counting partly shown ones.
+    fn get_rows(&self) -> usize {
+        return self.height.div_ceil(self.zoom);
+    }
+
+    fn get_columns(&self) -> usize {
+        return self.width.div_ceil(self.zoom);
+    }
+
+    // at least one cell of the page stays in the window.
+    fn clamp(&mut self) {
+        self.row_index = self.row_index.clamp(1 - self.get_rows() as isize, self.page_height as isize - 1);
+        self.column_index = self.column_index.clamp(1 - self.get_columns() as isize, self.page_width as isize - 1);
+    }
+}
+
+const EDGE_COLOUR : u32 = 0x202020;
+const SELECTION_COLOUR : u32 = 0x3399ff;
+
+/// Returns the colour of a cell; `Components` expects a connected components grid.
+pub fn get_colour(cell : usize, colouring : Colouring) -> u32 {
+    const PALETTE : [u32; 6] = [0xff0000, 0x00ff00, 0x0000ff, 0xffff00, 0xff00ff, 0x00ffff];
+
+    return match (colouring, cell) {
+        (_, 0) => 0x000000,
+        (Colouring::Raw, 1) => 0xffffff,
+        (Colouring::Raw, cell) => PALETTE[(cell - 2) % PALETTE.len()],
+        (Colouring::Components, cell) => (cell as u32).wrapping_mul(1000) & 0xffffff
+    };
+}
+
+/// Opens a window on page 0 of `gol` and runs it until Escape is pressed or the window is
+/// closed; `soup` makes the configuration to restart from, given a seed, and the seeds
+/// used are `seed + 1`, `seed + 2`, ... (printed to stderr).
+pub fn run<R, F>(gol : &mut TCA2D<R>, mut soup : F, seed : u64) -> Result<(), Box<dyn std::error::Error>>
+    where R : Rule + Sync, F : FnMut(u64) -> Result<Grid, Error> {
+
+    let (width, height) : (usize, usize) = (gol.get_width(), gol.get_height());
+    let mut viewport = Viewport::new(width, height);
+    let mut buffer : Vec<u32> = vec![0; viewport.width * viewport.height];
+
+    let mut window = Window::new("gol-rs - ESC to exit", viewport.width, viewport.height, WindowOptions::default())?;
+
+    let mut state = ViewerState::new();
+    let mut generation : usize = 0;
+    let mut seed : u64 = seed;
+
+    let mut selection : Option<Selection> = None;
+    let mut clipboard : Option<Grid> = None;
+    let mut mouse = Mouse::default();
+
+    while window.is_open() {
+        let control : bool = window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl);
+        let shift : bool = window.is_key_down(Key::LeftShift) || window.is_key_down(Key::RightShift);
+        let position : Option<(f32, f32)> = window.get_mouse_pos(MouseMode::Discard);
+        let keys : Vec<Key> = window.get_keys_pressed(KeyRepeat::No).unwrap_or_default();
+
+        for command in keys.into_iter().filter_map(|key| get_command(key, control)) {
+            match command {
+                Command::Quit if selection.is_some() => selection = None,
+                Command::Quit => return Ok(()),
+                Command::Restart => {
+                    seed = seed.wrapping_add(1);
+                    eprintln!("seed : {}", seed);
+                    gol.reset(&soup(seed)?)?;
+                    generation = 0;
+                },
+                Command::SaveScreenshot => save(&format!("generation-{}.png", generation),
+                    |path| save_screenshot(path, &buffer, viewport.width, viewport.height)),
+                Command::SavePattern => save(&format!("generation-{}.rle", generation),
+                    |path| pattern_io::write(path, &gol.get_pattern(0))),
+                Command::Copy | Command::Cut | Command::Rotate | Command::Clear => {
+                    if let Some(selected) = selection {
+                        match edit(gol, command, selected, &mut clipboard) {
+                            Ok(edited) => selection = Some(edited),
+                            Err(e) => eprintln!("could not edit the selection : {}", e)
+                        }
+                    }
+                },
+                Command::Paste => {
+                    // under the mouse, else over the selection
+                    let target : Option<(isize, isize)> = position.map(|(x, y)| viewport.get_cell(x, y))
+                        .or_else(|| selection.map(|selected| (selected.row_index as isize, selected.column_index as isize)));
+
+                    if let (Some(cells), Some((row_index, column_index))) = (&clipboard, target) {
+                        match gol.insert_at(0, row_index, column_index, cells, BlendMode::Overwrite) {
+                            Ok(_) => selection = Some(Selection::from_corners((row_index, column_index),
+                                (row_index + cells.get_height() as isize - 1, column_index + cells.get_width() as isize - 1), width, height)),
+                            Err(e) => eprintln!("could not paste : {}", e)
+                        }
+                    }
+                },
+                command => {
+                    state.apply(command);
+                    viewport.apply(command);
+                }
+            }
+        }
+
+        // the mouse : draw, erase, select, pan and zoom
+        let left : bool = window.get_mouse_down(MouseButton::Left);
+        let right : bool = window.get_mouse_down(MouseButton::Right);
+        let middle : bool = window.get_mouse_down(MouseButton::Middle);
+
+        if let Some((x, y)) = position {
+            let cell : (isize, isize) = viewport.get_cell(x, y);
+
+            if left && shift {
+                let anchor : (isize, isize) = *mouse.anchor.get_or_insert(cell);
+                selection = Some(Selection::from_corners(anchor, cell, width, height));
+            } else if left || right {
+                draw_line(gol, mouse.last_cell.unwrap_or(cell), cell, left as usize);
+                mouse.last_cell = Some(cell);
+            } else if middle {
+                let ((start_x, start_y), (row_index, column_index)) = *mouse.pan.get_or_insert(((x, y), (viewport.row_index, viewport.column_index)));
+                viewport.move_to(row_index + ((start_y - y) / viewport.zoom as f32) as isize,
+                    column_index + ((start_x - x) / viewport.zoom as f32) as isize);
+            }
+
+            if let Some((_, scroll)) = window.get_scroll_wheel() {
+                if scroll > 0.0 {
+                    viewport.zoom_at(x, y, viewport.zoom * 2);
+                } else if scroll < 0.0 {
+                    viewport.zoom_at(x, y, viewport.zoom / 2);
+                }
+            }
+        }
+
+        if !left {
+            mouse.anchor = None;
+        }
+        if !left && !right {
+            mouse.last_cell = None;
+        }
+        if !middle {
+            mouse.pan = None;
+        }
+
+        let frame_start : Instant = Instant::now();
+        let generations : usize = state.get_generations(frame_start);
+        gol.step(generations);
+        generation += generations;
+
+        // the components are only labelled when shown
+        let (cells, count) : (Grid, String) = match state.colouring {
+            Colouring::Raw => (gol.get_page(0).clone(), format!("population:{}", gol.get_page(0).get_population())),
+            Colouring::Components => {
+                let components : Grid = gol.get_page(0).get_connected_components_grid();
+                let count : String = format!("#components:{}", components.get_number_of_components());
+                (components, count)
+            }
+        };
+
+        viewport.render(&cells, state.colouring, selection, &mut buffer);
+
+        window.set_title(&format!("rule:{}|width:{}|height:{}|generation:{}|{}|speed:{}|zoom:{}{}{}",
+            gol.get_rule().get_name(),
+            width,
+            height,
+            generation,
+            count,
+            state.speed,
+            viewport.zoom,
+            selection.map_or(String::new(), |selected| format!("|selection:{}x{}", selected.width, selected.height)),
+            if state.paused { "|paused" } else { "" }));
+        window.update_with_buffer_size(&buffer, viewport.width, viewport.height)?;
+
+        // waiting for the next step : no faster than FRAME, so the loop does not spin
+        if generations == 0 {
+            thread::sleep(Duration::from_millis(FRAME).saturating_sub(frame_start.elapsed()));
+        }
+    }
+
+    return Ok(());
+}
+
+// what the mouse is doing across frames.
+#[derive(Default)]
+struct Mouse {
+    last_cell : Option<(isize, isize)>, // drawn or erased in the last frame
+    anchor : Option<(isize, isize)>, // first corner of the selection being dragged
+    pan : Option<((f32, f32), (isize, isize))>, // pixel the pan started at, and the viewport then
+}
+
+// sets the cells from `from` to `to` to `value`, so a fast stroke leaves no gaps; cells past
+// an edge are skipped.
+fn draw_line<R : Rule>(gol : &mut TCA2D<R>, from : (isize, isize), to : (isize, isize), value : usize) {
+    let steps : isize = (to.0 - from.0).abs().max((to.1 - from.1).abs()).max(1);
+
+    for step in 0..=steps {
+        let row_index : isize = from.0 + (to.0 - from.0) * step / steps;
+        let column_index : isize = from.1 + (to.1 - from.1) * step / steps;
+
+        if row_index >= 0 && column_index >= 0 && (row_index as usize) < gol.get_height() && (column_index as usize) < gol.get_width() {
+            gol.set_value(0, row_index as usize, column_index as usize, value);
+        }
+    }
+}
+
+/// Copies, cuts, rotates or clears the selected cells of page 0, and returns the selection
+/// after the edit.
+///
+/// The selection is rotated clockwise about its top left cell, and must still lie inside the
+/// page once rotated; nothing changes otherwise.
+///
+/// # Example
+///
+/// ```
+/// use gol_rs::{BoundaryType, Grid, TCA2D};
+/// use gol_rs::rule::OuterTotalistic;
+/// use gol_rs::viewer::{self, Command, Selection};
+///
+/// let page = Grid::from_vec(4, 3, vec![
+///     1, 1, 1, 0,
+///     0, 0, 0, 0,
+///     0, 0, 0, 0]).unwrap();
+/// let mut gol = TCA2D::new(OuterTotalistic::new(224), 4, 3, 1, true, BoundaryType::Null, page).unwrap();
+/// let mut clipboard = None;
+///
+/// let row = Selection { row_index : 0, column_index : 0, width : 3, height : 1 };
+/// let column = viewer::edit(&mut gol, Command::Rotate, row, &mut clipboard).unwrap();
+/// assert_eq!(column, Selection { row_index : 0, column_index : 0, width : 1, height : 3 });
+/// assert_eq!(gol.get_page(0).get_grid(), &vec![1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0]);
+/// ```
+pub fn edit<R : Rule>(gol : &mut TCA2D<R>, command : Command, selection : Selection, clipboard : &mut Option<Grid>) -> Result<Selection, Error> {
+    let Selection { row_index, column_index, width, height } = selection;
+    let selected : Grid = gol.get_page(0).get_subgrid(row_index, column_index, width, height)?;
+    let empty : Grid = Grid::new(width, height)?;
+
+    match command {
+        Command::Copy => *clipboard = Some(selected),
+        Command::Cut => {
+            gol.insert_at(0, row_index as isize, column_index as isize, &empty, BlendMode::Overwrite)?;
+            *clipboard = Some(selected);
+        },
+        Command::Rotate => {
+            // as high as it was wide : checked first, as past the edges insert_at would clip or wrap
+            if row_index + width > gol.get_height() || column_index + height > gol.get_width() {
+                return Err(Error::OutOfBounds {
+                    row_index : (row_index + width) as isize - 1,
+                    column_index : (column_index + height) as isize - 1
+                });
+            }
+
+            gol.insert_at(0, row_index as isize, column_index as isize, &empty, BlendMode::Overwrite)?;
+            gol.insert_at(0, row_index as isize, column_index as isize, &selected.rotate_clockwise(), BlendMode::Overwrite)?;
+
+            return Ok(Selection { row_index : row_index, column_index : column_index, width : height, height : width });
+        },
+        Command::Clear => {
+            gol.insert_at(0, row_index as isize, column_index as isize, &empty, BlendMode::Overwrite)?;
+        },
+        _ => {}
+    }
+
+    return Ok(selection);
+}
+
+// saves with `save_to`, and reports where, or why not; a failed save does not close the viewer.
+fn save<F>(path : &str, save_to : F) where F : FnOnce(&str) -> Result<(), Box<dyn std::error::Error>> {
+    match save_to(path) {
+        Ok(()) => eprintln!("saved {}", path),
+        Err(e) => eprintln!("could not save {} : {}", path, e)
+    }
+}
+
+// the window, as shown.
+fn save_screenshot(path : &str, buffer : &[u32], width : usize, height : usize) -> Result<(), Box<dyn std::error::Error>> {
+    let rgba : Vec<u8> = buffer.iter()
+        .flat_map(|pixel| vec![(pixel >> 16) as u8, (pixel >> 8) as u8, *pixel as u8, 0xff])
+        .collect();
+    image::save_buffer(path, &rgba, width as u32, height as u32, image::RGBA(8))?;
+
+    return Ok(());
+}
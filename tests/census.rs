@@ -0,0 +1,84 @@
+// Census : apgcodes of well known Life objects, and a census of settled soups.
+
+use gol_rs::{BoundaryType, Grid, TCA2D};
+use gol_rs::census::{self, Census, ObjectKind};
+use gol_rs::rule::OuterTotalistic;
+
+// rows of . and o.
+fn parse(rows : &[&str]) -> Grid {
+    let width : usize = rows.iter().map(|row| row.len()).max().unwrap();
+    let mut grid : Grid = Grid::new(width, rows.len()).unwrap();
+
+    for (row_index, row) in rows.iter().enumerate() {
+        for (column_index, cell) in row.chars().enumerate() {
+            grid.set_value(row_index, column_index, (cell == 'o') as usize);
+        }
+    }
+
+    return grid;
+}
+
+#[test]
+fn life_objects() {
+    let objects : Vec<(&str, Vec<&str>)> = vec![
+        ("xs4_33", vec!["oo", "oo"]),
+        ("xs6_696", vec![".oo.", "o..o", ".oo."]),
+        ("xs7_2596", vec![".oo.", "o..o", ".o.o", "..o."]),
+        ("xs5_253", vec!["oo.", "o.o", ".o."]),
+        ("xs6_356", vec!["oo.", "o.o", ".oo"]),
+        ("xs4_252", vec![".o.", "o.o", ".o."]),
+        ("xs8_6996", vec![".oo.", "o..o", "o..o", ".oo."]),
+        ("xp2_7e", vec![".ooo", "ooo."]),
+        ("xp2_318c", vec!["oo..", "oo..", "..oo", "..oo"]),
+        ("xq4_6frc", vec![".o..o", "o....", "o...o", "oooo."]),
+        ("xp15_4r4z4r4", vec!["..o....o..", "oo.oooo.oo", "..o....o.."]),
+        ("xp3_co9nas0san9oczgoldlo0oldlogz1047210127401", vec![
+            "..ooo...ooo..",
+            ".............",
+            "o....o.o....o",
+            "o....o.o....o",
+            "o....o.o....o",
+            "..ooo...ooo..",
+            ".............",
+            "..ooo...ooo..",
+            "o....o.o....o",
+            "o....o.o....o",
+            "o....o.o....o",
+            ".............",
+            "..ooo...ooo.."]),
+    ];
+
+    for (apgcode, rows) in objects.iter() {
+        let (_, found) : (ObjectKind, String) = census::classify(&parse(rows), 224, 100).unwrap();
+        assert_eq!(&found, apgcode);
+
+        // every orientation has the same name
+        let (_, rotated) : (ObjectKind, String) = census::classify(&parse(rows).rotate_clockwise().reflect(), 224, 100).unwrap();
+        assert_eq!(&rotated, apgcode);
+    }
+}
+
+#[test]
+fn soups() {
+    let mut total = Census::new();
+
+    for seed in 0..4 {
+        let soup : Grid = Grid::random_patch(64, 64, 16, 16, 0.5, seed).unwrap();
+        let mut gol = TCA2D::new(OuterTotalistic::new(224), 64, 64, 1, true, BoundaryType::Torus, soup).unwrap();
+        gol.step_until_cycle(2000).unwrap();
+
+        // every live cell belongs to exactly one object, and every object settled
+        let objects = census::get_classified_objects(gol.get_page(0), 224).unwrap();
+        let population : usize = objects.iter().map(|object| object.cells.get_population()).sum();
+        assert_eq!(population, gol.get_page(0).get_population());
+        assert!(objects.iter().all(|object| object.kind != ObjectKind::Pathological));
+
+        let census : Census = census::take_census(gol.get_page(0), 224).unwrap();
+        assert_eq!(census.get_total(), objects.len());
+        total.merge(&census);
+    }
+
+    assert_eq!(total.get_count("xs4_33"), 6);
+    assert_eq!(total.get_count("xp2_7"), 7);
+    assert_eq!(total.get_total(), 23);
+}
@@ -0,0 +1,23 @@
+// Shared by the integration tests : bounded references for the unbounded universes.
+
+use gol_rs::{BoundaryType, Grid, Rule, TCA2D};
+use gol_rs::tca2d::Backend;
+
+/// Returns `soup` after `generations` on a null grid padded by `generations` cells on every
+/// side, so that nothing reaches the edge; its top left cell is the soup's, moved up and left
+/// by `generations`.
+pub fn get_padded_reference<R : Rule>(rule : R, soup : &Grid, moore : bool, generations : usize, backend : Backend) -> Grid {
+    let (width, height) : (usize, usize) = (soup.get_width() + 2 * generations, soup.get_height() + 2 * generations);
+
+    let mut padded : Grid = Grid::new(width, height).unwrap();
+    for row_index in 0..soup.get_height() {
+        for column_index in 0..soup.get_width() {
+            padded.set_value(generations + row_index, generations + column_index, soup.get_value(row_index, column_index));
+        }
+    }
+
+    let mut gol = TCA2D::with_backend(rule, width, height, 1, moore, BoundaryType::Null, padded, backend).unwrap();
+    gol.step(generations);
+
+    return gol.get_page(0).clone();
+}
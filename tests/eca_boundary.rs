@@ -0,0 +1,153 @@
+// Spacetime diagrams of rules 30 and 90 under each boundary type.
+
+use gol_rs::{BoundaryType, ECA};
+
+fn parse_row(row : &str) -> Vec<usize> {
+    return row.chars().map(|cell| cell.to_digit(10).unwrap() as usize).collect();
+}
+
+fn assert_diagram(rule : u8, boundary_type : BoundaryType, expected : &[&str]) {
+    let width : usize = expected[0].len();
+    let height : usize = expected.len();
+
+    let mut automata = ECA::new(rule, width, height, parse_row(expected[0]), boundary_type).unwrap();
+    automata.generate();
+
+    for (row_index, row) in expected.iter().enumerate() {
+        let actual : String = (0..width)
+            .map(|column_index| automata.get_value(row_index, column_index).to_string())
+            .collect();
+        assert_eq!(&actual, row, "rule {} {:?} row {}", rule, boundary_type, row_index);
+    }
+}
+
+#[test]
+fn rule_30_single_cell() {
+    // far enough from the edges for the boundary not to matter
+    assert_diagram(30, BoundaryType::Null, &[
+        "00000100000",
+        "00001110000",
+        "00011001000",
+        "00110111100",
+        "01100100010",
+        "11011110111",
+    ]);
+}
+
+#[test]
+fn rule_90_single_cell() {
+    assert_diagram(90, BoundaryType::Null, &[
+        "000000010000000",
+        "000000101000000",
+        "000001000100000",
+        "000010101010000",
+        "000100000001000",
+        "001010000010100",
+        "010001000100010",
+        "101010101010101",
+    ]);
+}
+
+#[test]
+fn rule_30_null() {
+    assert_diagram(30, BoundaryType::Null, &[
+        "10000010",
+        "11000111",
+        "10101100",
+        "10101010",
+        "10101011",
+        "10101010",
+    ]);
+}
+
+#[test]
+fn rule_30_periodic() {
+    assert_diagram(30, BoundaryType::Torus, &[
+        "10000010",
+        "11000110",
+        "10101100",
+        "10101011",
+        "00101010",
+        "01101011",
+    ]);
+    assert_diagram(30, BoundaryType::Cylinder, &[
+        "10000010",
+        "11000110",
+        "10101100",
+        "10101011",
+        "00101010",
+        "01101011",
+    ]);
+}
+
+#[test]
+fn rule_30_constant() {
+    assert_diagram(30, BoundaryType::Constant(1), &[
+        "10000010",
+        "01000110",
+        "01101100",
+        "01001011",
+        "01111010",
+        "01000010",
+    ]);
+}
+
+#[test]
+fn rule_30_reflective() {
+    assert_diagram(30, BoundaryType::Reflective, &[
+        "10000010",
+        "01000111",
+        "11101100",
+        "00001010",
+        "00011011",
+        "00110010",
+    ]);
+}
+
+#[test]
+fn rule_90_null() {
+    assert_diagram(90, BoundaryType::Null, &[
+        "10000010",
+        "01000101",
+        "10101000",
+        "00000100",
+        "00001010",
+        "00010001",
+    ]);
+}
+
+#[test]
+fn rule_90_periodic() {
+    assert_diagram(90, BoundaryType::Torus, &[
+        "10000010",
+        "01000100",
+        "10101010",
+        "00000000",
+        "00000000",
+        "00000000",
+    ]);
+}
+
+#[test]
+fn rule_90_constant() {
+    assert_diagram(90, BoundaryType::Constant(1), &[
+        "10000010",
+        "11000100",
+        "01101011",
+        "01100010",
+        "01110100",
+        "01010011",
+    ]);
+}
+
+#[test]
+fn rule_90_reflective() {
+    assert_diagram(90, BoundaryType::Reflective, &[
+        "10000010",
+        "11000101",
+        "01101001",
+        "11100111",
+        "00111100",
+        "01100110",
+    ]);
+}
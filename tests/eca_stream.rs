@@ -0,0 +1,88 @@
+// Streaming : the rows of a stream against a full spacetime diagram, in every update mode and
+// under each boundary, and what the png and csv sinks write.
+
+use gol_rs::{BoundaryType, ECA, ECARule, Grid};
+use gol_rs::eca::{self, UpdateMode};
+use gol_rs::eca_stream::{CsvSink, ECAStream, PngSink};
+
+use std::fs::File;
+
+const BOUNDARIES : [BoundaryType; 4] = [BoundaryType::Null, BoundaryType::Torus, BoundaryType::Reflective, BoundaryType::Constant(1)];
+const UPDATE_MODES : [UpdateMode; 4] = [UpdateMode::Synchronous, UpdateMode::SecondOrder, UpdateMode::Asynchronous,
+    UpdateMode::AlphaAsynchronous(0.5)];
+const HEIGHT : usize = 40;
+
+// the rows of `generations` generations of a stream, the first included.
+fn stream_rows(rule : &ECARule, initial_configuration : &[usize], boundary_type : BoundaryType, update_mode : UpdateMode, seed : u64) -> Vec<usize> {
+    let mut stream = ECAStream::new(rule.clone(), initial_configuration.to_vec(), boundary_type).unwrap();
+    stream.set_update_mode(update_mode, seed).unwrap();
+
+    let mut rows : Vec<usize> = Vec::new();
+    let mut collect = |_generation : usize, row : &[usize]| {
+        rows.extend_from_slice(row);
+        return Ok(());
+    };
+    stream.run(HEIGHT - 1, &mut [&mut collect]).unwrap();
+
+    return rows;
+}
+
+#[test]
+fn stream_against_diagram() {
+    let rules : [ECARule; 3] = [ECARule::elementary(30), ECARule::elementary(110), ECARule::totalistic(3, 1, "1599").unwrap()];
+
+    for (seed, rule) in rules.iter().enumerate() {
+        let states : usize = rule.get_states();
+        let initial_configuration : Vec<usize> = eca::random_states_configuration(67, states, 0.5, seed as u64).unwrap();
+
+        for boundary_type in BOUNDARIES.iter().copied() {
+            for update_mode in UPDATE_MODES.iter().copied() {
+                let mut automata = ECA::with_rule(rule.clone(), 67, HEIGHT, initial_configuration.clone(), boundary_type).unwrap();
+                automata.set_update_mode(update_mode, 42).unwrap();
+                automata.generate();
+
+                assert_eq!(&stream_rows(rule, &initial_configuration, boundary_type, update_mode, 42), automata.get_universe().get_grid(),
+                    "{} states, {:?}, {:?}", states, boundary_type, update_mode);
+            }
+        }
+    }
+}
+
+#[test]
+fn sinks() {
+    let rule : ECARule = ECARule::totalistic(3, 1, "1599").unwrap();
+    let mut initial_configuration : Vec<usize> = vec![0; 21];
+    initial_configuration[10] = 1;
+
+    let mut automata = ECA::with_rule(rule.clone(), 21, 12, initial_configuration.clone(), BoundaryType::Null).unwrap();
+    automata.generate();
+    let diagram : &Grid = automata.get_universe();
+
+    let path = std::env::temp_dir().join(format!("gol-rs-eca-stream-{}.png", std::process::id()));
+    let path : &str = path.to_str().unwrap();
+
+    let mut csv : Vec<u8> = Vec::new();
+    {
+        let mut stream = ECAStream::new(rule, initial_configuration, BoundaryType::Null).unwrap();
+        let mut png_sink = PngSink::create(path, 21, 12, 3).unwrap();
+        let mut csv_sink = CsvSink::new(&mut csv);
+        stream.run(11, &mut [&mut png_sink, &mut csv_sink]).unwrap();
+    }
+
+    // csv : one line per row, in the format of Grid::write_csv
+    let text : String = String::from_utf8(csv).unwrap();
+    assert_eq!(text.lines().count(), 12);
+    assert_eq!(text.lines().next().unwrap(), "0,0,0,0,0,0,0,0,0,0,1,0,0,0,0,0,0,0,0,0,0");
+    assert_eq!(Grid::from_csv_str(&text).unwrap().get_grid(), diagram.get_grid());
+
+    // png : 8 bit greyscale, 0 white and the last state black
+    let decoder = png::Decoder::new(File::open(path).unwrap());
+    let mut reader = decoder.read_info().unwrap();
+    let mut pixels : Vec<u8> = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    std::fs::remove_file(path).unwrap();
+
+    assert_eq!((info.width, info.height, info.color_type, info.bit_depth), (21, 12, png::ColorType::Grayscale, png::BitDepth::Eight));
+    let expected : Vec<u8> = diagram.get_grid().iter().map(|cell| [255, 128, 0][*cell]).collect();
+    assert_eq!(&pixels[..21 * 12], &expected[..]);
+}
@@ -0,0 +1,56 @@
+// HashLife against TCA2D, on random codes and soups. The soup sits in the middle of a null
+// grid wide enough that nothing reaches the edge, so the bounded and unbounded runs agree.
+
+mod common;
+
+use gol_rs::Grid;
+use gol_rs::hashlife::HashLife;
+use gol_rs::rule::OuterTotalistic;
+use gol_rs::tca2d::Backend;
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+const SOUP : usize = 20;
+
+fn get_hashlife(code : usize, soup : &Grid, generations : usize, max_nodes : usize) -> (HashLife, Grid) {
+    let mut hashlife = HashLife::new(code).unwrap();
+    hashlife.set_max_nodes(max_nodes);
+    hashlife.import_grid(soup, 0, 0);
+    hashlife.step(generations as u64).unwrap();
+
+    let size : usize = SOUP + 2 * generations;
+    let region : Grid = hashlife.export_grid(-(generations as i64), -(generations as i64), size, size).unwrap();
+
+    return (hashlife, region);
+}
+
+#[test]
+fn random_codes() {
+    let mut rng = Pcg64::seed_from_u64(0);
+
+    for seed in 0..40 {
+        let code : usize = rng.gen_range(0, 1 << 17) << 1; // no B0
+        let generations : usize = rng.gen_range(1, 40);
+        let soup : Grid = Grid::random(SOUP, SOUP, 0.4, seed).unwrap();
+
+        let reference : Grid = common::get_padded_reference(OuterTotalistic::new(code), &soup, true, generations, Backend::BitSliced);
+        let (hashlife, region) : (HashLife, Grid) = get_hashlife(code, &soup, generations, 1 << 22);
+
+        assert_eq!(region.get_grid(), reference.get_grid(), "code {} generations {}", code, generations);
+        assert_eq!(hashlife.get_population(), reference.get_population() as u64);
+        assert_eq!(hashlife.get_generation(), generations as u64);
+    }
+}
+
+#[test]
+fn garbage_collection() {
+    let soup : Grid = Grid::random(SOUP, SOUP, 0.5, 1).unwrap();
+    let reference : Grid = common::get_padded_reference(OuterTotalistic::new(224), &soup, true, 63, Backend::BitSliced);
+
+    // collected after every step
+    let (hashlife, region) : (HashLife, Grid) = get_hashlife(224, &soup, 63, 0);
+
+    assert_eq!(region.get_grid(), reference.get_grid());
+    assert!(hashlife.get_node_count() < 10_000);
+}
@@ -0,0 +1,215 @@
+// Pattern files : known files, and round trips of random grids.
+
+use gol_rs::{BoundaryType, Error, Grid, TCA2D};
+use gol_rs::pattern_io::{cells, life, macrocell, rle, Format, Pattern};
+use gol_rs::patterns;
+use gol_rs::rule::OuterTotalistic;
+
+const GOSPER_GLIDER_GUN : &str = "#N Gosper glider gun
+#O Bill Gosper
+#C A true period 30 glider gun.
+#C The first known gun and the first known finite pattern with unbounded growth.
+x = 36, y = 9, rule = B3/S23
+24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4b
+obo$10bo5bo7bo$11bo3bo$12b2o!
+";
+
+#[test]
+fn rle_file() {
+    let pattern : Pattern = rle::parse(GOSPER_GLIDER_GUN).unwrap();
+    assert_eq!(pattern.name.as_deref(), Some("Gosper glider gun"));
+    assert_eq!(pattern.comments.len(), 2);
+    assert_eq!(pattern.get_outer_totalistic().unwrap().code, 224);
+    assert_eq!(pattern.cells.get_grid(), patterns::get_pattern("gosper glider gun").unwrap().get_grid());
+
+    // written back line for line, but for the author
+    let written : String = rle::to_string(&pattern).unwrap();
+    let expected : Vec<&str> = GOSPER_GLIDER_GUN.lines().filter(|line| !line.starts_with("#O")).collect();
+    assert_eq!(written.lines().collect::<Vec<&str>>(), expected);
+
+    // a bounded grid : the rule holds a comma, and is written back whole
+    let bounded : Pattern = rle::parse("x = 3, y = 3, rule = B3/S23:T100,100\nbo$2bo$3o!").unwrap();
+    assert_eq!(bounded.rule.as_deref(), Some("B3/S23:T100,100"));
+    assert_eq!(bounded.get_outer_totalistic().unwrap().code, 224);
+    assert_eq!(rle::to_string(&bounded).unwrap(), "x = 3, y = 3, rule = B3/S23:T100,100\nbo$2bo$3o!\n");
+
+    assert!(rle::parse("bo$2bo$3o!").is_err());
+    assert!(rle::parse("x = 3, y = 3\nbo$2bq$3o!").is_err());
+}
+
+#[test]
+fn rle_round_trip() {
+    for seed in 0..20 {
+        let (width, height) : (usize, usize) = (1 + seed as usize * 7 % 90, 1 + seed as usize * 13 % 40);
+        let mut cells : Grid = Grid::random(width, height, 0.3, seed).unwrap();
+
+        // every other grid is multi-state
+        if seed % 2 == 1 {
+            for (index, cell) in cells.get_grid().clone().iter().enumerate() {
+                cells.set_value(index / width, index % width, cell * (index * 37 % 256));
+            }
+        }
+
+        let text : String = Format::Rle.to_string(&Pattern::new(cells.clone())).unwrap();
+        assert!(text.lines().all(|line| line.len() <= 70), "{}", text);
+
+        let parsed : Pattern = Format::Rle.parse(&text).unwrap();
+        assert_eq!((parsed.cells.get_width(), parsed.cells.get_height()), (width, height));
+        assert_eq!(parsed.cells.get_grid(), cells.get_grid());
+        assert!(parsed.rule.is_none());
+    }
+
+    // 255 is the last state with a tag
+    let last : Grid = Grid::from_vec(2, 1, vec![1, 255]).unwrap();
+    assert_eq!(rle::to_string(&Pattern::new(last.clone())).unwrap(), "x = 2, y = 1\nAyO!\n");
+    for state in [256, 300, 10000].iter().copied() {
+        let large : Grid = Grid::from_vec(2, 1, vec![1, state]).unwrap();
+        assert_eq!(rle::to_string(&Pattern::new(large)), Err(Error::InvalidState { state : state, states : 256 }));
+    }
+}
+
+#[test]
+fn tca2d_rule() {
+    let pattern : Pattern = rle::parse("x = 5, y = 5, rule = B36/S23\n2b3o$bo2bo$o3bo$o2bo$3o!").unwrap();
+    let mut cells : Grid = Grid::new(64, 64).unwrap();
+    for row_index in 0..5 {
+        for column_index in 0..5 {
+            cells.set_value(30 + row_index, 30 + column_index, pattern.cells.get_value(row_index, column_index));
+        }
+    }
+
+    let mut gol = TCA2D::new(OuterTotalistic::new(224), 64, 64, 1, true, BoundaryType::Null, cells).unwrap();
+    gol.set_rule(pattern.get_outer_totalistic().unwrap()).unwrap();
+
+    // the replicator copies itself every 12 generations in HighLife
+    gol.step(12);
+    assert_eq!(gol.get_page(0).get_population(), 2 * pattern.cells.get_population());
+
+    let written : Pattern = rle::parse(&rle::to_string(&gol.get_pattern(0)).unwrap()).unwrap();
+    assert_eq!(written.rule.as_deref(), Some("B36/S23"));
+    assert_eq!(written.cells.get_grid(), gol.get_page(0).get_grid());
+}
+
+#[test]
+fn formats() {
+    let formats : [Format; 5] = [Format::Rle, Format::Cells, Format::Life105, Format::Life106, Format::Macrocell];
+    let gun : Pattern = rle::parse(GOSPER_GLIDER_GUN).unwrap();
+
+    // the gun in every format, and random soups cropped to their bounding box
+    for seed in 0..10 {
+        let soup : Grid = Grid::random(3 + seed as usize * 11 % 50, 1 + seed as usize * 7 % 30, 0.4, seed).unwrap();
+        let mut soup : Pattern = Pattern::new(crop(&soup));
+        soup.rule = Some(String::from("B36/S23"));
+
+        for pattern in [&gun, &soup].iter() {
+            for format in formats.iter() {
+                let parsed : Pattern = format.parse(&format.to_string(pattern).unwrap()).unwrap();
+                assert_eq!(parsed.cells.get_grid(), pattern.cells.get_grid(), "{:?}", format);
+                assert_eq!((parsed.cells.get_width(), parsed.cells.get_height()), (pattern.cells.get_width(), pattern.cells.get_height()));
+
+                // plaintext and Life 1.06 have no rule
+                if *format != Format::Cells && *format != Format::Life106 {
+                    assert_eq!(parsed.get_outer_totalistic().unwrap().code, pattern.get_outer_totalistic().unwrap().code, "{:?}", format);
+                }
+            }
+        }
+    }
+
+    assert_eq!(Format::from_path("gun.rle").unwrap(), Format::Rle);
+    assert_eq!(Format::from_path("gun.cells").unwrap(), Format::Cells);
+    assert_eq!(Format::from_path("gun.lif").unwrap(), Format::Life106);
+    assert_eq!(Format::from_path("gun.mc").unwrap(), Format::Macrocell);
+    assert!(Format::from_path("gun").is_err());
+
+    // two state formats
+    let multi_state : Pattern = Pattern::new(Grid::from_vec(2, 1, vec![1, 2]).unwrap());
+    assert!(cells::to_string(&multi_state).is_err());
+    assert!(life::to_string_106(&multi_state).is_err());
+    let parsed : Pattern = macrocell::parse(&macrocell::to_string(&multi_state)).unwrap();
+    assert_eq!(parsed.cells.get_grid(), multi_state.cells.get_grid());
+}
+
+#[test]
+fn life_105_blocks() {
+    // a glider and a block, in two blocks on either side of the origin
+    let text = "#Life 1.05\n#D Two objects.\n#N\n#P -10 -10\n.*\n..*\n***\n#P 5 4\n**\n**\n";
+    let pattern : Pattern = life::parse(text).unwrap();
+
+    assert_eq!(pattern.comments, vec![String::from("Two objects.")]);
+    assert_eq!((pattern.cells.get_width(), pattern.cells.get_height()), (17, 16));
+    assert_eq!(pattern.cells.get_population(), 9);
+    assert_eq!(pattern.cells.get_value(0, 1), 1);
+    assert_eq!(pattern.cells.get_value(15, 16), 1);
+}
+
+#[test]
+fn macrocell_sharing() {
+    // 256 x 256 blocks on a 1024 x 1024 grid, written in a few nodes
+    let mut blocks : Grid = Grid::new(1024, 1024).unwrap();
+    for row_index in 0..1024 {
+        for column_index in 0..1024 {
+            if row_index % 4 < 2 && column_index % 4 < 2 {
+                blocks.set_value(row_index, column_index, 1);
+            }
+        }
+    }
+
+    let text : String = macrocell::to_string(&Pattern::new(blocks.clone()));
+    assert_eq!(text.lines().count(), 1 + 8);
+    assert_eq!(macrocell::parse(&text).unwrap().cells.get_grid(), crop(&blocks).get_grid());
+
+    // a glider and a block in the middle of a tree 2^41 cells wide : the grid read is small
+    let mut text : String = String::from("[M2] (golly 4.0)\n#R B3/S23\n.*$..*$***$\n**$**$\n4 0 0 0 1\n4 2 0 0 0\n");
+    for level in 5..=40 {
+        // the glider chain goes down the south east corners, the block chain the north west ones
+        text.push_str(&format!("{} 0 0 0 {}\n{} {} 0 0 0\n", level, 2 * level - 7, level, 2 * level - 6));
+    }
+    text.push_str("41 75 0 0 76\n");
+
+    let pattern : Pattern = macrocell::parse(&text).unwrap();
+    assert_eq!(pattern.cells.get_population(), 9);
+    assert_eq!((pattern.cells.get_width(), pattern.cells.get_height()), (10, 10));
+    assert!(macrocell::parse(&text.replace("41 75 0 0 76", "50 75 0 0 76")).is_err());
+}
+
+#[test]
+fn too_large() {
+    // run counts that overflow, and runs, rows and headers past 1 << 28 cells, are errors
+    for text in ["x = 1, y = 1\n99999999999999999999999o!", "x = 1, y = 1\n268435457o!", "x = 1, y = 1\n268435456$o!",
+        "x = 1, y = 1\n20000o$19999$o!", "x = 4000000000, y = 4000000000\n!", "x = 16385, y = 16384\n!"].iter() {
+        assert!(rle::parse(text).is_err(), "{}", text);
+    }
+
+    // but the largest runs read fine, without a cell per run
+    let pattern : Pattern = rle::parse("x = 1, y = 1\n100000b3o$2o!").unwrap();
+    assert_eq!((pattern.cells.get_width(), pattern.cells.get_height()), (100003, 2));
+    assert_eq!(pattern.cells.get_population(), 5);
+
+    let wide : String = format!("{}\n{}", "O".repeat(20000), ".\n".repeat(20000));
+    assert!(cells::parse(&wide).is_err());
+
+    // coordinates whose span overflows an i64
+    for text in ["#Life 1.06\n9223372036854775807 0\n-9223372036854775808 0", "#Life 1.06\n0 9223372036854775807\n0 -1",
+        "#Life 1.06\n0 0\n0 20000\n20000 0"].iter() {
+        assert!(matches!(life::parse(text), Err(Error::InvalidDimensions { .. })), "{}", text);
+    }
+    assert!(life::parse("#Life 1.05\n#P 9223372036854775807 0\n.*").is_err());
+    assert!(life::parse("#Life 1.05\n#P 0 9223372036854775807\n*\n*").is_err());
+}
+
+// the bounding box of the nonzero cells.
+fn crop(grid : &Grid) -> Grid {
+    let cells : Vec<(usize, usize)> = (0..grid.get_height())
+        .flat_map(|row_index| (0..grid.get_width()).map(move |column_index| (row_index, column_index)))
+        .filter(|(row_index, column_index)| grid.get_value(*row_index, *column_index) != 0)
+        .collect();
+    let (min_row, max_row) = (cells.iter().map(|cell| cell.0).min().unwrap(), cells.iter().map(|cell| cell.0).max().unwrap());
+    let (min_column, max_column) = (cells.iter().map(|cell| cell.1).min().unwrap(), cells.iter().map(|cell| cell.1).max().unwrap());
+
+    let mut cropped : Grid = Grid::new(max_column - min_column + 1, max_row - min_row + 1).unwrap();
+    for (row_index, column_index) in cells {
+        cropped.set_value(row_index - min_row, column_index - min_column, 1);
+    }
+
+    return cropped;
+}
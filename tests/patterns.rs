@@ -0,0 +1,89 @@
+// Patterns : the built-in library against known apgcodes, the Lexicon parser, and insertion.
+
+use gol_rs::{BoundaryType, Grid, TCA2D};
+use gol_rs::census::{self, ObjectKind};
+use gol_rs::patterns::{self, Lexicon};
+use gol_rs::rule::OuterTotalistic;
+use gol_rs::tca2d::BlendMode;
+
+#[test]
+fn library() {
+    let objects : [(&str, &str); 14] = [
+        ("block", "xs4_33"),
+        ("beehive", "xs6_696"),
+        ("loaf", "xs7_2596"),
+        ("boat", "xs5_253"),
+        ("tub", "xs4_252"),
+        ("pond", "xs8_6996"),
+        ("blinker", "xp2_7"),
+        ("toad", "xp2_7e"),
+        ("beacon", "xp2_318c"),
+        ("pentadecathlon", "xp15_4r4z4r4"),
+        ("glider", "xq4_153"),
+        ("lwss", "xq4_6frc"),
+        ("mwss", "xq4_27dee6"),
+        ("hwss", "xq4_27deee6"),
+    ];
+
+    for (name, apgcode) in objects.iter() {
+        let pattern : Grid = patterns::get_pattern(name).unwrap();
+
+        // every orientation is the same object
+        for quarter_turns in 0..4 {
+            for reflect in [false, true].iter().copied() {
+                let (_, found) : (ObjectKind, String) = census::classify(&patterns::orient(&pattern, quarter_turns, reflect), 224, 100).unwrap();
+                assert_eq!(&found, apgcode, "{} {} {}", name, quarter_turns, reflect);
+            }
+        }
+    }
+
+    assert!(patterns::get_pattern_names().iter().all(|name| patterns::get_pattern(name).is_ok()));
+}
+
+#[test]
+fn gosper_glider_gun() {
+    let gun : Grid = patterns::get_pattern("gosper glider gun").unwrap();
+    let mut gol = TCA2D::new(OuterTotalistic::new(224), 60, 40, 1, true, BoundaryType::Null, Grid::new(60, 40).unwrap()).unwrap();
+    gol.insert_pattern(0, 1, 1, &gun, 0, false, BlendMode::Overwrite).unwrap();
+
+    // one glider every 30 generations
+    gol.step(120);
+    let objects = census::get_classified_objects(gol.get_page(0), 224).unwrap();
+    let gliders : usize = objects.iter().filter(|object| object.apgcode == "xq4_153").count();
+    assert_eq!(gliders, 4);
+}
+
+#[test]
+fn lexicon() {
+    let text = "LIFE LEXICON\n\
+        \n\
+        :aircraft carrier: (p1) This is the smallest still life that has more than one island.\n\
+        \t**..\n\
+        \t*..*\n\
+        \t..**\n\
+        :glider: (c/4 diagonally, p4) The smallest, most common and first discovered spaceship.\n\
+        \tOOO\n\
+        \tO..\n\
+        \t.O.\n\
+        A second diagram is not the pattern.\n\
+        \t.O.\n\
+        \tO..\n\
+        \tOOO\n\
+        :Life: The cellular automaton.\n\
+        \tThe rules are given in {B3/S23}.\n\
+        :ship: (p1) The only 6-cell still life with more than one island... not really.\n\
+        \t**.\n\
+        \t*.*\n\
+        \t.**";
+
+    let lexicon = Lexicon::parse(text);
+    assert_eq!(lexicon.get_names(), vec!["aircraft-carrier", "glider", "ship"]);
+
+    let carrier : &Grid = lexicon.get("Aircraft Carrier").unwrap();
+    assert_eq!((carrier.get_width(), carrier.get_height()), (4, 3));
+    assert_eq!(carrier.get_grid(), &vec![1, 1, 0, 0, 1, 0, 0, 1, 0, 0, 1, 1]);
+
+    assert_eq!(lexicon.get("glider").unwrap().get_grid(), &vec![1, 1, 1, 1, 0, 0, 0, 1, 0]);
+    assert_eq!(census::classify(lexicon.get("ship").unwrap(), 224, 10).unwrap().1, "xs6_356");
+    assert!(lexicon.get("life").is_none());
+}
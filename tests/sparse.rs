@@ -0,0 +1,130 @@
+// The sparse universe : the soup placed across tile corners, stepped against a padded TCA2D
+// page for any rule, against HashLife for Life, and a single cell grown across tile edges.
+
+mod common;
+
+use gol_rs::{Grid, Rule};
+use gol_rs::hashlife::HashLife;
+use gol_rs::rule::{ClosureRule, OuterTotalistic, Totalistic};
+use gol_rs::sparse::SparseUniverse;
+use gol_rs::tca2d::Backend;
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+const SOUP : usize = 20;
+
+// steps `soup` in a sparse universe with `rule`, and in a padded TCA2D page with `reference_rule`.
+fn check_against_tca2d<R : Rule, S : Rule>(reference_rule : R, rule : S, soup : &Grid, moore : bool, generations : usize) {
+    let name : String = rule.get_name();
+    let reference : Grid = common::get_padded_reference(reference_rule, soup, moore, generations, Backend::Reference);
+    let offset : i64 = generations as i64;
+
+    // placed across tile corners
+    let mut sparse = SparseUniverse::new(rule, moore).unwrap();
+    sparse.import_grid(soup, -7, -9);
+    sparse.step(generations);
+
+    assert_eq!(sparse.export_grid(-7 - offset, -9 - offset, reference.get_width(), reference.get_height()).unwrap().get_grid(),
+        reference.get_grid(), "{} moore {} generations {}", name, moore, generations);
+    assert_eq!(sparse.get_population(), reference.get_population());
+}
+
+#[test]
+fn outer_totalistic_codes() {
+    let mut rng = Pcg64::seed_from_u64(0);
+
+    for seed in 0..20 {
+        let code : usize = rng.gen_range(0, 1 << 17) << 1; // no B0
+        let generations : usize = rng.gen_range(1, 30);
+        let soup : Grid = Grid::random(SOUP, SOUP, 0.4, seed).unwrap();
+
+        check_against_tca2d(OuterTotalistic::new(code), OuterTotalistic::new(code), &soup, seed % 2 == 0, generations);
+    }
+}
+
+#[test]
+fn totalistic_codes() {
+    let mut rng = Pcg64::seed_from_u64(1);
+
+    for seed in 0..10 {
+        let code : usize = rng.gen_range(0, 1 << 9) << 1; // f(0) = 0
+        let soup : Grid = Grid::random(SOUP, SOUP, 0.4, seed).unwrap();
+
+        check_against_tca2d(Totalistic::new(code), Totalistic::new(code), &soup, seed % 2 == 0, 25);
+    }
+}
+
+#[test]
+fn multi_state() {
+    // Brian's Brain : 1 (firing) -> 2 (refractory) -> 0, and 0 -> 1 next to exactly two firing cells
+    let brain = |radius : &[usize]| -> usize {
+        return match radius[4] {
+            1 => 2,
+            2 => 0,
+            _ => (radius.iter().filter(|cell| **cell == 1).count() == 2) as usize
+        };
+    };
+
+    for seed in 0..4 {
+        let soup : Grid = Grid::random(SOUP, SOUP, 0.3, seed).unwrap();
+        check_against_tca2d(ClosureRule::new("brain", brain), ClosureRule::new("brain", brain), &soup, true, 30);
+    }
+}
+
+#[test]
+fn hashlife() {
+    let soup : Grid = Grid::random(SOUP, SOUP, 0.5, 3).unwrap();
+
+    let mut sparse = SparseUniverse::new(OuterTotalistic::new(224), true).unwrap();
+    sparse.import_grid(&soup, 0, 0);
+    sparse.step(300);
+
+    let mut hashlife = HashLife::new(224).unwrap();
+    hashlife.import_grid(&soup, 0, 0);
+    hashlife.step(300).unwrap();
+
+    let (min_row, min_column, max_row, max_column) : (i64, i64, i64, i64) = hashlife.get_bounding_box().unwrap();
+    assert_eq!(sparse.get_bounding_box(), hashlife.get_bounding_box());
+
+    let (width, height) : (usize, usize) = ((max_column - min_column + 1) as usize, (max_row - min_row + 1) as usize);
+    assert_eq!(sparse.export_grid(min_row, min_column, width, height).unwrap().get_grid(),
+        hashlife.export_grid(min_row, min_column, width, height).unwrap().get_grid());
+}
+
+#[test]
+fn tile_edges() {
+    // every cell next to a nonzero one becomes 1 : a single cell grows into a 3 x 3 block, on
+    // every tile it reaches
+    let grow = |radius : &[usize]| -> usize { return radius.iter().any(|cell| *cell != 0) as usize; };
+    let block : Grid = Grid::from_vec(3, 3, vec![1; 9]).unwrap();
+
+    // (row, column, tiles reached) : corners, edges and the middle of a tile
+    let cells : [(i64, i64, usize); 8] = [(0, 0, 4), (0, 5, 2), (5, 0, 2), (5, 5, 1), (31, 31, 4), (0, 31, 4), (15, 31, 2), (31, 15, 2)];
+
+    for (row_index, column_index, tiles) in cells.iter().copied() {
+        let mut sparse = SparseUniverse::new(ClosureRule::new("grow", grow), true).unwrap();
+        sparse.set_value(row_index, column_index, 1);
+        sparse.step(1);
+
+        assert_eq!(sparse.get_tile_count(), tiles, "({}, {})", row_index, column_index);
+        assert_eq!(sparse.get_population(), 9);
+        assert_eq!(sparse.export_grid(row_index - 1, column_index - 1, 3, 3).unwrap().get_grid(), block.get_grid());
+
+        // the edges of a stepped tile, two cells further
+        sparse.step(2);
+        assert_eq!(sparse.get_population(), 49, "({}, {})", row_index, column_index);
+    }
+
+    // clearing the only cell on an edge, and putting one back on another
+    let mut sparse = SparseUniverse::new(ClosureRule::new("grow", grow), true).unwrap();
+    sparse.set_value(0, 31, 1);
+    sparse.set_value(5, 5, 1);
+    sparse.set_value(0, 31, 0);
+    sparse.set_value(31, 7, 2);
+    sparse.step(1);
+
+    assert_eq!(sparse.get_tile_count(), 2);
+    assert_eq!(sparse.get_population(), 18);
+    assert_eq!(sparse.get_bounding_box(), Some((4, 4, 32, 8)));
+}
@@ -0,0 +1,60 @@
+// Sweeps : resuming after an interruption runs every job once, whatever was cut short.
+
+use gol_rs::grid::Symmetry;
+use gol_rs::sweep::{self, OutputFormat, RuleKind, SweepConfiguration};
+
+fn get_configuration() -> SweepConfiguration {
+    return SweepConfiguration {
+        codes : vec![224, 6152],
+        kinds : vec![RuleKind::OuterTotalistic],
+        neighbourhoods : vec![true],
+        seeds : vec![1, 2],
+        width : 12,
+        height : 10,
+        density : 0.5,
+        symmetry : Symmetry::Asymmetric,
+        generations : 20,
+        threads : 2,
+    };
+}
+
+fn get_sorted_lines(contents : &str) -> Vec<String> {
+    let mut lines : Vec<String> = contents.lines().map(String::from).collect();
+    lines.sort();
+    return lines;
+}
+
+#[test]
+fn resume() {
+    let configuration = get_configuration();
+
+    for format in [OutputFormat::Csv, OutputFormat::Json].iter().copied() {
+        let path = std::env::temp_dir().join(format!("gol-rs-sweep-{}-{:?}", std::process::id(), format));
+        let path : &str = path.to_str().unwrap();
+
+        assert_eq!(sweep::run_sweep(&configuration, path, format, false).unwrap(), 4);
+        let full : String = std::fs::read_to_string(path).unwrap();
+        let lines : Vec<&str> = full.lines().collect();
+
+        // two whole jobs, then one cut just after its last comma : every field is there, but not the period
+        let header : usize = if format == OutputFormat::Csv { 1 } else { 0 };
+        let cut : &str = lines[header + 2];
+        let cut : &str = &cut[..=cut.rfind(',').unwrap()];
+        std::fs::write(path, format!("{}\n{}", lines[..header + 2].join("\n"), cut)).unwrap();
+
+        assert_eq!(sweep::run_sweep(&configuration, path, format, true).unwrap(), 2, "{:?}", format);
+        let resumed : String = std::fs::read_to_string(path).unwrap();
+        assert!(resumed.ends_with('\n'));
+        assert_eq!(get_sorted_lines(&resumed), get_sorted_lines(&full), "{:?}", format);
+        assert_eq!(resumed.lines().filter(|line| line.starts_with("code,")).count(), header);
+
+        // a header cut short is written again
+        if format == OutputFormat::Csv {
+            std::fs::write(path, "code,ki").unwrap();
+            assert_eq!(sweep::run_sweep(&configuration, path, format, true).unwrap(), 4);
+            assert_eq!(get_sorted_lines(&std::fs::read_to_string(path).unwrap()), get_sorted_lines(&full));
+        }
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
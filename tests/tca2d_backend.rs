@@ -0,0 +1,102 @@
+// The bit-sliced backend against the reference one, on random codes, sizes and soups.
+
+use gol_rs::{BoundaryType, Grid, TCA2D};
+use gol_rs::rule::{OuterTotalistic, Totalistic};
+use gol_rs::tca2d::Backend;
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+
+const SIZES : [(usize, usize); 6] = [(1, 1), (1, 7), (7, 1), (63, 5), (64, 9), (130, 33)];
+
+fn assert_same<R : gol_rs::Rule, S : gol_rs::Rule>(reference_rule : R,
+    bit_sliced_rule : S,
+    soup : &Grid,
+    moore : bool,
+    boundary_type : BoundaryType) {
+
+    let (width, height) : (usize, usize) = (soup.get_width(), soup.get_height());
+    let depth : usize = 8;
+
+    let mut reference = TCA2D::new(reference_rule, width, height, depth, moore, boundary_type, soup.clone()).unwrap();
+    let mut bit_sliced = TCA2D::with_backend(bit_sliced_rule, width, height, depth, moore, boundary_type, soup.clone(), Backend::BitSliced).unwrap();
+    reference.generate();
+    bit_sliced.generate();
+
+    for page_index in 0..depth {
+        assert_eq!(reference.get_page(page_index).get_grid(), bit_sliced.get_page(page_index).get_grid(),
+            "{} {}x{} moore {} {:?} page {}", reference.get_rule().get_name(), width, height, moore, boundary_type, page_index);
+    }
+}
+
+#[test]
+fn outer_totalistic_codes() {
+    let mut rng = Pcg64::seed_from_u64(0);
+
+    for (seed, (width, height)) in SIZES.iter().enumerate() {
+        let soup : Grid = Grid::random(*width, *height, 0.4, seed as u64).unwrap();
+
+        for _ in 0..20 {
+            let code : usize = rng.gen_range(0, 1 << 18);
+
+            for moore in &[true, false] {
+                for boundary_type in &[BoundaryType::Null, BoundaryType::Torus] {
+                    assert_same(OuterTotalistic::new(code), OuterTotalistic::new(code), &soup, *moore, *boundary_type);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn totalistic_codes() {
+    let mut rng = Pcg64::seed_from_u64(1);
+    let soup : Grid = Grid::random(70, 40, 0.5, 3).unwrap();
+
+    for _ in 0..50 {
+        let code : usize = rng.gen_range(0, 1 << 10);
+
+        for moore in &[true, false] {
+            assert_same(Totalistic::new(code), Totalistic::new(code), &soup, *moore, BoundaryType::Torus);
+        }
+    }
+}
+
+#[test]
+fn step() {
+    // stepping past the depth only unpacks the generations kept in the stack
+    let soup : Grid = Grid::random(100, 37, 0.5, 4).unwrap();
+
+    for boundary_type in &[BoundaryType::Null, BoundaryType::Torus] {
+        let mut reference = TCA2D::new(OuterTotalistic::new(224), 100, 37, 3, true, *boundary_type, soup.clone()).unwrap();
+        let mut bit_sliced = TCA2D::with_backend(OuterTotalistic::new(224), 100, 37, 3, true, *boundary_type, soup.clone(), Backend::BitSliced).unwrap();
+
+        for generations in [0, 1, 2, 3, 4, 10].iter().copied() {
+            reference.step(generations);
+            bit_sliced.step(generations);
+
+            for page_index in 0..3 {
+                assert_eq!(reference.get_page(page_index).get_grid(), bit_sliced.get_page(page_index).get_grid(),
+                    "{:?} {} generations, page {}", boundary_type, generations, page_index);
+            }
+        }
+
+        // edits between steps are picked up
+        reference.set_value(2, 5, 5, 1);
+        bit_sliced.set_value(2, 5, 5, 1);
+        reference.increase_generation(1);
+        bit_sliced.increase_generation(1);
+        reference.step(5);
+        bit_sliced.step(5);
+        assert_eq!(reference.get_page(2).get_grid(), bit_sliced.get_page(2).get_grid());
+    }
+}
+
+#[test]
+fn unsupported_boundary() {
+    let soup : Grid = Grid::random(10, 10, 0.5, 0).unwrap();
+
+    for boundary_type in &[BoundaryType::Cylinder, BoundaryType::Klein, BoundaryType::Reflective, BoundaryType::Constant(1)] {
+        assert!(TCA2D::with_backend(OuterTotalistic::new(224), 10, 10, 1, true, *boundary_type, soup.clone(), Backend::BitSliced).is_err());
+    }
+}
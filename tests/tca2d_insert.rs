@@ -0,0 +1,171 @@
+// Insertion : where a 2 x 2 block lands across each boundary, and how it blends, worked out by hand.
+
+use gol_rs::{BoundaryType, Error, Grid, TCA2D};
+use gol_rs::rule::OuterTotalistic;
+use gol_rs::tca2d::{Backend, BlendMode, Placement};
+
+use BoundaryType::{Null, Cylinder, Moebius, Torus, Klein, Constant, Reflective};
+
+// inserts 1 2 / 3 4 into an empty 5 x 4 page, with its top left cell at (row_index, column_index).
+fn check(boundary_types : &[BoundaryType], row_index : isize, column_index : isize, expected : &[usize], written : usize, wrapped : usize) {
+    let block : Grid = Grid::from_vec(2, 2, vec![1, 2, 3, 4]).unwrap();
+
+    for boundary_type in boundary_types.iter().copied() {
+        let mut gol = TCA2D::new(OuterTotalistic::new(224), 5, 4, 1, true, boundary_type, Grid::new(5, 4).unwrap()).unwrap();
+        let placement : Placement = gol.insert_at(0, row_index, column_index, &block, BlendMode::Overwrite).unwrap();
+
+        let clipped : usize = 4 - written;
+        assert_eq!(placement, Placement { written : written, wrapped : wrapped, clipped : clipped, changed : written },
+            "{:?} ({}, {})", boundary_type, row_index, column_index);
+        assert_eq!(gol.get_page(0).get_grid(), &expected.to_vec(), "{:?} ({}, {})", boundary_type, row_index, column_index);
+    }
+}
+
+#[test]
+fn boundaries() {
+    // inside, the same everywhere
+    check(&[Null, Cylinder, Moebius, Torus, Klein, Constant(1), Reflective], 1, 1, &[
+        0, 0, 0, 0, 0,
+        0, 1, 2, 0, 0,
+        0, 3, 4, 0, 0,
+        0, 0, 0, 0, 0], 4, 0);
+
+    // across the right edge, flipped upside down on the Moebius strip and the Klein bottle
+    check(&[Null], 1, 4, &[
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 1,
+        0, 0, 0, 0, 3,
+        0, 0, 0, 0, 0], 2, 0);
+    check(&[Cylinder, Torus], 1, 4, &[
+        0, 0, 0, 0, 0,
+        2, 0, 0, 0, 1,
+        4, 0, 0, 0, 3,
+        0, 0, 0, 0, 0], 4, 2);
+    check(&[Moebius, Klein], 1, 4, &[
+        0, 0, 0, 0, 0,
+        4, 0, 0, 0, 1,
+        2, 0, 0, 0, 3,
+        0, 0, 0, 0, 0], 4, 2);
+
+    // across the bottom right corner
+    check(&[Null], 3, 4, &[
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 1], 1, 0);
+    check(&[Cylinder], 3, 4, &[
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+        2, 0, 0, 0, 1], 2, 1);
+    check(&[Moebius], 3, 4, &[
+        2, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 1], 2, 1);
+    check(&[Torus], 3, 4, &[
+        4, 0, 0, 0, 3,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+        2, 0, 0, 0, 1], 4, 3);
+    check(&[Klein], 3, 4, &[
+        2, 0, 0, 0, 3,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+        4, 0, 0, 0, 1], 4, 3);
+
+    // across the top left corner
+    check(&[Null], -1, -1, &[
+        4, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0], 1, 0);
+    check(&[Cylinder], -1, -1, &[
+        4, 0, 0, 0, 3,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0], 2, 1);
+    check(&[Moebius], -1, -1, &[
+        4, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 3], 2, 1);
+    check(&[Torus], -1, -1, &[
+        4, 0, 0, 0, 3,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+        2, 0, 0, 0, 1], 4, 3);
+    check(&[Klein], -1, -1, &[
+        4, 0, 0, 0, 1,
+        0, 0, 0, 0, 0,
+        0, 0, 0, 0, 0,
+        2, 0, 0, 0, 3], 4, 3);
+
+    // nothing outside a constant or reflective page is writable, and nothing is written
+    let block : Grid = Grid::from_vec(2, 2, vec![1, 2, 3, 4]).unwrap();
+    for boundary_type in [Constant(1), Reflective].iter().copied() {
+        for (row_index, column_index) in [(1, 4), (3, 4), (-1, -1)].iter().copied() {
+            let mut gol = TCA2D::new(OuterTotalistic::new(224), 5, 4, 1, true, boundary_type, Grid::new(5, 4).unwrap()).unwrap();
+            assert!(matches!(gol.insert_at(0, row_index, column_index, &block, BlendMode::Overwrite), Err(Error::OutOfBounds { .. })));
+            assert_eq!(gol.get_page(0).get_population(), 0, "{:?} ({}, {})", boundary_type, row_index, column_index);
+        }
+    }
+}
+
+#[test]
+fn blend_modes() {
+    let page : Grid = Grid::from_vec(3, 2, vec![
+        1, 1, 0,
+        2, 0, 0]).unwrap();
+    let block : Grid = Grid::from_vec(2, 2, vec![
+        0, 1,
+        3, 2]).unwrap();
+
+    // (blend, expected page, written, changed)
+    let cases : [(BlendMode, [usize; 6], usize, usize); 4] = [
+        (BlendMode::Overwrite, [0, 1, 0, 3, 2, 0], 4, 3),
+        (BlendMode::Or, [1, 1, 0, 3, 2, 0], 4, 2),
+        (BlendMode::Xor, [1, 0, 0, 1, 2, 0], 4, 3),
+        (BlendMode::IgnoreZeros, [1, 1, 0, 3, 2, 0], 3, 2),
+    ];
+
+    for (blend, expected, written, changed) in cases.iter().copied() {
+        let mut gol = TCA2D::new(OuterTotalistic::new(224), 3, 2, 1, true, Torus, page.clone()).unwrap();
+        let placement : Placement = gol.insert_at(0, 0, 0, &block, blend).unwrap();
+
+        assert_eq!(placement, Placement { written : written, wrapped : 0, clipped : 0, changed : changed }, "{:?}", blend);
+        assert_eq!(gol.get_page(0).get_grid(), &expected.to_vec(), "{:?}", blend);
+    }
+}
+
+#[test]
+fn errors() {
+    let page : Grid = Grid::new(8, 8).unwrap();
+    let large : Grid = Grid::new(9, 2).unwrap();
+
+    // larger than the page along a wrapping edge
+    let mut torus = TCA2D::new(OuterTotalistic::new(224), 8, 8, 1, true, BoundaryType::Torus, page.clone()).unwrap();
+    assert!(torus.insert_at(0, 0, 0, &large, BlendMode::Overwrite).is_err());
+
+    // but clipped past null edges
+    let mut null = TCA2D::new(OuterTotalistic::new(224), 8, 8, 1, true, BoundaryType::Null, page.clone()).unwrap();
+    let mut ones : Grid = large.clone();
+    ones.set_value(0, 8, 1);
+    assert_eq!(null.insert_at(0, 0, 0, &ones, BlendMode::Overwrite).unwrap().clipped, 2);
+
+    // zeros are clipped like they are written, unless skipped
+    let placement : Placement = null.insert_at(0, 7, 7, &Grid::from_vec(2, 2, vec![0, 1, 0, 0]).unwrap(), BlendMode::Overwrite).unwrap();
+    assert_eq!((placement.written, placement.clipped), (1, 3));
+    let placement : Placement = null.insert_at(0, 7, 7, &Grid::from_vec(2, 2, vec![0, 1, 0, 0]).unwrap(), BlendMode::IgnoreZeros).unwrap();
+    assert_eq!((placement.written, placement.clipped), (0, 1));
+
+    // a page past the depth
+    let before : Vec<usize> = null.get_page(0).get_grid().clone();
+    assert_eq!(null.insert_at(1, 0, 0, &ones, BlendMode::Overwrite), Err(Error::InvalidPage { page_index : 1, depth : 1 }));
+    assert_eq!(null.get_page(0).get_grid(), &before);
+
+    // the bit-sliced backend only holds two states
+    let mut bit_sliced = TCA2D::with_backend(OuterTotalistic::new(224), 8, 8, 1, true, BoundaryType::Torus, page, Backend::BitSliced).unwrap();
+    let two : Grid = Grid::from_vec(1, 1, vec![2]).unwrap();
+    assert_eq!(bit_sliced.insert_at(0, 0, 0, &two, BlendMode::Overwrite), Err(Error::InvalidState { state : 2, states : 2 }));
+}
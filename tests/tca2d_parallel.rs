@@ -0,0 +1,33 @@
+// Parallel stepping : every thread count gives the serial generations, on every boundary.
+
+use gol_rs::{BoundaryType, Grid, TCA2D};
+use gol_rs::rule::OuterTotalistic;
+
+const BOUNDARIES : [BoundaryType; 7] = [BoundaryType::Null, BoundaryType::Cylinder, BoundaryType::Moebius, BoundaryType::Torus,
+    BoundaryType::Klein, BoundaryType::Constant(1), BoundaryType::Reflective];
+
+#[test]
+fn step_and_generate() {
+    // 13 rows : bands of uneven height, and more threads than rows
+    let soup : Grid = Grid::random(21, 13, 0.5, 2).unwrap();
+
+    for boundary_type in BOUNDARIES.iter().copied() {
+        for depth in [1, 4].iter().copied() {
+            let mut serial = TCA2D::new(OuterTotalistic::new(224), 21, 13, depth, true, boundary_type, soup.clone()).unwrap();
+            serial.generate();
+            serial.step(7);
+
+            for threads in [2, 3, 5, 13, 20].iter().copied() {
+                let mut parallel = TCA2D::new(OuterTotalistic::new(224), 21, 13, depth, true, boundary_type, soup.clone()).unwrap();
+                parallel.generate_parallel(threads);
+                parallel.step_parallel(2, threads);
+                parallel.step_parallel(5, threads);
+
+                for page_index in 0..depth {
+                    assert_eq!(parallel.get_page(page_index).get_grid(), serial.get_page(page_index).get_grid(),
+                        "{:?} depth {} threads {} page {}", boundary_type, depth, threads, page_index);
+                }
+            }
+        }
+    }
+}
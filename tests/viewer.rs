@@ -0,0 +1,106 @@
+// Viewer : zoom, pan, selections and their edits, without a window.
+
+use gol_rs::{BoundaryType, Error, Grid, TCA2D};
+use gol_rs::rule::OuterTotalistic;
+use gol_rs::viewer::{self, Colouring, Command, Selection, Viewport, MAX_ZOOM};
+
+use minifb::Key;
+
+#[test]
+fn zoom_and_pan() {
+    // larger than the screen : one pixel a cell, showing the middle
+    let mut viewport = Viewport::new(2000, 1000);
+    assert_eq!((viewport.width, viewport.height, viewport.zoom), (1024, 768, 1));
+    assert_eq!(viewport.get_cell(512.0, 384.0), (500, 1000));
+
+    // zooms in and out by powers of two, up to MAX_ZOOM, around the mouse
+    for zoom in [2, 4, 8, 16, 32, 64].iter().copied() {
+        viewport.zoom_at(100.0, 700.0, zoom);
+        assert_eq!(viewport.zoom, zoom.min(MAX_ZOOM));
+        assert_eq!(viewport.get_cell(100.0, 700.0), (816, 588));
+    }
+    viewport.apply(Command::ZoomOut);
+    assert_eq!(viewport.zoom, 16);
+
+    // part of the page stays in the window
+    viewport.move_to(-1000, 5000);
+    assert_eq!((viewport.row_index, viewport.column_index), (-47, 1999));
+    viewport.apply(Command::Pan(-1, 1));
+    assert_eq!((viewport.row_index, viewport.column_index), (-47, 1999));
+    viewport.apply(Command::Pan(1, -1));
+    assert_eq!((viewport.row_index, viewport.column_index), (-41, 1991));
+
+    viewport.apply(Command::Fit);
+    assert_eq!(viewport, Viewport::new(2000, 1000));
+}
+
+#[test]
+fn render() {
+    // a 4 x 3 page, in the middle of a 256 x 256 window at 32 pixels a cell
+    let page : Grid = Grid::from_vec(4, 3, vec![
+        1, 0, 0, 2,
+        0, 0, 0, 0,
+        0, 0, 0, 1]).unwrap();
+    let viewport = Viewport::new(4, 3);
+    assert_eq!((viewport.zoom, viewport.row_index, viewport.column_index), (32, -2, -2));
+
+    let mut buffer : Vec<u32> = vec![0; 256 * 256];
+    let selection = Selection { row_index : 1, column_index : 1, width : 2, height : 2 };
+    viewport.render(&page, Colouring::Raw, Some(selection), &mut buffer);
+
+    let pixel = |row_index : usize, column_index : usize| buffer[(row_index * 32 + 16) * 256 + column_index * 32 + 16];
+    assert_eq!(pixel(0, 0), pixel(7, 7), "past the edges");
+    assert_eq!(pixel(2, 2), viewer::get_colour(1, Colouring::Raw));
+    assert_eq!(pixel(2, 5), viewer::get_colour(2, Colouring::Raw));
+    assert_eq!(pixel(3, 3), viewer::get_colour(0, Colouring::Raw));
+
+    // the outline of the selection, inside its cells
+    let (top, left) : (usize, usize) = (3 * 32, 3 * 32);
+    assert_ne!(buffer[top * 256 + left + 10], pixel(3, 3));
+    assert_eq!(buffer[top * 256 + left + 10], buffer[(top + 63) * 256 + left + 63]);
+    assert_eq!(buffer[(top + 1) * 256 + left + 10], pixel(3, 3));
+}
+
+#[test]
+fn key_bindings() {
+    assert_eq!(viewer::get_command(Key::C, false), Some(Command::ToggleColouring));
+    assert_eq!(viewer::get_command(Key::C, true), Some(Command::Copy));
+    assert_eq!(viewer::get_command(Key::V, true), Some(Command::Paste));
+    assert_eq!(viewer::get_command(Key::Up, false), Some(Command::Pan(-1, 0)));
+    assert_eq!(viewer::get_command(Key::Q, false), None);
+
+    assert_eq!(Selection::from_corners((2, 3), (2, 3), 4, 4), Selection { row_index : 2, column_index : 3, width : 1, height : 1 });
+}
+
+#[test]
+fn rotate_at_an_edge() {
+    // a 3 x 1 row along the bottom edge would stick out once rotated, on every boundary
+    for boundary_type in [BoundaryType::Null, BoundaryType::Torus, BoundaryType::Klein].iter().copied() {
+        let page : Grid = Grid::from_vec(5, 4, vec![
+            0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0,
+            0, 0, 0, 0, 1,
+            0, 1, 1, 1, 0]).unwrap();
+        let mut gol = TCA2D::new(OuterTotalistic::new(224), 5, 4, 1, true, boundary_type, page.clone()).unwrap();
+        let mut clipboard : Option<Grid> = None;
+
+        let row = Selection { row_index : 3, column_index : 1, width : 3, height : 1 };
+        assert!(matches!(viewer::edit(&mut gol, Command::Rotate, row, &mut clipboard), Err(Error::OutOfBounds { .. })));
+        assert_eq!(gol.get_page(0).get_grid(), page.get_grid(), "{:?}", boundary_type);
+
+        // a column along the right edge too
+        let column = Selection { row_index : 1, column_index : 4, width : 1, height : 3 };
+        assert!(viewer::edit(&mut gol, Command::Rotate, column, &mut clipboard).is_err());
+        assert_eq!(gol.get_page(0).get_grid(), page.get_grid(), "{:?}", boundary_type);
+
+        // one that fits : 3 x 2 into 2 x 3, about its top left cell
+        let selection = Selection { row_index : 1, column_index : 2, width : 3, height : 2 };
+        let rotated : Selection = viewer::edit(&mut gol, Command::Rotate, selection, &mut clipboard).unwrap();
+        assert_eq!(rotated, Selection { row_index : 1, column_index : 2, width : 2, height : 3 });
+        assert_eq!(gol.get_page(0).get_grid(), &vec![
+            0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0,
+            0, 1, 1, 0, 0], "{:?}", boundary_type);
+    }
+}